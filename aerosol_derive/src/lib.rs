@@ -0,0 +1,219 @@
+#![deny(missing_docs)]
+//! Derive macros for `aerosol`. Use these via the `derive` feature of `aerosol`,
+//! rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Field, Fields, Type};
+
+/// Type names whose clones are cheap: reference counted pointers, and common `Copy`
+/// types.
+const CHEAP_TYPES: &[&str] = &[
+    "Arc",
+    "Weak",
+    "Aero",
+    "WeakAero",
+    "PhantomData",
+    "Duration",
+    "Instant",
+    "SystemTime",
+    "TypeId",
+    "bool",
+    "char",
+    "f32",
+    "f64",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+];
+
+/// Type names which are cheap to clone if their type arguments are.
+const CHEAP_WRAPPERS: &[&str] = &["Option"];
+
+/// Checks that a type is suitable for use as an aerosol resource.
+///
+/// Resources are cloned every time they are obtained, so each field must be cheap to
+/// clone: an `Arc`, a primitive, a shared reference, or an `Option`, tuple or array of
+/// those. Other fields are rejected with a compile error, unless marked with
+/// `#[aerosol(cheap_clone)]` (eg. for a field whose type is itself a cheap resource),
+/// or the type is marked with `#[aerosol(expensive_clone)]` to acknowledge the cost.
+///
+/// Also checks that the type is `Send + Sync + Clone + 'static`, so that it implements
+/// `Resource`.
+#[proc_macro_derive(Resource, attributes(aerosol))]
+pub fn derive_resource(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_resource_impl(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn derive_resource_impl(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !has_flag(&input.attrs, "expensive_clone")? {
+        let mut errors: Option<syn::Error> = None;
+        for field in fields(&input.data) {
+            if has_flag(&field.attrs, "cheap_clone")? || is_cheap(&field.ty) {
+                continue;
+            }
+            let error = syn::Error::new_spanned(
+                &field.ty,
+                format!(
+                    "`{}` may be expensive to clone, but resources are cloned every time \
+                     they are obtained. Wrap it in an `Arc`, mark the field \
+                     `#[aerosol(cheap_clone)]` if cloning it is cheap, or mark the type \
+                     `#[aerosol(expensive_clone)]` to acknowledge the cost",
+                    field.ty.to_token_stream().to_string().replace(' ', "")
+                ),
+            );
+            match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            }
+        }
+        if let Some(errors) = errors {
+            return Err(errors);
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        const _: () = {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #[allow(dead_code)]
+                fn __aerosol_assert_resource()
+                where
+                    Self: ::aerosol::Resource,
+                {
+                }
+            }
+        };
+    })
+}
+
+/// Implements `Constructible` for a struct by obtaining each of its fields from the
+/// container, constructing them if necessary. Errors are reported as an `ObtainError`.
+///
+/// A field marked with `#[aerosol(container)]` must have type `Aero`, and is filled
+/// with a handle to the container instead, so that the resource can resolve
+/// collaborators lazily. The handle shares the container's `Arc`, so the resource
+/// keeps the container alive for as long as it is stored there: use a `WeakAero`
+/// field (which is obtained like any other resource) if that is a problem.
+#[proc_macro_derive(Constructible, attributes(aerosol))]
+pub fn derive_constructible(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_constructible_impl(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn derive_constructible_impl(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`#[derive(Constructible)]` only supports structs",
+        ));
+    };
+    let mut values = Vec::new();
+    for field in &data.fields {
+        values.push(if has_flag(&field.attrs, "container")? {
+            quote! { aero.handle() }
+        } else {
+            quote! { aero.try_obtain()? }
+        });
+    }
+    let body = match &data.fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote! { Self { #(#names: #values,)* } }
+        }
+        Fields::Unnamed(_) => quote! { Self(#(#values,)*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::aerosol::Constructible for #name #ty_generics #where_clause {
+            type Error = ::aerosol::ObtainError;
+
+            fn construct(aero: &::aerosol::Aero) -> ::core::result::Result<Self, Self::Error> {
+                ::core::result::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+/// Every field of a struct, or of every variant of an enum or union.
+fn fields(data: &Data) -> Vec<&Field> {
+    match data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter())
+            .collect(),
+        Data::Union(data) => data.fields.named.iter().collect(),
+    }
+}
+
+/// Returns true if `attrs` contains `#[aerosol(flag)]`.
+fn has_flag(attrs: &[Attribute], flag: &str) -> syn::Result<bool> {
+    let mut found = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("aerosol")) {
+        attr.parse_nested_meta(|meta| {
+            if ["cheap_clone", "expensive_clone", "container"]
+                .iter()
+                .any(|known| meta.path.is_ident(known))
+            {
+                found |= meta.path.is_ident(flag);
+                Ok(())
+            } else {
+                Err(meta.error("unrecognized aerosol attribute"))
+            }
+        })?;
+    }
+    Ok(found)
+}
+
+fn is_cheap(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let Some(segment) = path.path.segments.last() else {
+                return false;
+            };
+            let name = segment.ident.to_string();
+            if CHEAP_TYPES.contains(&name.as_str()) {
+                return true;
+            }
+            if !CHEAP_WRAPPERS.contains(&name.as_str()) {
+                return false;
+            }
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().all(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => is_cheap(ty),
+                    _ => true,
+                }),
+                _ => false,
+            }
+        }
+        // Shared references are `Copy`.
+        Type::Reference(reference) => reference.mutability.is_none(),
+        Type::Tuple(tuple) => tuple.elems.iter().all(is_cheap),
+        Type::Array(array) => is_cheap(&array.elem),
+        Type::Paren(paren) => is_cheap(&paren.elem),
+        Type::Group(group) => is_cheap(&group.elem),
+        _ => false,
+    }
+}
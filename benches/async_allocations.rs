@@ -0,0 +1,90 @@
+//! Compares the allocations made when constructing a transient resource through
+//! `AsyncConstructible` (boxed) and `UnboxedAsyncConstructible` (unboxed).
+//!
+//! Run with `cargo bench --features async --bench async_allocations`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    convert::Infallible,
+    future::Future,
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use aerosol::{Aero, AsyncConstructible, Lifetime, UnboxedAsyncConstructible};
+use async_trait::async_trait;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Clone)]
+struct RequestId(u64);
+
+#[async_trait]
+impl AsyncConstructible for RequestId {
+    type Error = Infallible;
+
+    async fn construct_async(_aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(RequestId(42))
+    }
+}
+
+impl UnboxedAsyncConstructible for RequestId {
+    type Error = Infallible;
+
+    async fn construct_unboxed(_aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(RequestId(42))
+    }
+}
+
+const ITERATIONS: usize = 100_000;
+
+fn measure<F: Future<Output = RequestId>>(
+    runtime: &tokio::runtime::Runtime,
+    name: &str,
+    mut obtain: impl FnMut() -> F,
+) {
+    runtime.block_on(async {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(obtain().await.0);
+        }
+        let elapsed = start.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+        println!(
+            "{name:>8}: {:>6.1} ns/iter, {:.2} allocations/iter",
+            elapsed.as_nanos() as f64 / ITERATIONS as f64,
+            allocations as f64 / ITERATIONS as f64,
+        );
+    });
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let aero = Aero::new();
+    aero.register::<RequestId>(Lifetime::Transient);
+
+    measure(&runtime, "boxed", || aero.obtain_async::<RequestId>());
+    measure(&runtime, "unboxed", || {
+        aero.obtain_unboxed_async::<RequestId>()
+    });
+}
@@ -0,0 +1,75 @@
+//! Compares `init()` and `obtain()` on a large resource which is already present.
+//! `init()` only checks that the resource is present, so it should not clone it.
+//!
+//! Run with `cargo bench --bench init_presence`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    convert::Infallible,
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use aerosol::{Aero, Constructible};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Clone)]
+struct Blob(Vec<u8>);
+
+impl Constructible for Blob {
+    type Error = Infallible;
+
+    fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(Blob(vec![0; 1 << 20]))
+    }
+}
+
+const ITERATIONS: usize = 10_000;
+
+fn measure(name: &str, mut f: impl FnMut()) -> usize {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{name:>8}: {:>9.1} ns/iter, {:.2} allocations/iter",
+        elapsed.as_nanos() as f64 / ITERATIONS as f64,
+        allocations as f64 / ITERATIONS as f64,
+    );
+    allocations
+}
+
+fn main() {
+    let aero = Aero::new();
+    aero.init::<Blob>();
+
+    let allocations = measure("init", || aero.init::<Blob>());
+    assert_eq!(
+        allocations, 0,
+        "`init()` cloned a resource which was present"
+    );
+    measure("obtain", || {
+        black_box(aero.obtain::<Blob>().0.len());
+    });
+}
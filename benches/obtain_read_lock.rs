@@ -0,0 +1,80 @@
+//! Checks that obtaining a resource which is already present only takes the read lock,
+//! and measures how long it takes.
+//!
+//! Another thread holds a read guard for the duration of the benchmark, so any attempt
+//! to take the write lock stalls until the guard is released after a timeout, which is
+//! reported as a failure.
+//!
+//! Run with `cargo bench --bench obtain_read_lock`.
+
+use std::{
+    convert::Infallible,
+    hint::black_box,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use aerosol::{Aero, Constructible};
+
+#[derive(Clone)]
+struct Client(Arc<str>);
+
+impl Constructible for Client {
+    type Error = Infallible;
+
+    fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(Client("client".into()))
+    }
+}
+
+const ITERATIONS: usize = 1_000_000;
+
+fn measure(aero: &Aero, name: &str, mut f: impl FnMut()) {
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let holder = thread::spawn({
+        let aero = aero.clone();
+        move || {
+            let _guard = aero.read();
+            ready_tx.send(()).unwrap();
+            // Only times out if the benchmark is stalled waiting for the write lock.
+            done_rx.recv_timeout(Duration::from_secs(5)) == Err(RecvTimeoutError::Timeout)
+        }
+    });
+    ready_rx.recv().unwrap();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    drop(done_tx);
+    let stalled = holder.join().unwrap();
+
+    println!(
+        "{name:>14}: {:>6.1} ns/iter",
+        elapsed.as_nanos() as f64 / ITERATIONS as f64,
+    );
+    assert!(
+        !stalled,
+        "`{}` took the write lock for a resource which was present",
+        name
+    );
+}
+
+fn main() {
+    let aero = Aero::new();
+    aero.init::<Client>();
+
+    measure(&aero, "obtain", || {
+        black_box(aero.obtain::<Client>().0.len());
+    });
+    measure(&aero, "init", || aero.init::<Client>());
+    measure(&aero, "try_obtain", || {
+        black_box(aero.try_obtain::<Client>().unwrap().0.len());
+    });
+}
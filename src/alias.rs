@@ -0,0 +1,103 @@
+#[cfg(feature = "async")]
+use std::any::TypeId;
+use std::sync::Arc;
+
+use crate::{
+    resource::{Resource, ResourceList},
+    slot::SlotDesc,
+    state::{Aero, InnerAero},
+};
+
+type AliasFn<A> = Arc<dyn Fn(&Aero) -> Option<A> + Send + Sync>;
+type LockedAliasFn<A> = Arc<dyn Fn(&InnerAero) -> Option<A> + Send + Sync>;
+
+/// Resolves lookups for one resource type to an instance of another.
+pub(crate) struct Alias<A> {
+    /// Looks up the aliased resource, waiting for it if it is under construction.
+    pub get: AliasFn<A>,
+    /// Looks up the aliased resource in an instance which is already locked.
+    pub get_locked: LockedAliasFn<A>,
+    /// See `Aero::subscription_sources()`.
+    #[cfg(feature = "async")]
+    pub sources: fn(&Aero, &mut Vec<(Aero, TypeId)>),
+}
+
+impl<A> Clone for Alias<A> {
+    fn clone(&self) -> Self {
+        Self {
+            get: self.get.clone(),
+            get_locked: self.get_locked.clone(),
+            #[cfg(feature = "async")]
+            sources: self.sources,
+        }
+    }
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Make lookups for the resource type `A` resolve to the instance of `C`, converted
+    /// using `convert`, whenever there is no instance of `A` itself. Typically used to
+    /// expose a concrete resource as a trait object without storing a second copy:
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use aerosol::Aero;
+    /// trait Database: Send + Sync {}
+    /// struct PostgresPool;
+    /// impl Database for PostgresPool {}
+    ///
+    /// let aero = Aero::new().with(Arc::new(PostgresPool));
+    /// aero.alias::<Arc<PostgresPool>, Arc<dyn Database>>(|pool| pool);
+    /// assert!(aero.has::<Arc<dyn Database>>());
+    /// ```
+    ///
+    /// `convert` is called on every lookup, so should be cheap. Replaces any previous
+    /// alias for `A`.
+    pub fn alias<C: Resource, A: Resource>(&self, convert: fn(C) -> A) {
+        let alias = Alias {
+            get: Arc::new(move |aero: &Aero| aero.try_get::<C>().map(convert)),
+            get_locked: Arc::new(move |inner: &InnerAero| match inner.get_slot::<C>()? {
+                SlotDesc::Filled(x) => Some(convert(x)),
+                SlotDesc::Placeholder | SlotDesc::Failed(_) => None,
+            }),
+            #[cfg(feature = "async")]
+            sources: Aero::subscription_sources::<C>,
+        };
+        self.inner.write().aliases.insert(alias);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::Aero;
+
+    trait Database: Send + Sync {
+        fn name(&self) -> &'static str;
+    }
+
+    struct PostgresPool;
+
+    impl Database for PostgresPool {
+        fn name(&self) -> &'static str {
+            "postgres"
+        }
+    }
+
+    #[test]
+    fn alias() {
+        let state = Aero::new();
+        state.alias::<Arc<PostgresPool>, Arc<dyn Database>>(|pool| pool);
+        assert!(!state.has::<Arc<dyn Database>>());
+        assert!(state.try_get::<Arc<dyn Database>>().is_none());
+
+        let pool = Arc::new(PostgresPool);
+        state.insert(pool.clone());
+        let db = state.try_get::<Arc<dyn Database>>().unwrap();
+        assert_eq!(db.name(), "postgres");
+        assert_eq!(
+            Arc::as_ptr(&db) as *const (),
+            Arc::as_ptr(&pool) as *const ()
+        );
+    }
+}
@@ -1,30 +1,93 @@
 use std::{
+    any::TypeId,
     future::Future,
     marker::PhantomData,
     pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
     task::{Context, Poll},
 };
 
 use crate::{
     resource::{Resource, ResourceList},
-    slot::SlotDesc,
-    state::Aero,
+    slot::{SlotDesc, SlotResult},
+    state::{Aero, WaitRegistration},
 };
 
-pub(crate) struct WaitForSlot<R: ResourceList, T: Resource> {
+pub(crate) struct WaitForSlot<R: ResourceList, T: Resource, U = T> {
     state: Aero<R>,
-    wait_index: Option<usize>,
+    registration: Option<WaitRegistration>,
     insert_placeholder: bool,
+    map: fn(&T) -> U,
+}
+
+impl<R: ResourceList, T: Resource, U> Future for WaitForSlot<R, T, U> {
+    type Output = SlotResult<U>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.state.poll_for_slot(
+            &mut this.registration,
+            || cx.waker(),
+            this.insert_placeholder,
+            this.map,
+        )
+    }
+}
+
+impl<R: ResourceList, T: Resource, U> Drop for WaitForSlot<R, T, U> {
+    fn drop(&mut self) {
+        if let Some(registration) = self.registration.take() {
+            self.state.cancel_wait(registration);
+        }
+    }
+}
+
+/// Source of unique IDs for `Subscribe` futures.
+static NEXT_SUBSCRIPTION: AtomicU64 = AtomicU64::new(0);
+
+struct Subscribe<R: ResourceList, T: Resource> {
+    state: Aero<R>,
+    id: u64,
+    /// Where this future's waker is currently registered.
+    registered: Vec<(Aero, TypeId)>,
     phantom: PhantomData<fn() -> T>,
 }
 
-impl<R: ResourceList, T: Resource> Future for WaitForSlot<R, T> {
-    type Output = Option<T>;
+impl<R: ResourceList, T: Resource> Subscribe<R, T> {
+    fn deregister(&mut self) {
+        for (aero, id) in self.registered.drain(..) {
+            aero.remove_subscriber(id, self.id);
+        }
+    }
+}
+
+impl<R: ResourceList, T: Resource> Future for Subscribe<R, T> {
+    type Output = T;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
-        this.state
-            .poll_for_slot(&mut this.wait_index, || cx.waker(), this.insert_placeholder)
+        // Re-register on every poll, since the waker or the instances `T` could be
+        // found in may have changed.
+        this.deregister();
+        this.state.subscription_sources::<T>(&mut this.registered);
+        for (aero, id) in &this.registered {
+            aero.add_subscriber(*id, this.id, cx.waker());
+        }
+        // Check after registering, so that a resource inserted in between is not missed.
+        match this.state.try_get_slot::<T>() {
+            Some(SlotDesc::Filled(x)) => {
+                this.deregister();
+                this.state.mark_accessed::<T>();
+                Poll::Ready(x)
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
+
+impl<R: ResourceList, T: Resource> Drop for Subscribe<R, T> {
+    fn drop(&mut self) {
+        self.deregister();
     }
 }
 
@@ -35,17 +98,44 @@ impl<R: ResourceList> Aero<R> {
     ) -> WaitForSlot<R, T> {
         WaitForSlot {
             state: self.clone(),
-            wait_index: None,
+            registration: None,
             insert_placeholder,
-            phantom: PhantomData,
+            map: T::clone,
+        }
+    }
+    /// Like `wait_for_slot_async`, but only reports whether the slot is filled,
+    /// without cloning the resource.
+    pub(crate) fn wait_for_presence_async<T: Resource>(
+        &self,
+        insert_placeholder: bool,
+    ) -> WaitForSlot<R, T, ()> {
+        WaitForSlot {
+            state: self.clone(),
+            registration: None,
+            insert_placeholder,
+            map: |_| (),
         }
     }
     /// Tries to get an instance of `T` from the AppState. Returns `None` if there is no such instance.
     /// This function does not attempt to construct `T` if it does not exist.
     pub async fn try_get_async<T: Resource>(&self) -> Option<T> {
-        match self.try_get_slot()? {
+        let x = match self.try_get_slot()? {
             SlotDesc::Filled(x) => Some(x),
-            SlotDesc::Placeholder => self.wait_for_slot_async::<T>(false).await,
+            SlotDesc::Placeholder => self.wait_for_slot_async::<T>(false).await.ok().flatten(),
+            SlotDesc::Failed(_) => None,
+        }?;
+        self.mark_accessed::<T>();
+        Some(x)
+    }
+    /// Returns a future which resolves once an instance of `T` is available, whether it was
+    /// constructed or inserted directly. This never attempts to construct `T` itself, so
+    /// can be used to wait for a resource provided by another part of the application.
+    pub fn subscribe<T: Resource>(&self) -> impl Future<Output = T> {
+        Subscribe {
+            state: self.clone(),
+            id: NEXT_SUBSCRIPTION.fetch_add(1, Ordering::Relaxed),
+            registered: Vec::new(),
+            phantom: PhantomData,
         }
     }
 }
@@ -65,4 +155,52 @@ mod tests {
         let state = Aero::new().with("Hello");
         assert_eq!(state.try_get_async::<i32>().await, None);
     }
+
+    #[tokio::test]
+    async fn subscribe_present() {
+        let state = Aero::new().with(42);
+        assert_eq!(state.subscribe::<i32>().await, 42);
+    }
+
+    #[tokio::test]
+    async fn subscribe_insert() {
+        let state = Aero::new();
+        let handle = tokio::spawn(state.subscribe::<i32>());
+        tokio::task::yield_now().await;
+        state.insert(42);
+        assert_eq!(handle.await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn subscribe_parent_and_alias() {
+        let parent = Aero::new();
+        let child = parent.scope();
+        child.alias::<i32, i64>(i64::from);
+        let handle = tokio::spawn(child.subscribe::<i64>());
+        tokio::task::yield_now().await;
+        parent.insert(42);
+        assert_eq!(handle.await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn subscribe_dropped() {
+        let state = Aero::new();
+        for _ in 0..3 {
+            tokio::select! {
+                _ = state.subscribe::<i32>() => panic!("Resource should not exist"),
+                _ = tokio::task::yield_now() => {}
+            }
+        }
+        assert!(state.inner.read().subscribers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn subscribe_select() {
+        let state = Aero::new();
+        tokio::select! {
+            _ = state.subscribe::<i32>() => panic!("Resource should not exist"),
+            _ = tokio::task::yield_now() => {}
+        }
+        assert!(!state.has::<i32>());
+    }
 }
@@ -1,23 +1,99 @@
-use std::{any::Any, marker::PhantomData, sync::Arc};
+use std::{
+    any::{type_name, Any, TypeId},
+    future::{pending, poll_fn, Future},
+    marker::PhantomData,
+    pin::{pin, Pin},
+    sync::Arc,
+    task::Poll,
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use frunk::{hlist::Sculptor, HCons, HNil};
+use futures_util::{
+    future::{BoxFuture, FutureExt, Shared},
+    StreamExt,
+};
 
 use crate::{
-    resource::{unwrap_constructed, unwrap_constructed_hlist, Resource, ResourceList},
+    resource::{unwrap_constructed, unwrap_constructed_hlist, ObtainError, Resource, ResourceList},
     slot::SlotDesc,
-    state::Aero,
-    sync_constructible::Constructible,
+    state::{Aero, Lookup, PlaceholderGuard},
+    sync_constructible::{ConstructCtx, Constructible, ConstructibleResource},
 };
 
+/// Future returned from `Aero::obtain_shared_future()`. Requires feature `async`.
+pub type SharedResourceFuture<T> = Shared<Pin<Box<dyn Future<Output = T> + Send>>>;
+
+/// Runs a closure on a thread where blocking is acceptable. See
+/// `Aero::set_blocking_executor()`.
+pub(crate) type BlockingExecutor = Arc<dyn Fn(Box<dyn FnOnce() + Send>) + Send + Sync>;
+
+/// Returns a future which completes after the given duration. See `Aero::set_timer()`.
+pub(crate) type Timer = Arc<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>;
+
 /// Implemented for values which can be constructed asynchronously from other
 /// resources. Requires feature `async`.
+///
+/// The future returned by `construct_async` is boxed, which costs one allocation
+/// each time a resource is constructed. For singleton resources this only happens
+/// once. Resources registered as `Lifetime::Transient` are constructed on every
+/// `obtain_async()`, so if construction is on a hot path, implement
+/// `UnboxedAsyncConstructible` instead.
+///
+/// `Arc<T>` and the lock types are constructible whenever `T` is. `Box<T>` is only
+/// constructible automatically when `T` is synchronously constructible, because `Box`
+/// is a fundamental type and a generic implementation would overlap with that one.
+/// For a resource which can only be constructed asynchronously, implement this trait
+/// for `Box<T>` directly, by boxing the result of `T::construct_async()`.
 #[async_trait]
 pub trait AsyncConstructible: Sized + Any + Send + Sync {
     /// Error type for when resource fails to be constructed.
     type Error: Into<anyhow::Error> + Send + Sync;
+    /// Name used to identify this resource in the `tracing` span emitted whilst it is
+    /// being constructed, eg. `"app::db"`. Defaults to the full name of the type.
+    const TRACING_NAME: Option<&'static str> = None;
+    /// How long construction of this resource may take before `try_obtain_async()`
+    /// gives up with `ObtainError::ConstructTimedOut`, dropping the constructor's future.
+    /// Only enforced if a timer is available, see `Aero::set_timer()`. Defaults to no
+    /// timeout.
+    const CONSTRUCT_TIMEOUT: Option<Duration> = None;
     /// Construct the resource with the provided application state.
     async fn construct_async(aero: &Aero) -> Result<Self, Self::Error>;
+    /// Construct the resource with access to the context it is being constructed in.
+    /// Override this instead of `construct_async()` if the context is needed, eg. to
+    /// stop early when construction is cancelled. Defaults to calling `construct_async()`.
+    ///
+    /// ```rust
+    /// # use aerosol::{Aero, AsyncConstructible, ConstructCtx, ObtainError};
+    /// # use async_trait::async_trait;
+    /// # async fn run_migrations() {}
+    /// #[derive(Clone)]
+    /// struct Migrated;
+    ///
+    /// #[async_trait]
+    /// impl AsyncConstructible for Migrated {
+    ///     type Error = ObtainError;
+    ///
+    ///     async fn construct_async(_aero: &Aero) -> Result<Self, Self::Error> {
+    ///         run_migrations().await;
+    ///         Ok(Migrated)
+    ///     }
+    ///
+    ///     async fn construct_async_in_ctx(
+    ///         _aero: &Aero,
+    ///         ctx: &ConstructCtx,
+    ///     ) -> Result<Self, Self::Error> {
+    ///         tokio::select! {
+    ///             _ = run_migrations() => Ok(Migrated),
+    ///             _ = ctx.cancel_token().cancelled() => Err(ctx.check_cancelled().unwrap_err()),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    async fn construct_async_in_ctx(aero: &Aero, _ctx: &ConstructCtx) -> Result<Self, Self::Error> {
+        Self::construct_async(aero).await
+    }
     /// Called after construction with the concrete resource to allow the callee
     /// to provide additional resources. Can be used by eg. an `Arc<Foo>` to also
     /// provide an implementation of `Arc<dyn Bar>`.
@@ -32,8 +108,13 @@ pub trait AsyncConstructible: Sized + Any + Send + Sync {
 #[async_trait]
 impl<T: Constructible> AsyncConstructible for T {
     type Error = <T as Constructible>::Error;
+    const TRACING_NAME: Option<&'static str> = <T as Constructible>::TRACING_NAME;
+    const CONSTRUCT_TIMEOUT: Option<Duration> = <T as Constructible>::CONSTRUCT_TIMEOUT;
     async fn construct_async(aero: &Aero) -> Result<Self, Self::Error> {
-        Self::construct(aero)
+        Self::construct_in_ctx(aero, &ConstructCtx::current(aero))
+    }
+    async fn construct_async_in_ctx(aero: &Aero, ctx: &ConstructCtx) -> Result<Self, Self::Error> {
+        Self::construct_in_ctx(aero, ctx)
     }
     async fn after_construction_async(
         this: &(dyn Any + Send + Sync),
@@ -43,12 +124,97 @@ impl<T: Constructible> AsyncConstructible for T {
     }
 }
 
+/// Implemented for values which can be constructed asynchronously from other
+/// resources without boxing the constructor's future. Requires feature `async`.
+///
+/// Obtain these resources with `Aero::obtain_unboxed_async()`, which avoids the
+/// allocation made by `obtain_async()` each time a `Lifetime::Transient` resource is
+/// constructed. The rest of the asynchronous API, eg. `obtain_async()` and
+/// `construct_remaining_async()`, only accepts `AsyncConstructible` resources. To make
+/// a resource available there too, implement `AsyncConstructible` as well by awaiting
+/// `construct_unboxed()` from `construct_async()`.
+///
+/// ```rust
+/// # use aerosol::{Aero, Lifetime, UnboxedAsyncConstructible};
+/// # use std::convert::Infallible;
+/// #[derive(Clone)]
+/// struct RequestId(u64);
+///
+/// impl UnboxedAsyncConstructible for RequestId {
+///     type Error = Infallible;
+///
+///     async fn construct_unboxed(_aero: &Aero) -> Result<Self, Self::Error> {
+///         Ok(RequestId(42))
+///     }
+/// }
+///
+/// # async fn example() {
+/// let aero = Aero::new();
+/// aero.register::<RequestId>(Lifetime::Transient);
+/// let id: RequestId = aero.obtain_unboxed_async().await;
+/// # }
+/// ```
+pub trait UnboxedAsyncConstructible: Sized + Any + Send + Sync {
+    /// Error type for when resource fails to be constructed.
+    type Error: Into<anyhow::Error> + Send + Sync;
+    /// Name used to identify this resource in `tracing` spans. See
+    /// `AsyncConstructible::TRACING_NAME`.
+    const TRACING_NAME: Option<&'static str> = None;
+    /// Construction timeout. See `AsyncConstructible::CONSTRUCT_TIMEOUT`.
+    const CONSTRUCT_TIMEOUT: Option<Duration> = None;
+    /// Construct the resource with the provided application state.
+    fn construct_unboxed(
+        aero: &Aero,
+    ) -> impl Future<Output = Result<Self, Self::Error>> + Send + '_;
+}
+
+/// How a resource `T` is constructed asynchronously, so that boxed and unboxed
+/// constructors share the rest of the construction logic.
+trait AsyncConstructor<T> {
+    type Error: Into<anyhow::Error>;
+    #[cfg(feature = "tracing")]
+    const TRACING_NAME: Option<&'static str>;
+    const CONSTRUCT_TIMEOUT: Option<Duration>;
+    fn construct(aero: &Aero) -> impl Future<Output = Result<T, Self::Error>> + Send + '_;
+}
+
+/// Constructs resources via `IndirectlyAsyncConstructible`.
+struct Boxed;
+
+impl<T: IndirectlyAsyncConstructible> AsyncConstructor<T> for Boxed {
+    type Error = T::Error;
+    #[cfg(feature = "tracing")]
+    const TRACING_NAME: Option<&'static str> = T::TRACING_NAME;
+    const CONSTRUCT_TIMEOUT: Option<Duration> = T::CONSTRUCT_TIMEOUT;
+    fn construct(aero: &Aero) -> impl Future<Output = Result<T, Self::Error>> + Send + '_ {
+        T::construct_async(aero)
+    }
+}
+
+/// Constructs resources via `UnboxedAsyncConstructible`.
+struct Unboxed;
+
+impl<T: UnboxedAsyncConstructible> AsyncConstructor<T> for Unboxed {
+    type Error = T::Error;
+    #[cfg(feature = "tracing")]
+    const TRACING_NAME: Option<&'static str> = T::TRACING_NAME;
+    const CONSTRUCT_TIMEOUT: Option<Duration> = T::CONSTRUCT_TIMEOUT;
+    fn construct(aero: &Aero) -> impl Future<Output = Result<T, Self::Error>> + Send + '_ {
+        T::construct_unboxed(aero)
+    }
+}
+
 /// Automatically implemented for values which can be indirectly asynchronously constructed from other resources.
 /// Requires feature `async`.
 #[async_trait]
 pub trait IndirectlyAsyncConstructible: Sized + Any + Send + Sync {
     /// Error type for when resource fails to be constructed.
     type Error: Into<anyhow::Error> + Send + Sync;
+    /// Name used to identify this resource in `tracing` spans. See
+    /// `AsyncConstructible::TRACING_NAME`.
+    const TRACING_NAME: Option<&'static str> = None;
+    /// Construction timeout. See `AsyncConstructible::CONSTRUCT_TIMEOUT`.
+    const CONSTRUCT_TIMEOUT: Option<Duration> = None;
     /// Construct the resource with the provided application state.
     async fn construct_async(aero: &Aero) -> Result<Self, Self::Error>;
     /// Called after construction with the concrete resource to allow the callee
@@ -65,9 +231,12 @@ pub trait IndirectlyAsyncConstructible: Sized + Any + Send + Sync {
 #[async_trait]
 impl<T: AsyncConstructible> IndirectlyAsyncConstructible for T {
     type Error = T::Error;
+    const TRACING_NAME: Option<&'static str> = T::TRACING_NAME;
+    const CONSTRUCT_TIMEOUT: Option<Duration> = T::CONSTRUCT_TIMEOUT;
 
     async fn construct_async(aero: &Aero) -> Result<Self, Self::Error> {
-        let res = <T as AsyncConstructible>::construct_async(aero).await?;
+        let ctx = ConstructCtx::current(aero);
+        let res = <T as AsyncConstructible>::construct_async_in_ctx(aero, &ctx).await?;
         <T as AsyncConstructible>::after_construction_async(&res, aero).await?;
         Ok(res)
     }
@@ -79,12 +248,17 @@ impl<T: AsyncConstructible> IndirectlyAsyncConstructible for T {
     }
 }
 
+// `Box<T>` cannot be listed here, as it would overlap with the blanket implementation
+// above: `Box<T>` is already `AsyncConstructible` when `T` is `Constructible`, and
+// downstream crates may implement `AsyncConstructible` for `Box<T>` themselves.
 macro_rules! impl_async_constructible {
     (<$t:ident>; $($x:ty: $y:expr;)*) => {
         $(
             #[async_trait]
             impl<$t: IndirectlyAsyncConstructible> IndirectlyAsyncConstructible for $x {
                 type Error = $t::Error;
+                const TRACING_NAME: Option<&'static str> = $t::TRACING_NAME;
+                const CONSTRUCT_TIMEOUT: Option<Duration> = $t::CONSTRUCT_TIMEOUT;
 
                 async fn construct_async(aero: &Aero) -> Result<Self, Self::Error> {
                     let res = $y($t::construct_async(aero).await?);
@@ -102,11 +276,24 @@ macro_rules! impl_async_constructible {
 impl_async_constructible! {
     <T>;
     Arc<T>: Arc::new;
+}
+#[cfg(feature = "std-locks")]
+impl_async_constructible! {
+    <T>;
     std::sync::Mutex<T>: std::sync::Mutex::new;
-    parking_lot::Mutex<T>: parking_lot::Mutex::new;
     std::sync::RwLock<T>: std::sync::RwLock::new;
+}
+#[cfg(feature = "parking-lot")]
+impl_async_constructible! {
+    <T>;
+    parking_lot::Mutex<T>: parking_lot::Mutex::new;
     parking_lot::RwLock<T>: parking_lot::RwLock::new;
 }
+#[cfg(feature = "watch")]
+impl_async_constructible! {
+    <T>;
+    crate::Watchable<T>: crate::Watchable::new;
+}
 
 /// Implemented for resources which can be asynchronously constructed from other resources. Requires feature `async`.
 /// Do not implement this trait directly, instead implement `AsyncConstructible` and ensure
@@ -119,6 +306,19 @@ impl<T: Resource + IndirectlyAsyncConstructible> AsyncConstructibleResource for
 pub trait AsyncConstructibleResourceList: ResourceList {
     /// Construct every resource in this list in the provided aerosol instance
     async fn construct_async<R: ResourceList>(aero: &Aero<R>) -> anyhow::Result<()>;
+    /// Append an asynchronous initializer for every resource in this list to `out`, so
+    /// that they can be scheduled at runtime.
+    #[doc(hidden)]
+    fn async_initializers(out: &mut Vec<AsyncInitializer>);
+}
+
+/// Type-erased asynchronous initializer for a single resource, see
+/// `AsyncConstructibleResourceList::async_initializers()`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncInitializer {
+    pub(crate) id: TypeId,
+    pub(crate) init: for<'a> fn(&'a Aero) -> BoxFuture<'a, Result<(), ObtainError>>,
 }
 
 #[async_trait]
@@ -126,6 +326,7 @@ impl AsyncConstructibleResourceList for HNil {
     async fn construct_async<R: ResourceList>(_aero: &Aero<R>) -> anyhow::Result<()> {
         Ok(())
     }
+    fn async_initializers(_out: &mut Vec<AsyncInitializer>) {}
 }
 
 #[async_trait]
@@ -133,60 +334,357 @@ impl<H: AsyncConstructibleResource, T: AsyncConstructibleResourceList>
     AsyncConstructibleResourceList for HCons<H, T>
 {
     async fn construct_async<R: ResourceList>(aero: &Aero<R>) -> anyhow::Result<()> {
-        aero.try_init_async::<H>().await.map_err(Into::into)?;
+        aero.try_init_async::<H>().await?;
         T::construct_async(aero).await
     }
+    fn async_initializers(out: &mut Vec<AsyncInitializer>) {
+        out.push(AsyncInitializer {
+            id: TypeId::of::<H>(),
+            init: |aero| Box::pin(aero.try_init_async::<H>()),
+        });
+        T::async_initializers(out)
+    }
 }
 
 impl<R: ResourceList> Aero<R> {
-    /// Try to get or construct an instance of `T` asynchronously. Requires feature `async`.
-    pub async fn try_obtain_async<T: AsyncConstructibleResource>(&self) -> Result<T, T::Error> {
-        match self.try_get_slot() {
-            Some(SlotDesc::Filled(x)) => Ok(x),
-            Some(SlotDesc::Placeholder) | None => match self.wait_for_slot_async::<T>(true).await {
-                Some(x) => Ok(x),
-                None => match T::construct_async(self.as_ref()).await {
-                    Ok(x) => {
-                        self.fill_placeholder::<T>(x.clone());
-                        Ok(x)
-                    }
-                    Err(e) => {
-                        self.clear_placeholder::<T>();
-                        Err(e)
-                    }
-                },
-            },
+    /// Construct a new instance of `T` asynchronously, applying everything that
+    /// `obtain_async()` applies: the sealed check, construction count, tracing span,
+    /// depth limit, timeout and decorators. The caller decides where to store it.
+    async fn construct_decorated_async<T: Resource, C: AsyncConstructor<T>>(
+        &self,
+    ) -> Result<T, ObtainError> {
+        self.check_sealed::<T>()?;
+        self.record_construction::<T>()?;
+        let construct = C::construct(self.as_ref());
+        // Attribute events emitted whilst the constructor is polled to a span within
+        // the caller's span. Work which the constructor spawns onto other tasks is not
+        // covered, unless the constructor instruments it with the current span itself.
+        #[cfg(feature = "tracing")]
+        let construct = tracing::Instrument::instrument(
+            construct,
+            tracing::debug_span!(
+                "construct",
+                resource = C::TRACING_NAME.unwrap_or_else(type_name::<T>)
+            ),
+        );
+        let construct = self.construct_with_depth::<T, _>(construct);
+        match self
+            .run_with_timeout::<T, _>(C::CONSTRUCT_TIMEOUT, construct)
+            .await
+        {
+            Ok(Ok(x)) => Ok(self.decorate(x)),
+            Ok(Err(e)) => Err(self.construct_failed::<T>(e)),
+            Err(e) => Err(self.construct_failed::<T>(e)),
         }
     }
+    /// Construct a new instance of `T` asynchronously which is not stored.
+    async fn construct_transient_async<T: Resource, C: AsyncConstructor<T>>(
+        &self,
+    ) -> Result<T, ObtainError> {
+        let x = self.construct_decorated_async::<T, C>().await?;
+        self.run_post_construct(&x);
+        Ok(x)
+    }
+    /// The timer used to enforce `CONSTRUCT_TIMEOUT`, if any.
+    fn timer(&self) -> Option<Timer> {
+        match self.inner.read().timer.clone() {
+            Some(timer) => Some(timer),
+            #[cfg(feature = "tokio")]
+            None => Some(Arc::new(|duration| tokio::time::sleep(duration).boxed())),
+            #[cfg(not(feature = "tokio"))]
+            None => None,
+        }
+    }
+    /// Drive `construct` to completion, giving up with `ObtainError::ConstructTimedOut` if
+    /// `timeout` elapses first. The timeout is ignored if no timer is available.
+    async fn run_with_timeout<T: Resource, F: Future>(
+        &self,
+        timeout: Option<Duration>,
+        construct: F,
+    ) -> Result<F::Output, ObtainError> {
+        let mut sleep = match timeout.and_then(|timeout| Some(self.timer()?(timeout))) {
+            Some(sleep) => sleep,
+            None => return Ok(construct.await),
+        };
+        let mut construct = pin!(construct);
+        poll_fn(|cx| match construct.as_mut().poll(cx) {
+            Poll::Ready(res) => Poll::Ready(Ok(res)),
+            Poll::Pending => sleep
+                .as_mut()
+                .poll(cx)
+                .map(|()| Err(ObtainError::ConstructTimedOut(type_name::<T>()))),
+        })
+        .await
+    }
+    /// Drive a constructor for `T`. Each poll counts towards the construction depth of
+    /// the polling thread, since nested constructors are polled recursively.
+    async fn construct_with_depth<T: Resource, F: Future>(&self, construct: F) -> F::Output {
+        let mut construct = pin!(construct);
+        poll_fn(|cx| {
+            let _depth = self.enter_construction::<T>();
+            construct.as_mut().poll(cx)
+        })
+        .await
+    }
+    /// Construct `T` into the placeholder owned by the current task.
+    async fn construct_placeholder_async<T: Resource, C: AsyncConstructor<T>>(
+        &self,
+    ) -> Result<T, ObtainError> {
+        let guard = PlaceholderGuard::new(self);
+        let x = match self.construct_decorated_async::<T, C>().await {
+            Ok(x) => x,
+            Err(e) => return Err(guard.fail(e)),
+        };
+        guard.fill(x.clone());
+        self.run_post_construct(&x);
+        Ok(x)
+    }
+    /// Get or construct `T`, giving up with `ObtainError::WaitTimedOut` if `timeout`
+    /// completes whilst waiting for another thread or task to finish constructing `T`.
+    /// Pass `std::future::pending()` to wait indefinitely.
+    async fn obtain_async_with<T: Resource, C: AsyncConstructor<T>>(
+        &self,
+        timeout: impl Future<Output = ()>,
+    ) -> Result<T, ObtainError> {
+        let slot = match self.lookup_for_obtain::<T>() {
+            Lookup::Transient => return self.construct_transient_async::<T, C>().await,
+            Lookup::Linked(source) => {
+                // Boxed, since this recurses into another instance.
+                return Box::pin(source.obtain_async_with::<T, C>(timeout)).await;
+            }
+            Lookup::Slot(slot) => slot,
+        };
+        let x = match slot {
+            Some(SlotDesc::Filled(x)) => x,
+            Some(SlotDesc::Failed(e)) => return Err(ObtainError::Failed(e)),
+            Some(SlotDesc::Placeholder) | None => {
+                let mut wait = self.wait_for_slot_async::<T>(true);
+                let mut timeout = pin!(timeout);
+                let res = poll_fn(|cx| match Pin::new(&mut wait).poll(cx) {
+                    Poll::Ready(res) => Poll::Ready(Ok(res)),
+                    Poll::Pending => timeout.as_mut().poll(cx).map(Err),
+                })
+                .await
+                .map_err(|()| ObtainError::WaitTimedOut(type_name::<T>()))?;
+                match res.map_err(ObtainError::Failed)? {
+                    Some(x) => x,
+                    None => self.construct_placeholder_async::<T, C>().await?,
+                }
+            }
+        };
+        self.mark_accessed::<T>();
+        Ok(x)
+    }
+    /// Try to get or construct an instance of `T` asynchronously. Requires feature `async`.
+    pub async fn try_obtain_async<T: AsyncConstructibleResource>(&self) -> Result<T, ObtainError> {
+        self.obtain_async_with::<T, Boxed>(pending()).await
+    }
+    /// Construct a fresh instance of `T` asynchronously against `overrides` layered over
+    /// `self`. See `try_obtain_with_overrides()`. Requires feature `async`.
+    pub async fn try_obtain_with_overrides_async<T: AsyncConstructibleResource>(
+        &self,
+        overrides: Aero,
+    ) -> Result<T, ObtainError> {
+        let waker = poll_fn(|cx| Poll::Ready(cx.waker().clone())).await;
+        overrides.layer_overrides::<T>(self.clone().into(), (&waker).into());
+        overrides.construct_placeholder_async::<T, Boxed>().await
+    }
+    /// Try to get or construct an instance of `T` asynchronously, without boxing the
+    /// future returned by its constructor. Requires feature `async`.
+    pub async fn try_obtain_unboxed_async<T: Resource + UnboxedAsyncConstructible>(
+        &self,
+    ) -> Result<T, ObtainError> {
+        self.obtain_async_with::<T, Unboxed>(pending()).await
+    }
+    /// Get or construct an instance of `T` asynchronously, without boxing the future
+    /// returned by its constructor. Panics if unable. Requires feature `async`.
+    pub async fn obtain_unboxed_async<T: Resource + UnboxedAsyncConstructible>(&self) -> T {
+        unwrap_constructed::<T, _>(self.try_obtain_unboxed_async::<T>().await)
+    }
+    /// Try to get or construct an instance of `T` asynchronously, giving up with
+    /// `ObtainError::WaitTimedOut` if `timeout` completes whilst waiting for another
+    /// thread or task to finish constructing `T`. Once this task starts constructing
+    /// `T` itself, the timeout no longer applies. `timeout` can be any future, for
+    /// example `tokio::time::sleep(duration)`. Requires feature `async`.
+    pub async fn try_obtain_timeout_async<T: AsyncConstructibleResource>(
+        &self,
+        timeout: impl Future<Output = ()>,
+    ) -> Result<T, ObtainError> {
+        self.obtain_async_with::<T, Boxed>(timeout).await
+    }
+    /// Try to get or construct an instance of `T` asynchronously, without boxing the
+    /// future returned by its constructor, giving up if `timeout` completes first. See
+    /// `try_obtain_timeout_async()`. Requires feature `async`.
+    pub async fn try_obtain_unboxed_timeout_async<T: Resource + UnboxedAsyncConstructible>(
+        &self,
+        timeout: impl Future<Output = ()>,
+    ) -> Result<T, ObtainError> {
+        self.obtain_async_with::<T, Unboxed>(timeout).await
+    }
+    /// Construct a fresh instance of `T` asynchronously, ignoring any existing instance,
+    /// and then replace the existing instance with it. Returns the old instance, if any,
+    /// so that it can be torn down. If construction fails, the existing instance is kept.
+    /// The new instance is constructed exactly as `obtain_async()` would construct it,
+    /// including decorators, post-construct hooks and `CONSTRUCT_TIMEOUT`.
+    ///
+    /// Readers continue to see the old instance until construction has finished, at which
+    /// point the swap happens atomically. Readers which obtained the old instance before
+    /// the swap keep using it. Requires feature `async`.
+    pub async fn reconstruct_async<T: AsyncConstructibleResource>(
+        &self,
+    ) -> Result<Option<T>, ObtainError> {
+        let x = self.construct_decorated_async::<T, Boxed>().await?;
+        let old = self.replace(x.clone());
+        self.run_post_construct(&x);
+        Ok(old)
+    }
     /// Get or construct an instance of `T` asynchronously. Panics if unable. Requires feature `async`.
     pub async fn obtain_async<T: AsyncConstructibleResource>(&self) -> T {
         unwrap_constructed::<T, _>(self.try_obtain_async::<T>().await)
     }
+    /// Try to get or construct an instance of `T` asynchronously, along with the
+    /// generation it belongs to. See `try_obtain_versioned()`.
+    pub async fn try_obtain_versioned_async<T: AsyncConstructibleResource>(
+        &self,
+    ) -> Result<(T, u64), ObtainError> {
+        loop {
+            let generation = self.current_generation::<T>();
+            let value = self.try_obtain_async::<T>().await?;
+            if self.current_generation::<T>() == generation {
+                return Ok((value, generation));
+            }
+        }
+    }
+    /// Get or construct an instance of `T` asynchronously, along with the generation
+    /// it belongs to. Panics if unable.
+    pub async fn obtain_versioned_async<T: AsyncConstructibleResource>(&self) -> (T, u64) {
+        unwrap_constructed::<T, _>(self.try_obtain_versioned_async::<T>().await)
+    }
     /// Try to initialize an instance of `T` asynchronously. Does nothing if `T` is already initialized.
-    pub async fn try_init_async<T: AsyncConstructibleResource>(&self) -> Result<(), T::Error> {
-        match self.wait_for_slot_async::<T>(true).await {
-            Some(_) => Ok(()),
-            None => match T::construct_async(self.as_ref()).await {
-                Ok(x) => {
-                    self.fill_placeholder::<T>(x);
-                    Ok(())
-                }
-                Err(e) => {
-                    self.clear_placeholder::<T>();
-                    Err(e)
-                }
-            },
+    pub async fn try_init_async<T: AsyncConstructibleResource>(&self) -> Result<(), ObtainError> {
+        match self
+            .wait_for_presence_async::<T>(true)
+            .await
+            .map_err(ObtainError::Failed)?
+        {
+            Some(()) => Ok(()),
+            None => self
+                .construct_placeholder_async::<T, Boxed>()
+                .await
+                .map(|_| ()),
         }
     }
+    /// Try to get or construct an instance of `T` from an async context, using its
+    /// synchronous `Constructible` implementation. If `T` needs constructing, this runs
+    /// on the executor set with `set_blocking_executor()`. With feature `tokio`, this
+    /// defaults to tokio's blocking thread pool (so this must be called from within a
+    /// tokio runtime), and otherwise an executor must be set or this fails. Requires
+    /// feature `async`.
+    ///
+    /// Prefer this over `try_obtain_async()` for resources which only implement
+    /// `Constructible` and whose constructor blocks, eg. on file or network I/O, or on
+    /// heavy computation: `try_obtain_async()` would call the synchronous constructor
+    /// directly from the current task, stalling every other task on the same thread.
+    pub async fn try_obtain_blocking_async<T: ConstructibleResource>(
+        &self,
+    ) -> Result<T, ObtainError> {
+        // Avoid the round trip to the blocking pool if the resource already exists.
+        if let Lookup::Slot(Some(SlotDesc::Filled(x))) = self.lookup_for_obtain::<T>() {
+            self.mark_accessed::<T>();
+            return Ok(x);
+        }
+        let executor = self.inner.read().blocking_executor.clone();
+        #[cfg(feature = "tokio")]
+        let executor = executor.unwrap_or_else(|| {
+            Arc::new(|task| drop(tokio::task::spawn_blocking(task))) as BlockingExecutor
+        });
+        #[cfg(not(feature = "tokio"))]
+        let executor = executor.ok_or_else(|| {
+            ObtainError::Failed(Arc::new(anyhow::anyhow!(
+                "Cannot construct `{}`: no blocking executor has been set",
+                type_name::<T>()
+            )))
+        })?;
+        let (tx, rx) = futures_channel::oneshot::channel();
+        let aero = self.handle();
+        executor(Box::new(move || {
+            // The receiver may have been dropped if the caller gave up waiting.
+            let _ = tx.send(aero.try_obtain::<T>());
+        }));
+        rx.await.unwrap_or_else(|_| {
+            Err(ObtainError::Failed(Arc::new(anyhow::anyhow!(
+                "Blocking construction of `{}` did not complete",
+                type_name::<T>()
+            ))))
+        })
+    }
+    /// Get or construct an instance of `T` from an async context, using its synchronous
+    /// `Constructible` implementation. Panics if unable. See `try_obtain_blocking_async()`.
+    pub async fn obtain_blocking_async<T: ConstructibleResource>(&self) -> T {
+        unwrap_constructed::<T, _>(self.try_obtain_blocking_async::<T>().await)
+    }
+    /// Set the executor used by `try_obtain_blocking_async()` to run synchronous
+    /// constructors, for applications which do not use tokio's blocking thread pool.
+    /// The executor is passed a closure which it must call on a thread where blocking
+    /// is acceptable. Requires feature `async`.
+    pub fn set_blocking_executor(
+        &self,
+        executor: impl Fn(Box<dyn FnOnce() + Send>) + Send + Sync + 'static,
+    ) {
+        self.inner.write().blocking_executor = Some(Arc::new(executor));
+    }
+    /// Set the timer used to enforce `AsyncConstructible::CONSTRUCT_TIMEOUT`, for
+    /// applications which do not use tokio. The timer is passed a duration, and must
+    /// return a future which completes once that much time has passed. With feature
+    /// `tokio`, this defaults to `tokio::time::sleep()`, and otherwise construction
+    /// timeouts are not enforced until a timer is set. Requires feature `async`.
+    pub fn set_timer<F>(&self, timer: impl Fn(Duration) -> F + Send + Sync + 'static)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.inner.write().timer = Some(Arc::new(move |duration| timer(duration).boxed()));
+    }
+    /// Get or construct an instance of `T` via a future which can be cloned and awaited
+    /// from several places, eg. to hand to many tasks which all need `T`. Construction
+    /// starts when the future is first polled, and every clone resolves to the same
+    /// instance. Panics from whichever task polls it if `T` cannot be obtained.
+    /// Requires feature `async`.
+    pub fn obtain_shared_future<T: AsyncConstructibleResource>(&self) -> SharedResourceFuture<T> {
+        let aero = self.handle();
+        let fut: Pin<Box<dyn Future<Output = T> + Send>> =
+            Box::pin(async move { aero.obtain_async::<T>().await });
+        fut.shared()
+    }
     /// Initialize an instance of `T` asynchronously. Does nothing if `T` is already initialized. Panics if unable.
     pub async fn init_async<T: AsyncConstructibleResource>(&self) {
         unwrap_constructed::<T, _>(self.try_init_async::<T>().await)
     }
+    /// Start initializing an instance of `T` in the background, so that it is likely to
+    /// be ready by the time it is first needed. Returns immediately. Anyone obtaining
+    /// `T` whilst it is being constructed waits for the background task, rather than
+    /// constructing it a second time. Must be called from within a tokio runtime.
+    /// Requires feature `tokio`.
+    #[cfg(feature = "tokio")]
+    pub fn prefetch_async<T: AsyncConstructibleResource>(
+        &self,
+    ) -> tokio::task::JoinHandle<Result<(), ObtainError>> {
+        let aero = self.clone();
+        tokio::spawn(async move { aero.try_init_async::<T>().await })
+    }
+    /// Start initializing every resource in `L` in the background. See `prefetch_async()`.
+    /// Requires feature `tokio`.
+    #[cfg(feature = "tokio")]
+    pub fn prefetch_all_async<L: AsyncConstructibleResourceList>(
+        &self,
+    ) -> tokio::task::JoinHandle<anyhow::Result<()>> {
+        let aero = self.clone();
+        tokio::spawn(async move { L::construct_async(&aero).await })
+    }
 
     /// Builder method equivalent to calling `try_init_async()` but can be chained.
     pub async fn try_with_constructed_async<T: AsyncConstructibleResource>(
         self,
-    ) -> Result<Aero<HCons<T, R>>, T::Error> {
+    ) -> Result<Aero<HCons<T, R>>, ObtainError> {
         self.try_init_async::<T>().await?;
         Ok(Aero {
             inner: self.inner,
@@ -200,19 +698,71 @@ impl<R: ResourceList> Aero<R> {
     }
 
     /// Convert into a different variant of the Aero type. Any missing required resources
-    /// will be automatically asynchronously constructed.
+    /// will be automatically asynchronously constructed, one at a time, according to
+    /// their ordering hints (see `register_ordering_hint()`).
     pub async fn try_construct_remaining_async<R2, I>(self) -> anyhow::Result<Aero<R2>>
     where
         R2: Sculptor<R, I> + ResourceList,
         <R2 as Sculptor<R, I>>::Remainder: AsyncConstructibleResourceList,
     {
-        <<R2 as Sculptor<R, I>>::Remainder>::construct_async(&self).await?;
+        let mut initializers = Vec::new();
+        <<R2 as Sculptor<R, I>>::Remainder>::async_initializers(&mut initializers);
+        self.sort_by_ordering_hints(&mut initializers, |item| item.id);
+        for item in initializers {
+            (item.init)(self.as_ref()).await?;
+        }
+        Ok(Aero {
+            inner: self.inner,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Convert into a different variant of the Aero type. Any missing required resources
+    /// will be automatically asynchronously constructed, with up to `limit` constructed
+    /// concurrently. When more resources are waiting than there are free slots, those
+    /// with a higher priority (see `register_priority()`) are started first.
+    ///
+    /// Constructors run concurrently within the calling task, so synchronous
+    /// constructors still run one at a time. Panics if `limit` is zero.
+    pub async fn try_construct_remaining_concurrently_async<R2, I>(
+        self,
+        limit: usize,
+    ) -> anyhow::Result<Aero<R2>>
+    where
+        R2: Sculptor<R, I> + ResourceList,
+        <R2 as Sculptor<R, I>>::Remainder: AsyncConstructibleResourceList,
+    {
+        assert!(limit > 0, "Concurrency limit must be non-zero");
+        let mut initializers = Vec::new();
+        <<R2 as Sculptor<R, I>>::Remainder>::async_initializers(&mut initializers);
+        self.sort_by_priority(&mut initializers, |item| item.id);
+        let aero: &Aero = self.as_ref();
+        let mut pending =
+            futures_util::stream::iter(initializers.iter().map(|item| (item.init)(aero)))
+                .buffer_unordered(limit);
+        while let Some(res) = pending.next().await {
+            res?;
+        }
+        drop(pending);
         Ok(Aero {
             inner: self.inner,
             phantom: PhantomData,
         })
     }
 
+    /// Convert into a different variant of the Aero type, constructing up to `limit`
+    /// missing resources concurrently. Panics if construction of any missing resource
+    /// fails. See `try_construct_remaining_concurrently_async()`.
+    pub async fn construct_remaining_concurrently_async<R2, I>(self, limit: usize) -> Aero<R2>
+    where
+        R2: Sculptor<R, I> + ResourceList,
+        <R2 as Sculptor<R, I>>::Remainder: AsyncConstructibleResourceList,
+    {
+        unwrap_constructed_hlist::<<R2 as Sculptor<R, I>>::Remainder, _>(
+            self.try_construct_remaining_concurrently_async(limit).await,
+        )
+    }
+
     /// Convert into a different variant of the Aero type. Any missing required resources
     /// will be automatically asynchronously constructed. Panics if construction of any missing resource fails.
     pub async fn construct_remaining_async<R2, I>(self) -> Aero<R2>
@@ -230,7 +780,7 @@ impl<R: ResourceList> Aero<R> {
 mod tests {
     use std::{convert::Infallible, time::Duration};
 
-    use crate::Aero;
+    use crate::{Aero, MissingResource};
 
     use super::*;
 
@@ -253,6 +803,117 @@ mod tests {
         state.obtain_async::<Dummy>().await;
     }
 
+    #[derive(Debug, Clone)]
+    struct DummyUnboxed(usize);
+
+    impl UnboxedAsyncConstructible for DummyUnboxed {
+        type Error = Infallible;
+
+        async fn construct_unboxed(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.obtain_async::<Dummy>().await;
+            Ok(Self(aero.construction_count::<DummyUnboxed>()))
+        }
+    }
+
+    #[tokio::test]
+    async fn obtain_unboxed() {
+        let state = Aero::new();
+        assert_eq!(state.obtain_unboxed_async::<DummyUnboxed>().await.0, 1);
+        assert_eq!(state.obtain_unboxed_async::<DummyUnboxed>().await.0, 1);
+        assert!(state.has::<Dummy>());
+
+        state.register::<DummyUnboxed>(crate::Lifetime::Transient);
+        state.reset::<DummyUnboxed>();
+        assert_eq!(state.obtain_unboxed_async::<DummyUnboxed>().await.0, 2);
+        assert_eq!(state.obtain_unboxed_async::<DummyUnboxed>().await.0, 3);
+    }
+
+    #[derive(Debug, Clone)]
+    struct DummyPort(u16);
+
+    #[async_trait]
+    impl AsyncConstructible for DummyPort {
+        type Error = Infallible;
+
+        async fn construct_async(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.obtain_async::<Dummy>().await;
+            Ok(Self(aero.try_get::<u16>().unwrap()))
+        }
+    }
+
+    #[tokio::test]
+    async fn obtain_with_overrides_async() {
+        let state = Aero::new().with(80u16);
+        let port = state
+            .try_obtain_with_overrides_async::<DummyPort>(Aero::new().with(8080u16).into())
+            .await
+            .unwrap();
+        assert_eq!(port.0, 8080);
+        assert!(!state.has::<Dummy>());
+        assert!(!state.has::<DummyPort>());
+    }
+
+    #[tokio::test]
+    async fn obtain_present_only_reads() {
+        let state = Aero::new();
+        state.init_async::<Dummy>().await;
+        // Taking the write lock whilst the guard is held would deadlock.
+        let _guard = state.read();
+        state.obtain_async::<Dummy>().await;
+        state.init_async::<Dummy>().await;
+    }
+
+    #[tokio::test]
+    async fn obtain_shared_future() {
+        let state = Aero::new();
+        let fut = state.obtain_shared_future::<Dummy>();
+        tokio::join!(fut.clone(), fut.clone(), fut);
+        assert_eq!(state.construction_count::<Dummy>(), 1);
+    }
+
+    #[tokio::test]
+    async fn obtain_box() {
+        let state = Aero::new();
+        state.obtain_async::<Box<DummySync>>().await;
+        assert!(state.has::<Box<DummySync>>());
+    }
+
+    #[async_trait]
+    impl AsyncConstructible for Box<Dummy> {
+        type Error = Infallible;
+
+        async fn construct_async(aero: &Aero) -> Result<Self, Self::Error> {
+            <Dummy as AsyncConstructible>::construct_async(aero)
+                .await
+                .map(Box::new)
+        }
+    }
+
+    #[tokio::test]
+    async fn obtain_box_async() {
+        let state = Aero::new();
+        state.obtain_async::<Box<Dummy>>().await;
+        assert!(state.has::<Box<Dummy>>());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn prefetch() {
+        let state = Aero::new();
+        let handle = state.prefetch_async::<Dummy>();
+        tokio::task::yield_now().await;
+        state.obtain_async::<Dummy>().await;
+        handle.await.unwrap().unwrap();
+        assert_eq!(state.construction_count::<Dummy>(), 1);
+
+        state
+            .prefetch_all_async::<frunk::HList![Dummy, DummyRecursive]>()
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(state.has::<DummyRecursive>());
+    }
+
     #[tokio::test]
     async fn obtain_race() {
         let state = Aero::new();
@@ -266,6 +927,7 @@ mod tests {
         for handle in handles {
             handle.await.unwrap();
         }
+        assert_eq!(state.construction_count::<Dummy>(), 1);
     }
 
     #[derive(Debug, Clone)]
@@ -297,6 +959,11 @@ mod tests {
                 state.obtain_async::<DummyRecursive>().await;
             }));
         }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(state.construction_count::<DummyRecursive>(), 1);
+        assert_eq!(state.construction_count::<Dummy>(), 1);
     }
 
     #[derive(Debug, Clone)]
@@ -331,6 +998,33 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn obtain_blocking() {
+        let state = Aero::new();
+        tokio::join!(
+            state.obtain_blocking_async::<DummySync>(),
+            state.obtain_blocking_async::<DummySync>()
+        );
+        state.obtain_blocking_async::<DummySync>().await;
+        assert_eq!(state.construction_count::<DummySync>(), 1);
+    }
+
+    #[tokio::test]
+    async fn obtain_blocking_custom_executor() {
+        let state = Aero::new();
+        let spawned = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        state.set_blocking_executor({
+            let spawned = spawned.clone();
+            move |task| {
+                spawned.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                std::thread::spawn(task);
+            }
+        });
+        state.obtain_blocking_async::<DummySync>().await;
+        assert_eq!(spawned.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
     #[derive(Debug, Clone)]
     struct DummySyncRecursive;
 
@@ -362,6 +1056,54 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn obtain_async_while_constructing_sync() {
+        let state = Aero::new();
+        let thread = {
+            let state = state.clone();
+            std::thread::spawn(move || state.obtain::<DummySync>())
+        };
+        // Give the thread time to insert its placeholder
+        while state.construction_count::<DummySync>() == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let mut handles = Vec::new();
+        for _ in 0..100 {
+            let state = state.clone();
+            handles.push(tokio::spawn(async move {
+                state.obtain_async::<DummySync>().await;
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        thread.join().unwrap();
+        assert_eq!(state.construction_count::<DummySync>(), 1);
+    }
+
+    #[tokio::test]
+    async fn obtain_sync_while_constructing_async() {
+        let state = Aero::new();
+        let task = {
+            let state = state.clone();
+            tokio::spawn(async move { state.obtain_async::<Dummy>().await })
+        };
+        while state.construction_count::<Dummy>() == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let threads: Vec<_> = (0..10)
+            .map(|_| {
+                let state = state.clone();
+                std::thread::spawn(move || state.try_get::<Dummy>().unwrap())
+            })
+            .collect();
+        task.await.unwrap();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(state.construction_count::<Dummy>(), 1);
+    }
+
     #[derive(Debug)]
     struct DummyNonClone;
 
@@ -434,4 +1176,257 @@ mod tests {
         state.get::<Dummy, _>();
         state.get::<DummyRecursive, _>();
     }
+
+    #[derive(Debug, Clone)]
+    struct DummyMissingDependency;
+
+    #[async_trait]
+    impl AsyncConstructible for DummyMissingDependency {
+        type Error = MissingResource;
+
+        async fn construct_async(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.try_get_async::<i32>()
+                .await
+                .ok_or_else(MissingResource::new::<i32>)?;
+            Ok(Self)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct DummyFailing;
+
+    #[async_trait]
+    impl AsyncConstructible for DummyFailing {
+        type Error = anyhow::Error;
+
+        async fn construct_async(_aero: &Aero) -> Result<Self, Self::Error> {
+            Err(anyhow::anyhow!("Connection refused"))
+        }
+    }
+
+    #[tokio::test]
+    async fn try_obtain_missing() {
+        let state = Aero::new();
+        let err = state
+            .try_obtain_async::<DummyMissingDependency>()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ObtainError::Missing(m) if m.name() == "i32"));
+    }
+
+    #[tokio::test]
+    async fn try_obtain_failed() {
+        let state = Aero::new();
+        let err = state.try_obtain_async::<DummyFailing>().await.unwrap_err();
+        assert!(matches!(err, ObtainError::Failed(_)));
+    }
+
+    #[tokio::test]
+    async fn obtain_sealed() {
+        let state = Aero::new();
+        state.seal();
+        assert!(matches!(
+            state.try_obtain_async::<Dummy>().await,
+            Err(ObtainError::Sealed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn obtain_timeout() {
+        let state = Aero::new();
+        let handle = tokio::spawn({
+            let state = state.clone();
+            async move { state.obtain_async::<Dummy>().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(matches!(
+            state
+                .try_obtain_timeout_async::<Dummy>(tokio::time::sleep(Duration::from_millis(10)))
+                .await,
+            Err(ObtainError::WaitTimedOut(_))
+        ));
+        state
+            .try_obtain_timeout_async::<Dummy>(tokio::time::sleep(Duration::from_secs(10)))
+            .await
+            .unwrap();
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn obtain_unboxed_timeout() {
+        let state = Aero::new();
+        let handle = tokio::spawn({
+            let state = state.clone();
+            async move { state.obtain_unboxed_async::<DummyUnboxed>().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(matches!(
+            state
+                .try_obtain_unboxed_timeout_async::<DummyUnboxed>(tokio::time::sleep(
+                    Duration::from_millis(10)
+                ))
+                .await,
+            Err(ObtainError::WaitTimedOut(_))
+        ));
+        handle.await.unwrap();
+    }
+
+    #[derive(Debug, Clone)]
+    struct DummyVersioned(usize);
+
+    #[async_trait]
+    impl AsyncConstructible for DummyVersioned {
+        type Error = anyhow::Error;
+
+        async fn construct_async(aero: &Aero) -> Result<Self, Self::Error> {
+            let version = aero.try_get::<usize>().unwrap_or_default();
+            if version == 0 {
+                return Err(anyhow::anyhow!("Not ready"));
+            }
+            Ok(Self(version))
+        }
+    }
+
+    #[tokio::test]
+    async fn reconstruct() {
+        let state = Aero::new().with(1usize);
+        assert_eq!(state.obtain_async::<DummyVersioned>().await.0, 1);
+        state.replace(2usize);
+        let old = state.reconstruct_async::<DummyVersioned>().await.unwrap();
+        assert_eq!(old.unwrap().0, 1);
+        assert_eq!(state.obtain_async::<DummyVersioned>().await.0, 2);
+        state.replace(0usize);
+        assert!(state.reconstruct_async::<DummyVersioned>().await.is_err());
+        assert_eq!(state.obtain_async::<DummyVersioned>().await.0, 2);
+    }
+
+    #[tokio::test]
+    async fn reconstruct_decorated() {
+        let state = Aero::new().with(1usize);
+        state.register_decorator(|x: DummyVersioned, _| DummyVersioned(x.0 * 10));
+        assert_eq!(state.obtain_async::<DummyVersioned>().await.0, 10);
+        state.replace(2usize);
+        state.reconstruct_async::<DummyVersioned>().await.unwrap();
+        assert_eq!(state.obtain_async::<DummyVersioned>().await.0, 20);
+        assert_eq!(state.construction_count::<DummyVersioned>(), 2);
+    }
+
+    #[derive(Debug, Clone)]
+    struct SlowToConnect;
+
+    #[async_trait]
+    impl AsyncConstructible for SlowToConnect {
+        type Error = Infallible;
+        const CONSTRUCT_TIMEOUT: Option<Duration> = Some(Duration::from_millis(20));
+
+        async fn construct_async(_aero: &Aero) -> Result<Self, Self::Error> {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(Self)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn construct_timeout_async() {
+        let state = Aero::new();
+        assert!(matches!(
+            state.try_obtain_async::<SlowToConnect>().await,
+            Err(ObtainError::ConstructTimedOut(_))
+        ));
+        assert!(!state.has::<SlowToConnect>());
+    }
+
+    #[tokio::test]
+    async fn construct_timeout_custom_timer() {
+        let state = Aero::new();
+        let timers = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        state.set_timer({
+            let timers = timers.clone();
+            move |duration| {
+                timers.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                tokio::time::sleep(duration)
+            }
+        });
+        state.register::<SlowToConnect>(crate::Lifetime::Transient);
+        assert!(matches!(
+            state.try_obtain_async::<SlowToConnect>().await,
+            Err(ObtainError::ConstructTimedOut(_))
+        ));
+        assert_eq!(timers.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "tracing")]
+    mod span {
+        use std::sync::Arc;
+
+        use parking_lot::Mutex;
+        use tracing::{
+            instrument::WithSubscriber,
+            span::{Attributes, Id, Record},
+            Event, Instrument, Metadata, Subscriber,
+        };
+
+        use super::*;
+
+        /// Records the names of the spans which were entered when each event was emitted.
+        #[derive(Default)]
+        struct SpanRecorder {
+            names: Mutex<Vec<&'static str>>,
+            entered: Mutex<Vec<u64>>,
+            events: Arc<Mutex<Vec<Vec<&'static str>>>>,
+        }
+
+        impl Subscriber for SpanRecorder {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut names = self.names.lock();
+                names.push(span.metadata().name());
+                Id::from_u64(names.len() as u64)
+            }
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, _event: &Event<'_>) {
+                let names = self.names.lock();
+                let entered = self.entered.lock();
+                let spans = entered.iter().map(|id| names[*id as usize - 1]).collect();
+                self.events.lock().push(spans);
+            }
+            fn enter(&self, span: &Id) {
+                self.entered.lock().push(span.into_u64());
+            }
+            fn exit(&self, _span: &Id) {
+                self.entered.lock().pop();
+            }
+        }
+
+        #[derive(Debug, Clone)]
+        struct Traced;
+
+        #[async_trait]
+        impl AsyncConstructible for Traced {
+            type Error = Infallible;
+
+            async fn construct_async(_aero: &Aero) -> Result<Self, Self::Error> {
+                tokio::task::yield_now().await;
+                tracing::info!("constructing");
+                Ok(Self)
+            }
+        }
+
+        #[tokio::test]
+        async fn construct_span() {
+            let recorder = SpanRecorder::default();
+            let events = recorder.events.clone();
+            let state = Aero::new();
+            async {
+                let span = tracing::info_span!("request");
+                state.obtain_async::<Traced>().instrument(span).await
+            }
+            .with_subscriber(recorder)
+            .await;
+            assert_eq!(*events.lock(), [vec!["request", "construct"]]);
+        }
+    }
 }
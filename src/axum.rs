@@ -1,34 +1,45 @@
 //! Integration with the `axum` web framework.
 //!
-//! Provides the `Dep` and `Obtain` axum extractors for easily accessing
-//! resources from within route handlers.
+//! Provides the `Dep`, `Obtain` and `ObtainBlocking` axum extractors for easily
+//! accessing resources from within route handlers.
 //!
 //! To make use of these extractors, your application state must either be
 //! an `Aero`, or you must implement `FromRef<YourState>` for `Aero`.
+//!
+//! Insert a `ConstructAllowList` resource to restrict which resources the `Obtain`
+//! and `ObtainBlocking` extractors may construct on demand.
 
-use std::any::type_name;
+use std::any::{type_name, TypeId};
+use std::collections::HashSet;
 use std::convert::Infallible;
+use std::sync::Arc;
 
 use axum::{
     extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
-    http::{request::Parts, StatusCode},
+    http::{header, request::Parts, StatusCode},
     response::{IntoResponse, Response},
 };
 use frunk::HCons;
 
-use crate::{Aero, AsyncConstructibleResource, Resource, ResourceList};
+use crate::{
+    Aero, AsyncConstructibleResource, ConstructibleResource, ObtainError, Resource, ResourceList,
+};
 
-/// Type of axum Rejection returned when a resource cannot be acquired
+/// Type of axum Rejection returned when a resource cannot be acquired. More variants
+/// may be added in future, so prefer `kind()` and `resource_name()` over matching on
+/// this directly.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum DependencyError {
     /// Tried to get a resource which did not exist. Use `Obtain(..)` if you want aerosol to
-    /// try to construct the resource on demand.
+    /// try to construct the resource on demand. Also returned by `Obtain(..)` if a resource
+    /// the constructor depends on does not exist. Maps to a 500 response.
     #[error("Resource `{name}` does not exist")]
     DoesNotExist {
         /// Name of the resource type
         name: &'static str,
     },
-    /// Tried and failed to construct a resource.
+    /// Tried and failed to construct a resource. Maps to a 503 response.
     #[error("Failed to construct `{name}`: {source}")]
     FailedToConstruct {
         /// Name of the resource type
@@ -37,27 +48,176 @@ pub enum DependencyError {
         #[source]
         source: anyhow::Error,
     },
+    /// Tried to construct a resource which is not in the `ConstructAllowList`. Maps to a
+    /// 500 response.
+    #[error("Resource `{name}` may not be constructed on demand")]
+    NotPermitted {
+        /// Name of the resource type
+        name: &'static str,
+    },
+    /// Tried to construct a resource after the `Aero` was sealed with `Aero::seal()`,
+    /// eg. during graceful shutdown. Maps to a 503 response with a `Retry-After` header,
+    /// so that clients or load balancers retry the request elsewhere.
+    #[error("Cannot construct `{name}` whilst shutting down")]
+    ShuttingDown {
+        /// Name of the resource type
+        name: &'static str,
+    },
+}
+
+/// Value of the `Retry-After` header sent with `DependencyError::ShuttingDown`, in seconds.
+const SHUTDOWN_RETRY_AFTER: &str = "5";
+
+/// The kind of a `DependencyError`, returned from `DependencyError::kind()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DependencyErrorKind {
+    /// See `DependencyError::DoesNotExist`.
+    DoesNotExist,
+    /// See `DependencyError::FailedToConstruct`.
+    FailedToConstruct,
+    /// See `DependencyError::NotPermitted`.
+    NotPermitted,
+    /// See `DependencyError::ShuttingDown`.
+    ShuttingDown,
 }
 
 impl IntoResponse for DependencyError {
     fn into_response(self) -> Response {
         tracing::error!("{}", self);
-        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        match self {
+            Self::DoesNotExist { .. } | Self::NotPermitted { .. } => {
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+            Self::FailedToConstruct { .. } => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+            Self::ShuttingDown { .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, SHUTDOWN_RETRY_AFTER)],
+            )
+                .into_response(),
+        }
     }
 }
 
 impl DependencyError {
-    pub(crate) fn does_not_exist<T>() -> Self {
+    /// The kind of error.
+    pub fn kind(&self) -> DependencyErrorKind {
+        match self {
+            Self::DoesNotExist { .. } => DependencyErrorKind::DoesNotExist,
+            Self::FailedToConstruct { .. } => DependencyErrorKind::FailedToConstruct,
+            Self::NotPermitted { .. } => DependencyErrorKind::NotPermitted,
+            Self::ShuttingDown { .. } => DependencyErrorKind::ShuttingDown,
+        }
+    }
+    /// Name of the resource type which could not be acquired.
+    pub fn resource_name(&self) -> &'static str {
+        match self {
+            Self::DoesNotExist { name }
+            | Self::FailedToConstruct { name, .. }
+            | Self::NotPermitted { name }
+            | Self::ShuttingDown { name } => name,
+        }
+    }
+    /// Find an error of type `E` in the source chain of the error returned by the
+    /// resource constructor, if any. This allows the underlying cause to be inspected,
+    /// eg. to return a different response for a specific database error.
+    pub fn downcast_source<E: std::error::Error + Send + Sync + 'static>(&self) -> Option<&E> {
+        match self {
+            Self::DoesNotExist { .. } | Self::NotPermitted { .. } | Self::ShuttingDown { .. } => {
+                None
+            }
+            Self::FailedToConstruct { source, .. } => {
+                source.chain().find_map(|e| e.downcast_ref::<E>())
+            }
+        }
+    }
+    /// Construct an error indicating that the resource `T` does not exist.
+    pub fn does_not_exist<T>() -> Self {
         Self::DoesNotExist {
             name: type_name::<T>(),
         }
     }
-    pub(crate) fn failed_to_construct<T>(error: impl Into<anyhow::Error>) -> Self {
+    /// Construct an error indicating that the resource `T` could not be constructed.
+    pub fn failed_to_construct<T>(error: impl Into<anyhow::Error>) -> Self {
         Self::FailedToConstruct {
             name: type_name::<T>(),
             source: error.into(),
         }
     }
+    /// Construct an error indicating that the resource `T` may not be constructed on
+    /// demand.
+    pub fn not_permitted<T>() -> Self {
+        Self::NotPermitted {
+            name: type_name::<T>(),
+        }
+    }
+    pub(crate) fn failed_to_obtain<T>(error: ObtainError) -> Self {
+        match error {
+            ObtainError::Missing(missing) => Self::DoesNotExist {
+                name: missing.name(),
+            },
+            ObtainError::Sealed(name) => Self::ShuttingDown { name },
+            ObtainError::Failed(source) => {
+                // A dependency may have been refused because the instance was sealed.
+                let sealed = source.chain().find_map(|e| match e.downcast_ref() {
+                    Some(ObtainError::Sealed(_)) => Some(type_name::<T>()),
+                    _ => None,
+                });
+                match sealed {
+                    Some(name) => Self::ShuttingDown { name },
+                    None => Self::failed_to_construct::<T>(ObtainError::Failed(source)),
+                }
+            }
+            error => Self::failed_to_construct::<T>(error),
+        }
+    }
+}
+
+/// Restricts which resources the `Obtain` and `ObtainBlocking` extractors may construct
+/// on demand. When this resource is present, extracting a resource which does not yet
+/// exist and is not in the list fails with `DependencyError::NotPermitted`. Resources
+/// which already exist, eg. because they were constructed at startup, can always be
+/// extracted.
+///
+/// This prevents a handler from accidentally extracting a resource which should only
+/// be built at startup, such as an admin-only client.
+///
+/// Only the type being extracted is checked. Dependencies which an allowed resource's
+/// constructor obtains are constructed without restriction, so if an allowed resource
+/// depends on the admin-only client, extracting it will still construct the client.
+/// Construct such dependencies at startup, or keep resources which depend on them off
+/// the list.
+#[derive(Debug, Default, Clone)]
+pub struct ConstructAllowList {
+    allowed: Arc<HashSet<TypeId>>,
+}
+
+impl ConstructAllowList {
+    /// Construct an empty allow list, which prevents any resource from being constructed
+    /// on demand.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Allow the resource `T` to be constructed on demand.
+    pub fn allow<T: Resource>(mut self) -> Self {
+        Arc::make_mut(&mut self.allowed).insert(TypeId::of::<T>());
+        self
+    }
+    /// Returns true if the resource `T` may be constructed on demand.
+    pub fn is_allowed<T: Resource>(&self) -> bool {
+        self.allowed.contains(&TypeId::of::<T>())
+    }
+}
+
+/// Check that `T` either exists, or may be constructed on demand. Dependencies of `T`
+/// are not checked.
+fn check_allowed<T: Resource>(aero: &Aero) -> Result<(), DependencyError> {
+    match aero.try_get::<ConstructAllowList>() {
+        Some(list) if !list.is_allowed::<T>() && !aero.has::<T>() => {
+            Err(DependencyError::not_permitted::<T>())
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Get an already-existing resource from the state. Equivalent to calling `Aero::try_get_async`.
@@ -107,11 +267,12 @@ where
     type Rejection = DependencyError;
 
     async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        Aero::from_ref(state)
-            .try_obtain_async()
+        let aero = Aero::from_ref(state);
+        check_allowed::<T>(&aero)?;
+        aero.try_obtain_async()
             .await
             .map(Self)
-            .map_err(DependencyError::failed_to_construct::<T>)
+            .map_err(DependencyError::failed_to_obtain::<T>)
     }
 }
 
@@ -134,6 +295,50 @@ where
     }
 }
 
+/// Get a resource from the state, or construct it if it doesn't exist, using its synchronous
+/// `Constructible` implementation. Construction runs on tokio's blocking thread pool, so
+/// constructors which perform blocking I/O do not stall the async runtime. Equivalent to
+/// calling `Aero::try_obtain_blocking_async`.
+///
+/// Prefer this over `Obtain` for resources which only implement `Constructible`: `Obtain`
+/// would call the synchronous constructor directly from the handler's task.
+pub struct ObtainBlocking<T: ConstructibleResource>(pub T);
+
+impl<T: ConstructibleResource, S: Send + Sync> FromRequestParts<S> for ObtainBlocking<T>
+where
+    Aero: FromRef<S>,
+{
+    type Rejection = DependencyError;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let aero = Aero::from_ref(state);
+        check_allowed::<T>(&aero)?;
+        aero.try_obtain_blocking_async()
+            .await
+            .map(Self)
+            .map_err(DependencyError::failed_to_obtain::<T>)
+    }
+}
+
+impl<T: ConstructibleResource, S: Send + Sync> OptionalFromRequestParts<S> for ObtainBlocking<T>
+where
+    Aero: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    // Required method
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(
+            <Self as FromRequestParts<S>>::from_request_parts(parts, state)
+                .await
+                .ok(),
+        )
+    }
+}
+
 impl<H: Resource, T: ResourceList> FromRef<Aero<HCons<H, T>>> for Aero {
     fn from_ref(input: &Aero<HCons<H, T>>) -> Self {
         input.clone().into()
@@ -151,3 +356,96 @@ mod extra_impls {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, io};
+
+    use crate::{Aero, Constructible};
+
+    use axum::{
+        http::{header, StatusCode},
+        response::IntoResponse,
+    };
+
+    use super::{check_allowed, ConstructAllowList, DependencyError, DependencyErrorKind};
+
+    #[derive(Debug, Clone)]
+    struct DbPool;
+
+    impl Constructible for DbPool {
+        type Error = io::Error;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Err(io::Error::new(io::ErrorKind::ConnectionRefused, "refused"))
+        }
+    }
+
+    #[test]
+    fn downcast_source() {
+        let error = Aero::new().try_obtain::<DbPool>().unwrap_err();
+        let error = DependencyError::failed_to_obtain::<DbPool>(error);
+        let io_error = error.downcast_source::<io::Error>().unwrap();
+        assert_eq!(io_error.kind(), io::ErrorKind::ConnectionRefused);
+        assert!(error.downcast_source::<std::fmt::Error>().is_none());
+    }
+
+    #[test]
+    fn kind() {
+        let error = DependencyError::does_not_exist::<DbPool>();
+        assert_eq!(error.kind(), DependencyErrorKind::DoesNotExist);
+        assert_eq!(error.resource_name(), std::any::type_name::<DbPool>());
+        let error = DependencyError::failed_to_construct::<DbPool>(io::Error::other("refused"));
+        assert_eq!(error.kind(), DependencyErrorKind::FailedToConstruct);
+        assert_eq!(error.resource_name(), std::any::type_name::<DbPool>());
+    }
+
+    #[derive(Debug, Clone)]
+    struct AdminClient;
+
+    impl Constructible for AdminClient {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(AdminClient)
+        }
+    }
+
+    #[test]
+    fn construct_allow_list() {
+        let state = Aero::new();
+        assert!(check_allowed::<AdminClient>(&state).is_ok());
+
+        state.insert(ConstructAllowList::new().allow::<DbPool>());
+        assert!(check_allowed::<DbPool>(&state).is_ok());
+        let error = check_allowed::<AdminClient>(&state).unwrap_err();
+        assert_eq!(error.kind(), DependencyErrorKind::NotPermitted);
+
+        // Resources which already exist can always be extracted.
+        state.insert(AdminClient);
+        assert!(check_allowed::<AdminClient>(&state).is_ok());
+    }
+
+    #[test]
+    fn shutting_down() {
+        let state = Aero::new();
+        state.seal();
+        let error = state.try_obtain::<AdminClient>().unwrap_err();
+        let error = DependencyError::failed_to_obtain::<AdminClient>(error);
+        assert_eq!(error.kind(), DependencyErrorKind::ShuttingDown);
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+    }
+
+    #[test]
+    fn cancelled() {
+        // Cancelling constructions does not seal the instance, so it is not a shutdown.
+        let error = crate::ObtainError::Cancelled(std::any::type_name::<AdminClient>());
+        let error = DependencyError::failed_to_obtain::<AdminClient>(error);
+        assert_eq!(error.kind(), DependencyErrorKind::FailedToConstruct);
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!response.headers().contains_key(header::RETRY_AFTER));
+    }
+}
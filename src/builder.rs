@@ -0,0 +1,91 @@
+use std::ops::Deref;
+
+use crate::{state::Aero, sync_constructible::Constructible};
+
+/// Implemented for resources which are constructed by first assembling a builder from
+/// other resources, and then building it. Splitting construction in two allows each
+/// step to be tested independently: `assemble` can be checked against a test `Aero`
+/// without connecting to anything, and `build` can be given a hand-written builder.
+/// See `Built<T>`.
+pub trait ConstructibleBuilder: Sized {
+    /// The builder type, eg. `reqwest::ClientBuilder`.
+    type Builder;
+    /// Error type for when either step fails.
+    type Error: Into<anyhow::Error> + Send + Sync;
+
+    /// Assemble a builder, reading configuration from the provided Aero instance.
+    fn assemble(aero: &Aero) -> Result<Self::Builder, Self::Error>;
+    /// Build the resource from an assembled builder.
+    fn build(builder: Self::Builder) -> Result<Self, Self::Error>;
+}
+
+/// A resource which is constructed via `ConstructibleBuilder`, by calling `assemble()`
+/// followed by `build()`. Dereferences to `T`.
+#[derive(Debug, Clone)]
+pub struct Built<T>(T);
+
+impl<T> Built<T> {
+    /// Unwrap the inner resource.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Built<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ConstructibleBuilder + Send + Sync + 'static> Constructible for Built<T> {
+    type Error = T::Error;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        T::build(T::assemble(aero)?).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, time::Duration};
+
+    use crate::Aero;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Client {
+        timeout: Duration,
+    }
+
+    struct ClientBuilder {
+        timeout: Option<Duration>,
+    }
+
+    impl ConstructibleBuilder for Client {
+        type Builder = ClientBuilder;
+        type Error = Infallible;
+
+        fn assemble(aero: &Aero) -> Result<Self::Builder, Self::Error> {
+            Ok(ClientBuilder {
+                timeout: aero.try_get(),
+            })
+        }
+
+        fn build(builder: Self::Builder) -> Result<Self, Self::Error> {
+            Ok(Self {
+                timeout: builder.timeout.unwrap_or(Duration::from_secs(30)),
+            })
+        }
+    }
+
+    #[test]
+    fn built() {
+        let state = Aero::new().with(Duration::from_secs(5));
+        let client = state.obtain::<Built<Client>>();
+        assert_eq!(client.timeout, Duration::from_secs(5));
+        assert!(Client::assemble(&Aero::new()).unwrap().timeout.is_none());
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+#[cfg(feature = "async")]
+use futures_channel::oneshot;
+#[cfg(feature = "async")]
+use futures_util::future::{FutureExt, Shared};
+#[cfg(feature = "async")]
+use parking_lot::Mutex;
+
+use crate::{resource::ResourceList, state::Aero};
+
+struct CancelState {
+    cancelled: AtomicBool,
+    /// Dropped on cancellation, which completes `cancelled`.
+    #[cfg(feature = "async")]
+    sender: Mutex<Option<oneshot::Sender<()>>>,
+    #[cfg(feature = "async")]
+    cancelled_future: Shared<oneshot::Receiver<()>>,
+}
+
+impl Default for CancelState {
+    fn default() -> Self {
+        #[cfg(feature = "async")]
+        let (sender, receiver) = oneshot::channel();
+        Self {
+            cancelled: AtomicBool::new(false),
+            #[cfg(feature = "async")]
+            sender: Mutex::new(Some(sender)),
+            #[cfg(feature = "async")]
+            cancelled_future: receiver.shared(),
+        }
+    }
+}
+
+/// Signal which is raised when `Aero::cancel_constructions()` is called, so that
+/// long-running constructors can stop early. Obtained from
+/// `ConstructCtx::cancel_token()`.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    state: Arc<CancelState>,
+}
+
+impl CancelToken {
+    /// Returns true if construction has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until construction is cancelled, eg. in a `tokio::select!` alongside the
+    /// work being done by a constructor. Requires feature `async`.
+    #[cfg(feature = "async")]
+    pub async fn cancelled(&self) {
+        // The sender is never used, so this always completes with an error once the
+        // sender is dropped.
+        let _ = self.state.cancelled_future.clone().await;
+    }
+
+    fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        #[cfg(feature = "async")]
+        drop(self.state.sender.lock().take());
+    }
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Ask every constructor running in this aerosol instance to stop, eg. because the
+    /// application started shutting down whilst it was still starting up. Constructors
+    /// observe this through `ConstructCtx::cancel_token()`, and typically give up by
+    /// returning the error from `ConstructCtx::check_cancelled()`, which is reported as
+    /// `ObtainError::Cancelled` and is never cached. Constructors which ignore the
+    /// token are unaffected. Cancellation cannot be undone, and does not stop new
+    /// constructions from starting: call `seal()` as well for that.
+    pub fn cancel_constructions(&self) {
+        self.inner.read().cancel.cancel();
+    }
+
+    pub(crate) fn cancel_token(&self) -> CancelToken {
+        self.inner.read().cancel.clone()
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use crate::{Aero, AsyncConstructible, ConstructCtx, ObtainError};
+
+    #[derive(Debug, Clone)]
+    struct Migrations;
+
+    #[async_trait]
+    impl AsyncConstructible for Migrations {
+        type Error = ObtainError;
+
+        async fn construct_async(_aero: &Aero) -> Result<Self, Self::Error> {
+            unreachable!()
+        }
+
+        async fn construct_async_in_ctx(
+            _aero: &Aero,
+            ctx: &ConstructCtx,
+        ) -> Result<Self, Self::Error> {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(60)) => Ok(Migrations),
+                _ = ctx.cancel_token().cancelled() => Err(ctx.check_cancelled().unwrap_err()),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Checked;
+
+    impl crate::Constructible for Checked {
+        type Error = ObtainError;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            unreachable!()
+        }
+
+        fn construct_in_ctx(_aero: &Aero, ctx: &ConstructCtx) -> Result<Self, Self::Error> {
+            ctx.check_cancelled()?;
+            Ok(Checked)
+        }
+    }
+
+    #[tokio::test]
+    async fn cancel_constructions() {
+        let state = Aero::new();
+        state.cache_failures(true);
+        let task = tokio::spawn({
+            let state = state.clone();
+            async move { state.try_obtain_async::<Migrations>().await }
+        });
+        tokio::task::yield_now().await;
+        state.cancel_constructions();
+        assert!(matches!(
+            task.await.unwrap(),
+            Err(ObtainError::Cancelled(name)) if name == std::any::type_name::<Migrations>()
+        ));
+        // The placeholder is cleaned up, and the cancellation is not cached.
+        assert!(!state.has::<Migrations>());
+        for _ in 0..2 {
+            assert!(matches!(
+                state.try_obtain::<Checked>(),
+                Err(ObtainError::Cancelled(_))
+            ));
+        }
+    }
+}
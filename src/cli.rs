@@ -0,0 +1,73 @@
+use std::ffi::OsString;
+
+use clap::Parser;
+use frunk::HCons;
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+impl<R: ResourceList> Aero<R> {
+    /// Parse the command-line arguments of the current process into `A`, and insert it
+    /// as a resource. Other resources can then implement `Constructible` by obtaining
+    /// `A` and reading its fields. Exits the process with a usage message if the
+    /// arguments are invalid, as `Parser::parse()` does. Requires feature `clap`.
+    pub fn with_clap<A: Parser + Resource>(self) -> Aero<HCons<A, R>> {
+        self.with(A::parse())
+    }
+
+    /// Parse `args` into `A`, and insert it as a resource. The first argument is the
+    /// binary name. Unlike `with_clap()`, invalid arguments are returned as an error
+    /// rather than exiting the process. Requires feature `clap`.
+    pub fn try_with_clap_from<A, I, T>(self, args: I) -> Result<Aero<HCons<A, R>>, clap::Error>
+    where
+        A: Parser + Resource,
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        Ok(self.with(A::try_parse_from(args)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use clap::Parser;
+
+    use crate::{Aero, Constructible};
+
+    #[derive(Debug, Clone, Parser)]
+    struct Args {
+        #[arg(long, default_value = "localhost")]
+        host: String,
+        #[arg(long)]
+        port: u16,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Endpoint(String);
+
+    impl Constructible for Endpoint {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            let args: Args = aero.try_get().expect("Args were parsed");
+            Ok(Self(format!("{}:{}", args.host, args.port)))
+        }
+    }
+
+    #[test]
+    fn try_with_clap_from() {
+        let state = Aero::new()
+            .try_with_clap_from::<Args, _, _>(["app", "--port", "8080"])
+            .unwrap();
+        assert_eq!(state.get::<Args, _>().port, 8080);
+        assert_eq!(state.obtain::<Endpoint>().0, "localhost:8080");
+
+        assert!(Aero::new()
+            .try_with_clap_from::<Args, _, _>(["app"])
+            .is_err());
+    }
+}
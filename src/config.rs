@@ -0,0 +1,157 @@
+use std::{
+    any::{type_name, Any},
+    collections::HashMap,
+    ops::Deref,
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{resource::MissingResource, state::Aero, sync_constructible::Constructible};
+
+/// Configuration for resources which implement `ConfigConstructible`, split into
+/// sections keyed by name. Insert this as a resource to allow `Configured<T>`
+/// resources to be constructed.
+#[derive(Debug, Default, Clone)]
+pub struct ConfigSections {
+    sections: Arc<HashMap<String, Value>>,
+}
+
+impl ConfigSections {
+    /// Construct the configuration from a map of section names to values.
+    pub fn new(sections: HashMap<String, Value>) -> Self {
+        Self {
+            sections: Arc::new(sections),
+        }
+    }
+
+    /// Get the configuration section with the given name.
+    pub fn get(&self, section: &str) -> Option<&Value> {
+        self.sections.get(section)
+    }
+}
+
+/// Implemented for resources which are constructed from a section of the
+/// `ConfigSections` resource. See `Configured<T>`.
+pub trait ConfigConstructible: Sized + Any + Send + Sync {
+    /// Configuration for this resource, deserialized from its section.
+    type Config: DeserializeOwned;
+    /// Error type for when resource fails to be constructed.
+    type Error: Into<anyhow::Error>;
+
+    /// Name of the configuration section for this resource. Defaults to the
+    /// full name of the resource type.
+    fn section() -> &'static str {
+        type_name::<Self>()
+    }
+
+    /// Construct the resource from its configuration, using the provided Aero
+    /// instance to obtain any other resources it depends on.
+    fn construct_from_config(aero: &Aero, config: Self::Config) -> Result<Self, Self::Error>;
+}
+
+/// A resource which is constructed from its section of the `ConfigSections`
+/// resource. Dereferences to `T`.
+///
+/// Construction fails if `ConfigSections` is not present, if it does not
+/// contain a section for `T`, or if the section cannot be deserialized.
+#[derive(Debug, Clone)]
+pub struct Configured<T>(T);
+
+impl<T> Configured<T> {
+    /// Unwrap the inner resource.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Configured<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ConfigConstructible> Constructible for Configured<T> {
+    type Error = anyhow::Error;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        let sections = aero
+            .try_get::<ConfigSections>()
+            .ok_or_else(MissingResource::new::<ConfigSections>)?;
+        let section = T::section();
+        let value = sections
+            .get(section)
+            .ok_or_else(|| anyhow!("Configuration section `{}` does not exist", section))?;
+        let config = serde_json::from_value::<T::Config>(value.clone()).map_err(|e| {
+            anyhow!(e).context(format!("Invalid configuration section `{}`", section))
+        })?;
+        T::construct_from_config(aero, config)
+            .map(Self)
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use crate::{Aero, ObtainError};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct HttpClient {
+        base_url: String,
+    }
+
+    #[derive(Deserialize)]
+    struct HttpClientConfig {
+        base_url: String,
+    }
+
+    impl ConfigConstructible for HttpClient {
+        type Config = HttpClientConfig;
+        type Error = Infallible;
+
+        fn section() -> &'static str {
+            "http"
+        }
+
+        fn construct_from_config(_aero: &Aero, config: Self::Config) -> Result<Self, Infallible> {
+            Ok(Self {
+                base_url: config.base_url,
+            })
+        }
+    }
+
+    #[test]
+    fn configured() {
+        let state = Aero::new().with(ConfigSections::new(
+            [("http".into(), json!({ "base_url": "https://example.com" }))].into(),
+        ));
+        let client = state.obtain::<Configured<HttpClient>>();
+        assert_eq!(client.base_url, "https://example.com");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            Aero::new().try_obtain::<Configured<HttpClient>>(),
+            Err(ObtainError::Missing(_))
+        ));
+        let state = Aero::new().with(ConfigSections::new(
+            [("http".into(), json!({ "url": "https://example.com" }))].into(),
+        ));
+        assert!(matches!(
+            state.try_obtain::<Configured<HttpClient>>(),
+            Err(ObtainError::Failed(_))
+        ));
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+/// Decorators registered for a single resource type, in registration order.
+pub(crate) type Decorators<T> = Vec<Arc<dyn Fn(T, &Aero) -> T + Send + Sync>>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Register a decorator for the resource `T`, which wraps or modifies each newly
+    /// constructed instance of `T` before it is stored, so that everything obtaining
+    /// `T` sees the decorated version. Useful for adding cross-cutting behaviour such
+    /// as caching or logging without changing the `Constructible` implementation.
+    /// Multiple decorators are applied in the order they were registered. Instances
+    /// inserted directly are not decorated.
+    pub fn register_decorator<T: Resource>(
+        &self,
+        f: impl Fn(T, &Aero) -> T + Send + Sync + 'static,
+    ) {
+        self.inner
+            .write()
+            .decorators
+            .entry::<Decorators<T>>()
+            .or_default()
+            .push(Arc::new(f));
+    }
+
+    /// Apply every decorator registered for `T` to a newly constructed instance.
+    pub(crate) fn decorate<T: Resource>(&self, value: T) -> T {
+        // Don't hold the lock whilst calling the decorators, as they may access other
+        // resources.
+        let decorators = match self.inner.read().decorators.get::<Decorators<T>>() {
+            Some(decorators) => decorators.clone(),
+            None => return value,
+        };
+        decorators
+            .iter()
+            .fold(value, |value, decorator| decorator(value, self.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, sync::Arc};
+
+    use crate::{Aero, Constructible};
+
+    #[derive(Debug, Clone)]
+    struct Greeting(Arc<str>);
+
+    impl Constructible for Greeting {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Greeting("Hello".into()))
+        }
+    }
+
+    #[test]
+    fn obtain_decorated() {
+        let state = Aero::new().with('!');
+        state.register_decorator::<Greeting>(|greeting, _| {
+            Greeting(format!("{}, world", greeting.0).into())
+        });
+        state.register_decorator::<Greeting>(|greeting, aero| {
+            Greeting(format!("{}{}", greeting.0, aero.try_get::<char>().unwrap()).into())
+        });
+        assert_eq!(&*state.obtain::<Greeting>().0, "Hello, world!");
+        assert_eq!(&*state.obtain::<Greeting>().0, "Hello, world!");
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use frunk::{prelude::HList, HCons, HNil};
+
+use crate::{
+    resource::{unwrap_constructed_hlist, MissingResource, ObtainError, Resource, ResourceList},
+    state::Aero,
+    sync_constructible::ConstructibleResourceList,
+};
+
+/// A resource which another resource declares that it depends on. See `depends_on!`.
+#[derive(Debug, Clone, Copy)]
+pub struct Dependency {
+    id: TypeId,
+    name: &'static str,
+    has: fn(&Aero) -> bool,
+}
+
+impl Dependency {
+    /// A dependency on the resource `T`.
+    pub fn of<T: Resource>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            has: Aero::has::<T>,
+        }
+    }
+
+    /// Name of the resource type depended on.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// Implemented by `depends_on!` for resources which declare their dependencies.
+pub trait DependsOn: Resource {
+    /// The resources which are needed to construct this resource.
+    fn dependencies() -> Vec<Dependency>;
+}
+
+/// Automatically implemented for lists of resources which declare their dependencies,
+/// see `Aero::declare_all()`.
+pub trait DependsOnList: HList {
+    /// Record the dependencies declared by every resource in this list.
+    fn declare<R: ResourceList>(aero: &Aero<R>);
+}
+impl DependsOnList for HNil {
+    fn declare<R: ResourceList>(_aero: &Aero<R>) {}
+}
+impl<H: DependsOn, T: DependsOnList> DependsOnList for HCons<H, T> {
+    fn declare<R: ResourceList>(aero: &Aero<R>) {
+        aero.declare::<H>();
+        T::declare(aero);
+    }
+}
+
+/// Declared dependencies of each resource type, see `Aero::declare()`.
+pub(crate) type DeclaredDependencies = HashMap<TypeId, (&'static str, Vec<Dependency>)>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Record the dependencies declared by `T` via `depends_on!`, so that they are
+    /// checked by `validate_graph()`. Declaring the same resource more than once has
+    /// no further effect.
+    ///
+    /// Declarations are not discovered automatically: `depends_on!` has no effect
+    /// until this is called for the resource, and the dependencies of resources which
+    /// were never declared are not checked. Use `declare_all()` to declare every
+    /// resource in the application from one place.
+    pub fn declare<T: DependsOn>(&self) {
+        self.inner
+            .write()
+            .declared
+            .insert(TypeId::of::<T>(), (type_name::<T>(), T::dependencies()));
+    }
+
+    /// Builder method equivalent to calling `declare()` but can be chained.
+    pub fn with_declared<T: DependsOn>(self) -> Self {
+        self.declare::<T>();
+        self
+    }
+
+    /// Record the dependencies declared by every resource in `L`, eg.
+    /// `aero.declare_all::<frunk::HList![Repository, Cache]>()`. See `declare()`.
+    pub fn declare_all<L: DependsOnList>(&self) {
+        L::declare(self);
+    }
+
+    /// Builder method equivalent to calling `declare_all()` but can be chained.
+    pub fn with_declared_all<L: DependsOnList>(self) -> Self {
+        self.declare_all::<L>();
+        self
+    }
+
+    /// Check that the dependencies of every declared resource are satisfied, without
+    /// constructing anything. A dependency is satisfied if it is present, or if it
+    /// has itself been declared (in which case its own dependencies are checked in
+    /// turn). Only resources declared with `declare()` or `declare_all()` are checked.
+    /// Call this at startup to catch missing resources early. Returns
+    /// `ObtainError::Missing` for the first unsatisfied dependency, in order of the
+    /// name of the resource which depends on it.
+    pub fn validate_graph(&self) -> Result<(), ObtainError> {
+        let mut declared: Vec<_> = self
+            .inner
+            .read()
+            .declared
+            .iter()
+            .map(|(id, (name, deps))| (*id, *name, deps.clone()))
+            .collect();
+        declared.sort_by_key(|(_, name, _)| *name);
+        let is_declared = |id| declared.iter().any(|(other, _, _)| *other == id);
+        for (_, _, deps) in &declared {
+            for dep in deps {
+                if !is_declared(dep.id) && !(dep.has)(self.as_ref()) {
+                    return Err(ObtainError::Missing(MissingResource::named(dep.name)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Construct every resource in `L`, ordering them so that each resource is
+    /// constructed after any resources in `L` which it declares a dependency on via
+    /// `declare()`. Resources are otherwise constructed according to their ordering
+    /// hints, and then in the order they are listed, so the order is deterministic.
+    /// Returns the names of the resources in the order they were constructed, which
+    /// is useful for logging the startup sequence.
+    ///
+    /// Resources in `L` which were never declared are treated as having no dependencies.
+    /// Fails without constructing anything if the declared dependencies are cyclic.
+    pub fn try_construct_in_dependency_order<L: ConstructibleResourceList>(
+        &self,
+    ) -> Result<Vec<&'static str>, ObtainError> {
+        let mut pending = Vec::new();
+        L::initializers(&mut pending);
+        self.sort_by_ordering_hints(&mut pending, |item| item.id);
+        let deps: Vec<Vec<TypeId>> = {
+            let guard = self.inner.read();
+            pending
+                .iter()
+                .map(|item| {
+                    guard
+                        .declared
+                        .get(&item.id)
+                        .map_or_else(Vec::new, |(_, deps)| {
+                            deps.iter().map(|dep| dep.id).collect()
+                        })
+                })
+                .collect()
+        };
+        let mut pending: Vec<_> = pending.into_iter().zip(deps).collect();
+
+        let mut order = Vec::with_capacity(pending.len());
+        while !pending.is_empty() {
+            let is_ready = |deps: &Vec<TypeId>| {
+                deps.iter()
+                    .all(|dep| !pending.iter().any(|(item, _)| item.id == *dep))
+            };
+            let index = pending
+                .iter()
+                .position(|(_, deps)| is_ready(deps))
+                .ok_or_else(|| {
+                    let names: Vec<_> = pending
+                        .iter()
+                        .map(|(item, _)| format!("`{}`", item.name))
+                        .collect();
+                    ObtainError::Failed(Arc::new(anyhow::anyhow!(
+                        "Declared dependencies are cyclic between {}",
+                        names.join(", ")
+                    )))
+                })?;
+            let (item, _) = pending.remove(index);
+            // Resources listed more than once are only constructed once.
+            pending.retain(|(other, _)| other.id != item.id);
+            order.push(item);
+        }
+
+        for item in &order {
+            (item.init)(self.as_ref())?;
+        }
+        Ok(order.into_iter().map(|item| item.name).collect())
+    }
+
+    /// Construct every resource in `L` in dependency order. Panics if unable. See
+    /// `try_construct_in_dependency_order()`.
+    pub fn construct_in_dependency_order<L: ConstructibleResourceList>(&self) -> Vec<&'static str> {
+        unwrap_constructed_hlist::<L, _>(self.try_construct_in_dependency_order::<L>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{depends_on, test_util::test_resource, Aero, ObtainError};
+
+    #[derive(Clone)]
+    struct DbPool;
+
+    #[derive(Clone)]
+    struct Cache;
+
+    #[derive(Clone)]
+    struct Repository;
+
+    depends_on!(Cache: DbPool);
+    depends_on!(Repository: DbPool, Cache);
+
+    #[test]
+    fn validate_graph() {
+        let state = Aero::new()
+            .with_declared::<Repository>()
+            .with_declared::<Cache>()
+            .with_declared::<Cache>();
+        match state.validate_graph() {
+            Err(ObtainError::Missing(missing)) => assert!(missing.name().ends_with("DbPool")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        state.insert(DbPool);
+        assert!(state.validate_graph().is_ok());
+    }
+
+    #[test]
+    fn declare_all() {
+        let state = Aero::new();
+        // Nothing is checked until the resources are declared.
+        assert!(state.validate_graph().is_ok());
+        state.declare_all::<frunk::HList![Repository, Cache]>();
+        assert!(matches!(
+            state.validate_graph(),
+            Err(ObtainError::Missing(_))
+        ));
+    }
+
+    test_resource!(Config, Pool, Service);
+
+    depends_on!(Pool: Config);
+    depends_on!(Service: Pool, Config);
+
+    #[test]
+    fn construct_in_dependency_order() {
+        let state = Aero::new()
+            .with_declared::<Service>()
+            .with_declared::<Pool>();
+        let order = state.construct_in_dependency_order::<frunk::HList![Service, Pool, Config]>();
+        let order: Vec<_> = order
+            .iter()
+            .map(|name| name.rsplit("::").next().unwrap())
+            .collect();
+        assert_eq!(order, ["Config", "Pool", "Service"]);
+        assert!(state.has::<Service>());
+    }
+
+    test_resource!(Chicken, Egg);
+
+    depends_on!(Chicken: Egg);
+    depends_on!(Egg: Chicken);
+
+    #[test]
+    fn construct_in_dependency_order_cyclic() {
+        let state = Aero::new()
+            .with_declared::<Chicken>()
+            .with_declared::<Egg>();
+        assert!(state
+            .try_construct_in_dependency_order::<frunk::HList![Chicken, Egg]>()
+            .is_err());
+        assert!(!state.has::<Chicken>());
+    }
+}
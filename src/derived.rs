@@ -0,0 +1,103 @@
+use std::{ops::Deref, sync::Arc};
+
+use crate::{
+    resource::{MissingResource, ObtainError, Resource, ResourceList},
+    state::Aero,
+    sync_constructible::{Constructible, ConstructibleResource},
+};
+
+/// Derives an instance of a resource from another resource.
+pub(crate) type Derivation<D> = Arc<dyn Fn(&Aero) -> Result<D, ObtainError> + Send + Sync>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Register a derivation of the resource `D` from the resource `S`, so that
+    /// obtaining `Derived<D>` obtains `S` and maps it using `f`. This is useful for
+    /// extracting a sub-component from a larger resource, eg. a handle from a client,
+    /// without implementing `Constructible`. Replaces any previous derivation of `D`.
+    pub fn register_derived<D: Resource, S: ConstructibleResource>(
+        &self,
+        f: impl Fn(&S) -> D + Send + Sync + 'static,
+    ) {
+        let derivation: Derivation<D> = Arc::new(move |aero| Ok(f(&aero.try_obtain::<S>()?)));
+        self.inner.write().derivations.insert(derivation);
+    }
+}
+
+/// A resource derived from another resource using the function registered with
+/// `Aero::register_derived()`. The function runs once, when `Derived<D>` is first
+/// obtained, and the result is stored like any other resource. Dereferences to `D`.
+#[derive(Debug, Clone)]
+pub struct Derived<D>(D);
+
+impl<D> Derived<D> {
+    /// Unwrap the derived resource.
+    pub fn into_inner(self) -> D {
+        self.0
+    }
+}
+
+impl<D> Deref for Derived<D> {
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        &self.0
+    }
+}
+
+impl<D: Resource> Constructible for Derived<D> {
+    type Error = ObtainError;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        let derivation = aero
+            .inner
+            .read()
+            .derivations
+            .get::<Derivation<D>>()
+            .cloned()
+            .ok_or_else(|| ObtainError::Missing(MissingResource::new::<D>()))?;
+        derivation(aero).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, sync::Arc};
+
+    use crate::{Aero, Constructible, ObtainError};
+
+    use super::Derived;
+
+    #[derive(Debug, Clone)]
+    struct Client {
+        handle: Arc<str>,
+    }
+
+    impl Constructible for Client {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Client {
+                handle: "primary".into(),
+            })
+        }
+    }
+
+    #[test]
+    fn obtain_derived() {
+        let state = Aero::new();
+        state.register_derived::<Arc<str>, Client>(|client| client.handle.clone());
+        assert_eq!(&**state.obtain::<Derived<Arc<str>>>(), "primary");
+        state.obtain::<Derived<Arc<str>>>();
+        assert_eq!(state.construction_count::<Client>(), 1);
+        assert_eq!(state.construction_count::<Derived<Arc<str>>>(), 1);
+    }
+
+    #[test]
+    fn obtain_derived_unregistered() {
+        let state = Aero::new();
+        assert!(matches!(
+            state.try_obtain::<Derived<u32>>(),
+            Err(ObtainError::Missing(_))
+        ));
+    }
+}
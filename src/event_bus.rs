@@ -0,0 +1,102 @@
+//! An in-process publish/subscribe resource. Requires feature `tokio`.
+
+use std::{any::Any, convert::Infallible, fmt};
+
+use tokio::sync::broadcast;
+
+use crate::{state::Aero, sync_constructible::Constructible};
+
+/// An in-process event bus, over which any number of publishers can send events of
+/// type `E` to any number of subscribers. Requires feature `tokio`.
+///
+/// Every clone publishes to the same subscribers, so the bus can be obtained wherever
+/// it is needed: `obtain::<EventBus<E>>()` creates it on first use with
+/// `DEFAULT_CAPACITY`. Insert an instance created with `with_capacity()` beforehand to
+/// use a different capacity.
+///
+/// Subscribers which fall more than the capacity behind miss the oldest events, and
+/// receive `RecvError::Lagged` from their receiver. See `tokio::sync::broadcast`.
+pub struct EventBus<E> {
+    sender: broadcast::Sender<E>,
+}
+
+impl<E> Clone for EventBus<E> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<E> fmt::Debug for EventBus<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.sender.receiver_count())
+            .finish()
+    }
+}
+
+impl<E: Clone> EventBus<E> {
+    /// Capacity of an event bus created by `obtain()`.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Create a new event bus which buffers up to `capacity` events for each
+    /// subscriber. Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity).0,
+        }
+    }
+
+    /// Publish an event to every current subscriber. Returns the number of subscribers
+    /// the event was sent to, which may be zero.
+    pub fn publish(&self, event: E) -> usize {
+        // Only fails if there are no subscribers.
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Subscribe to events published after this call.
+    pub fn subscribe(&self) -> broadcast::Receiver<E> {
+        self.sender.subscribe()
+    }
+
+    /// Number of current subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl<E: Clone + Any + Send + Sync> Constructible for EventBus<E> {
+    type Error = Infallible;
+
+    fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(Self::with_capacity(Self::DEFAULT_CAPACITY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Aero;
+
+    use super::EventBus;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum UserEvent {
+        SignedUp(u32),
+    }
+
+    #[tokio::test]
+    async fn publish_subscribe() {
+        let state = Aero::new();
+        let bus = state.obtain::<EventBus<UserEvent>>();
+        assert_eq!(bus.publish(UserEvent::SignedUp(1)), 0);
+
+        let mut receiver = state
+            .obtain_async::<EventBus<UserEvent>>()
+            .await
+            .subscribe();
+        assert_eq!(bus.subscriber_count(), 1);
+        assert_eq!(bus.publish(UserEvent::SignedUp(2)), 1);
+        assert_eq!(receiver.recv().await.unwrap(), UserEvent::SignedUp(2));
+    }
+}
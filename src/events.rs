@@ -0,0 +1,94 @@
+use futures_channel::mpsc;
+use futures_util::Stream;
+
+use crate::{resource::ResourceList, state::Aero};
+
+/// Number of events buffered for each subscriber before newer events are dropped.
+pub(crate) const EVENT_CAPACITY: usize = 64;
+
+/// A change to the resources in an `Aero` instance. Each event contains the name
+/// of the resource type affected. See `Aero::events()`. Requires feature `async`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AeroEvent {
+    /// A resource was inserted directly.
+    Inserted(&'static str),
+    /// Construction of a resource began.
+    ConstructionStarted(&'static str),
+    /// Construction of a resource finished successfully.
+    ConstructionFinished(&'static str),
+    /// Construction of a resource failed, or was cancelled.
+    ConstructionFailed(&'static str),
+    /// A resource was removed by `reset()` or `clear()`.
+    Removed(&'static str),
+    /// A resource was replaced by `replace()`.
+    Replaced(&'static str),
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Subscribe to changes to the resources in this aerosol instance. Events are
+    /// only recorded whilst there is at least one subscriber. If a subscriber falls
+    /// more than 64 events behind, further events are dropped for that subscriber
+    /// until it catches up. The stream does not depend on any particular async
+    /// runtime. Requires feature `async`.
+    pub fn events(&self) -> impl Stream<Item = AeroEvent> + Send + Unpin + 'static {
+        let (tx, rx) = mpsc::channel(EVENT_CAPACITY);
+        self.inner.write().events.push(tx);
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures_util::{FutureExt, StreamExt};
+
+    use crate::{Aero, Constructible};
+
+    use super::AeroEvent;
+
+    #[derive(Debug, Clone)]
+    struct Dummy;
+
+    impl Constructible for Dummy {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn events() {
+        let state = Aero::new();
+        let mut events = state.events();
+        state.insert(42);
+        state.obtain::<Dummy>();
+        state.replace(43);
+        state.reset::<Dummy>();
+        let dummy = std::any::type_name::<Dummy>();
+        for expected in [
+            AeroEvent::Inserted("i32"),
+            AeroEvent::ConstructionStarted(dummy),
+            AeroEvent::ConstructionFinished(dummy),
+            AeroEvent::Replaced("i32"),
+            AeroEvent::Removed(dummy),
+        ] {
+            assert_eq!(events.next().now_or_never(), Some(Some(expected)));
+        }
+        assert_eq!(events.next().now_or_never(), None);
+    }
+
+    #[test]
+    fn events_unsubscribe() {
+        let state = Aero::new();
+        drop(state.events());
+        let mut events = state.events();
+        state.insert(42);
+        assert_eq!(state.inner.read().events.len(), 1);
+        assert_eq!(
+            events.next().now_or_never(),
+            Some(Some(AeroEvent::Inserted("i32")))
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::{any::type_name, sync::Arc};
+
+use crate::{resource::ResourceList, state::Aero};
+
+/// Produces a fresh instance of `T` every time it is called.
+pub(crate) type Factory<T> = Arc<dyn Fn(&Aero) -> Box<T> + Send + Sync>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Register a factory which `make()` calls to produce a new `Box<T>` every time,
+    /// instead of sharing a single instance. `T` may be unsized, eg. a trait object,
+    /// and does not need to implement `Clone`. Replaces any previously registered
+    /// factory for `T`.
+    pub fn register_factory<T: ?Sized + 'static>(
+        &self,
+        factory: impl Fn(&Aero) -> Box<T> + Send + Sync + 'static,
+    ) {
+        let factory: Factory<T> = Arc::new(factory);
+        self.inner.write().factories.insert(factory);
+    }
+
+    /// Make a new instance of `T` using the factory registered with `register_factory()`.
+    /// Returns `None` if no factory is registered for `T`.
+    pub fn try_make<T: ?Sized + 'static>(&self) -> Option<Box<T>> {
+        // Don't hold the lock whilst calling the factory, as it may access other resources.
+        let factory = self.inner.read().factories.get::<Factory<T>>()?.clone();
+        Some(factory(self.as_ref()))
+    }
+
+    /// Make a new instance of `T` using the factory registered with `register_factory()`.
+    /// Panics if no factory is registered for `T`.
+    pub fn make<T: ?Sized + 'static>(&self) -> Box<T> {
+        self.try_make()
+            .unwrap_or_else(|| panic!("No factory registered for `{}`", type_name::<T>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    };
+
+    use crate::Aero;
+
+    trait Service {
+        fn id(&self) -> i32;
+    }
+
+    struct DummyService(i32);
+
+    impl Service for DummyService {
+        fn id(&self) -> i32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn make() {
+        let state = Aero::new().with(Arc::new(AtomicI32::new(0)));
+        state.register_factory::<dyn Service>(|aero| {
+            let counter = aero.try_get::<Arc<AtomicI32>>().unwrap();
+            Box::new(DummyService(counter.fetch_add(1, Ordering::SeqCst)))
+        });
+        assert_eq!(state.make::<dyn Service>().id(), 0);
+        assert_eq!(state.make::<dyn Service>().id(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "No factory registered")]
+    fn make_missing() {
+        Aero::new().make::<dyn Service>();
+    }
+}
@@ -0,0 +1,80 @@
+use std::{convert::Infallible, sync::Arc};
+
+use crate::{
+    resource::{unwrap_constructed, ObtainError, ResourceList},
+    state::Aero,
+    sync_constructible::{Constructible, ConstructibleResource},
+};
+
+/// Constructing a `Result<T, Arc<anyhow::Error>>` never fails: it attempts to obtain `T`,
+/// and stores the outcome. This allows initialization of an optional component to fail
+/// without failing whatever depends on it, whilst keeping the error around so that each
+/// consumer can decide how to handle it, eg. by disabling a feature and reporting why.
+///
+/// The outcome is stored like any other resource, so construction of `T` is only
+/// attempted once. `T` itself is also stored if it was constructed successfully.
+impl<T: ConstructibleResource> Constructible for Result<T, Arc<anyhow::Error>> {
+    type Error = Infallible;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(aero.try_obtain::<T>().map_err(|e| match e {
+            ObtainError::Failed(e) => e,
+            e => Arc::new(e.into()),
+        }))
+    }
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Get or construct the stored outcome of constructing `T`. The first call attempts
+    /// to obtain `T`, and if that fails, the error is stored and returned from every
+    /// subsequent call rather than retrying. Equivalent to calling
+    /// `obtain::<Result<T, Arc<anyhow::Error>>>()`.
+    pub fn obtain_result<T: ConstructibleResource>(&self) -> Result<T, Arc<anyhow::Error>> {
+        unwrap_constructed::<Result<T, Arc<anyhow::Error>>, _>(self.try_obtain())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use anyhow::anyhow;
+
+    use crate::{Aero, Constructible};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone)]
+    struct SearchIndex;
+
+    impl Constructible for SearchIndex {
+        type Error = anyhow::Error;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+            if aero.has::<i32>() {
+                Ok(SearchIndex)
+            } else {
+                Err(anyhow!("index unavailable"))
+            }
+        }
+    }
+
+    #[test]
+    fn obtain_result() {
+        let state = Aero::new();
+        let error = state.obtain_result::<SearchIndex>().unwrap_err();
+        assert_eq!(error.to_string(), "index unavailable");
+        let again = state.obtain_result::<SearchIndex>().unwrap_err();
+        assert!(Arc::ptr_eq(&error, &again));
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 1);
+        assert!(!state.has::<SearchIndex>());
+
+        let state = Aero::new().with(1);
+        assert!(state.obtain_result::<SearchIndex>().is_ok());
+        assert!(state.has::<SearchIndex>());
+    }
+}
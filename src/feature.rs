@@ -0,0 +1,130 @@
+use std::{collections::HashSet, ops::Deref, sync::Arc};
+
+use crate::{state::Aero, sync_constructible::Constructible, IndirectlyConstructible};
+
+/// The set of runtime feature flags which are enabled. Insert this as a resource
+/// to control which `Feature<T>` resources are enabled. If it is not present,
+/// every feature is disabled.
+#[derive(Debug, Default, Clone)]
+pub struct FeatureFlags {
+    enabled: Arc<HashSet<String>>,
+}
+
+impl FeatureFlags {
+    /// Construct a set of feature flags with the given flags enabled.
+    pub fn new<I: IntoIterator<Item = S>, S: Into<String>>(enabled: I) -> Self {
+        Self {
+            enabled: Arc::new(enabled.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    /// Returns true if the given flag is enabled.
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.enabled.contains(flag)
+    }
+}
+
+/// Implemented for resources which are only constructed when a feature flag is
+/// enabled. See `Feature<T>`.
+pub trait FeatureGated: IndirectlyConstructible + Clone {
+    /// Name of the feature flag which enables this resource.
+    const FLAG: &'static str;
+    /// A no-op implementation to use when the feature flag is disabled.
+    fn disabled() -> Self;
+}
+
+/// A resource which is only constructed if its feature flag is enabled in the
+/// `FeatureFlags` resource. Otherwise, the no-op implementation from
+/// `FeatureGated::disabled()` is used instead, so that callers do not need to
+/// check whether the feature is enabled. Dereferences to `T` either way.
+///
+/// The decision is made once, when `Feature<T>` is constructed, so every caller
+/// sees the same implementation.
+#[derive(Debug, Clone)]
+pub struct Feature<T> {
+    value: T,
+    enabled: bool,
+}
+
+impl<T> Feature<T> {
+    /// Returns true if the feature flag was enabled when this was constructed.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Unwrap the inner implementation.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Feature<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: FeatureGated> Constructible for Feature<T> {
+    type Error = T::Error;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        let enabled = aero
+            .try_get::<FeatureFlags>()
+            .is_some_and(|flags| flags.is_enabled(T::FLAG));
+        let value = if enabled {
+            T::construct(aero)?
+        } else {
+            T::disabled()
+        };
+        Ok(Self { value, enabled })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{Aero, Constructible};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Recommendations(Vec<&'static str>);
+
+    impl Constructible for Recommendations {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(vec!["aerosol"]))
+        }
+    }
+
+    impl FeatureGated for Recommendations {
+        const FLAG: &'static str = "recommendations";
+
+        fn disabled() -> Self {
+            Self(Vec::new())
+        }
+    }
+
+    #[test]
+    fn enabled() {
+        let state = Aero::new().with(FeatureFlags::new(["recommendations"]));
+        let feature = state.obtain::<Feature<Recommendations>>();
+        assert!(feature.is_enabled());
+        assert_eq!(feature.0, ["aerosol"]);
+    }
+
+    #[test]
+    fn disabled() {
+        let state = Aero::new().with(FeatureFlags::new(["other"]));
+        let feature = state.obtain::<Feature<Recommendations>>();
+        assert!(!feature.is_enabled());
+        assert!(feature.0.is_empty());
+        assert!(!Aero::new()
+            .obtain::<Feature<Recommendations>>()
+            .is_enabled());
+    }
+}
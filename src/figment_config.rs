@@ -0,0 +1,119 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    ops::Deref,
+};
+
+use anyhow::anyhow;
+use figment::Figment;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    resource::{MissingResource, ResourceList},
+    state::Aero,
+    sync_constructible::Constructible,
+};
+
+impl<R: ResourceList> Aero<R> {
+    /// Set the key of the section of the `Figment` resource which `Extracted<T>` is
+    /// extracted from, eg. `"database"` or `"server.tls"`. Replaces any previous key for
+    /// `T`. Requires feature `figment`.
+    pub fn register_figment_section<T: DeserializeOwned + Any>(&self, key: impl Into<String>) {
+        self.inner
+            .write()
+            .figment_sections
+            .insert(TypeId::of::<T>(), key.into());
+    }
+}
+
+/// A resource which is extracted from the `figment::Figment` resource. Dereferences to
+/// `T`. Requires feature `figment`.
+///
+/// `T` is extracted from the section registered with `Aero::register_figment_section()`,
+/// or from the whole configuration if no section is registered. Construction fails if
+/// `Figment` is not present, or if extraction fails.
+#[derive(Debug, Clone)]
+pub struct Extracted<T>(T);
+
+impl<T> Extracted<T> {
+    /// Unwrap the inner resource.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Extracted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Any + Send + Sync> Constructible for Extracted<T> {
+    type Error = anyhow::Error;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        let figment = aero
+            .try_get::<Figment>()
+            .ok_or_else(MissingResource::new::<Figment>)?;
+        let key = aero
+            .inner
+            .read()
+            .figment_sections
+            .get(&TypeId::of::<T>())
+            .cloned();
+        let res = match &key {
+            Some(key) => figment.extract_inner::<T>(key),
+            None => figment.extract::<T>(),
+        };
+        res.map(Self).map_err(|e| {
+            anyhow!(e).context(format!(
+                "Failed to extract `{}` from section `{}`",
+                type_name::<T>(),
+                key.as_deref().unwrap_or("<root>")
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use figment::{providers::Serialized, Figment};
+    use serde::Deserialize;
+
+    use crate::{Aero, ObtainError};
+
+    use super::Extracted;
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct DatabaseConfig {
+        url: String,
+        pool_size: u32,
+    }
+
+    #[test]
+    fn extracted() {
+        let figment = Figment::new()
+            .merge(Serialized::default("database.url", "postgres://localhost"))
+            .merge(Serialized::default("database.pool_size", 4));
+        let state = Aero::new().with(figment);
+        state.register_figment_section::<DatabaseConfig>("database");
+        let config = state.obtain::<Extracted<DatabaseConfig>>();
+        assert_eq!(config.url, "postgres://localhost");
+        assert_eq!(config.pool_size, 4);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            Aero::new().try_obtain::<Extracted<DatabaseConfig>>(),
+            Err(ObtainError::Missing(_))
+        ));
+        let state = Aero::new().with(Figment::new());
+        state.register_figment_section::<DatabaseConfig>("database");
+        assert!(matches!(
+            state.try_obtain::<Extracted<DatabaseConfig>>(),
+            Err(ObtainError::Failed(_))
+        ));
+    }
+}
@@ -0,0 +1,72 @@
+//! A process-wide `Aero` instance. Requires feature `global`.
+//!
+//! This is convenient for small applications and scripts, but hides
+//! dependencies and makes testing harder. Libraries should never use it:
+//! prefer passing an `Aero` explicitly.
+
+use std::sync::OnceLock;
+
+use crate::{Aero, ConstructibleResource, ObtainError, Resource};
+
+static GLOBAL: OnceLock<Aero> = OnceLock::new();
+
+/// Get the process-wide `Aero` instance, creating it on first use. Requires feature `global`.
+///
+/// Libraries should not use this, as it hides their dependencies from the application.
+pub fn global() -> &'static Aero {
+    GLOBAL.get_or_init(Aero::new)
+}
+
+/// Directly insert a resource into the global instance. Panics if a resource
+/// of the same type already exists.
+pub fn insert<T: Resource>(value: T) {
+    global().insert(value)
+}
+
+/// Tries to get an instance of `T` from the global instance. Returns `None` if
+/// there is no such instance.
+pub fn try_get<T: Resource>() -> Option<T> {
+    global().try_get()
+}
+
+/// Try to get or construct an instance of `T` in the global instance.
+pub fn try_obtain<T: ConstructibleResource>() -> Result<T, ObtainError> {
+    global().try_obtain()
+}
+
+/// Get or construct an instance of `T` in the global instance. Panics if unable.
+pub fn obtain<T: ConstructibleResource>() -> T {
+    global().obtain()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::Constructible;
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Dummy(i32);
+
+    impl Constructible for Dummy {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(42))
+        }
+    }
+
+    #[test]
+    fn obtain_global() {
+        assert_eq!(obtain::<Dummy>(), Dummy(42));
+        assert_eq!(global().try_get::<Dummy>(), Some(Dummy(42)));
+    }
+
+    #[test]
+    fn insert_global() {
+        insert(13u8);
+        assert_eq!(try_get::<u8>(), Some(13));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{any::TypeId, convert::Infallible, fmt, ops::Deref, sync::Arc};
+
+use crate::{
+    lifetime::Lifetime, resource::ResourceList, state::Aero, sync_constructible::Constructible,
+};
+
+/// Members registered for a single group.
+pub(crate) type Members<G> = Vec<Arc<G>>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Add `value` to the group `G`, which is usually a trait object, eg.
+    /// `aero.register_in_group::<dyn Handler>(Arc::new(LoginHandler))`. Providers
+    /// typically call this from `Constructible::after_construction()`, so that each
+    /// registers itself independently of the others.
+    ///
+    /// Any `GroupResource<G>` which has already been obtained is removed, so that it
+    /// is assembled afresh with the new member the next time it is obtained.
+    pub fn register_in_group<G: ?Sized + Send + Sync + 'static>(&self, value: Arc<G>) {
+        let mut guard = self.inner.write();
+        let inner = &mut *guard;
+        let lifetimes = &mut inner.lifetimes;
+        let members = inner.groups.entry::<Members<G>>().or_insert_with(|| {
+            // The group is being created, so make it transient unless a lifetime has
+            // been registered explicitly.
+            lifetimes
+                .entry(TypeId::of::<GroupResource<G>>())
+                .or_insert(Lifetime::Transient);
+            Vec::new()
+        });
+        members.push(value);
+        drop(guard);
+        self.reset::<GroupResource<G>>();
+    }
+
+    /// Every member of the group `G` registered with `register_in_group()`, in the
+    /// order they were registered.
+    pub fn group<G: ?Sized + Send + Sync + 'static>(&self) -> Vec<Arc<G>> {
+        self.inner
+            .read()
+            .groups
+            .get::<Members<G>>()
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Resource containing every member of the group `G`, eg.
+/// `aero.obtain::<GroupResource<dyn Handler>>()`. Once a member has been registered,
+/// `GroupResource<G>` has a transient lifetime, so the collection is assembled afresh
+/// each time it is obtained. If it is registered as a singleton instead, the stored
+/// collection is replaced whenever a member is registered.
+pub struct GroupResource<G: ?Sized>(Vec<Arc<G>>);
+
+impl<G: ?Sized> GroupResource<G> {
+    /// Take the members of the group.
+    pub fn into_inner(self) -> Vec<Arc<G>> {
+        self.0
+    }
+}
+
+impl<G: ?Sized> Clone for GroupResource<G> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<G: ?Sized> fmt::Debug for GroupResource<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupResource")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+impl<G: ?Sized> Deref for GroupResource<G> {
+    type Target = [Arc<G>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<G: ?Sized + Send + Sync + 'static> Constructible for GroupResource<G> {
+    type Error = Infallible;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(Self(aero.group()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{any::Any, convert::Infallible, sync::Arc};
+
+    use crate::{Aero, Constructible, Lifetime};
+
+    use super::GroupResource;
+
+    trait Handler: Send + Sync {
+        fn name(&self) -> &'static str;
+    }
+
+    #[derive(Clone)]
+    struct Login;
+
+    impl Handler for Login {
+        fn name(&self) -> &'static str {
+            "login"
+        }
+    }
+
+    impl Constructible for Login {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Login)
+        }
+
+        fn after_construction(
+            this: &(dyn Any + Send + Sync),
+            aero: &Aero,
+        ) -> Result<(), Self::Error> {
+            if let Some(this) = this.downcast_ref::<Self>() {
+                aero.register_in_group::<dyn Handler>(Arc::new(this.clone()));
+            }
+            Ok(())
+        }
+    }
+
+    struct Logout;
+
+    impl Handler for Logout {
+        fn name(&self) -> &'static str {
+            "logout"
+        }
+    }
+
+    #[test]
+    fn obtain_group() {
+        let state = Aero::new();
+        assert!(state.obtain::<GroupResource<dyn Handler>>().is_empty());
+        state.init::<Login>();
+        state.register_in_group::<dyn Handler>(Arc::new(Logout));
+        let handlers = state.obtain::<GroupResource<dyn Handler>>();
+        let names: Vec<_> = handlers.iter().map(|h| h.name()).collect();
+        assert_eq!(names, ["login", "logout"]);
+    }
+
+    #[test]
+    fn obtain_group_singleton() {
+        let state = Aero::new();
+        state.register::<GroupResource<dyn Handler>>(Lifetime::Singleton);
+        assert!(state.obtain::<GroupResource<dyn Handler>>().is_empty());
+        state.register_in_group::<dyn Handler>(Arc::new(Logout));
+        assert_eq!(state.obtain::<GroupResource<dyn Handler>>().len(), 1);
+        state.obtain::<GroupResource<dyn Handler>>();
+        assert_eq!(state.construction_count::<GroupResource<dyn Handler>>(), 2);
+    }
+}
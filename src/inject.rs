@@ -0,0 +1,164 @@
+#[cfg(feature = "async")]
+use std::future::Future;
+
+#[cfg(feature = "async")]
+use async_trait::async_trait;
+
+#[cfg(feature = "async")]
+use crate::async_constructible::AsyncConstructibleResource;
+use crate::{
+    resource::{unwrap_constructed, ObtainError, ResourceList},
+    state::Aero,
+    sync_constructible::ConstructibleResource,
+};
+
+/// Implemented for functions whose arguments can all be obtained from an aerosol
+/// instance. Used by `Aero::call`.
+pub trait Injectable<Args> {
+    /// Return type of the function.
+    type Output;
+    /// Obtain each argument, and then call the function.
+    fn try_call<R: ResourceList>(self, aero: &Aero<R>) -> Result<Self::Output, ObtainError>;
+}
+
+/// Implemented for async functions whose arguments can all be obtained asynchronously
+/// from an aerosol instance. Used by `Aero::call_async`. Requires feature `async`.
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait AsyncInjectable<Args>: Sized {
+    /// Output of the future returned by the function.
+    type Output;
+    /// Obtain each argument asynchronously, and then call the function and await
+    /// the result.
+    async fn try_call_async<R: ResourceList>(
+        self,
+        aero: &Aero<R>,
+    ) -> Result<Self::Output, ObtainError>;
+}
+
+macro_rules! impl_injectable {
+    ($($arg:ident),*) => {
+        impl<F, O, $($arg: ConstructibleResource),*> Injectable<($($arg,)*)> for F
+        where
+            F: FnOnce($($arg),*) -> O,
+        {
+            type Output = O;
+
+            #[allow(unused_variables)]
+            fn try_call<R: ResourceList>(self, aero: &Aero<R>) -> Result<O, ObtainError> {
+                Ok(self($(aero.try_obtain::<$arg>()?),*))
+            }
+        }
+
+        #[cfg(feature = "async")]
+        #[async_trait]
+        impl<F, Fut, $($arg: AsyncConstructibleResource),*> AsyncInjectable<($($arg,)*)> for F
+        where
+            F: FnOnce($($arg),*) -> Fut + Send,
+            Fut: Future + Send,
+            Fut::Output: Send,
+        {
+            type Output = Fut::Output;
+
+            #[allow(unused_variables)]
+            async fn try_call_async<R: ResourceList>(
+                self,
+                aero: &Aero<R>,
+            ) -> Result<Fut::Output, ObtainError> {
+                Ok(self($(aero.try_obtain_async::<$arg>().await?),*).await)
+            }
+        }
+    };
+}
+
+impl_injectable!();
+impl_injectable!(A1);
+impl_injectable!(A1, A2);
+impl_injectable!(A1, A2, A3);
+impl_injectable!(A1, A2, A3, A4);
+impl_injectable!(A1, A2, A3, A4, A5);
+impl_injectable!(A1, A2, A3, A4, A5, A6);
+impl_injectable!(A1, A2, A3, A4, A5, A6, A7);
+impl_injectable!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+impl<R: ResourceList> Aero<R> {
+    /// Call `f`, obtaining each of its arguments from this aerosol instance first.
+    /// Functions with up to eight arguments are supported.
+    pub fn try_call<Args, F: Injectable<Args>>(&self, f: F) -> Result<F::Output, ObtainError> {
+        f.try_call(self)
+    }
+    /// Call `f`, obtaining each of its arguments from this aerosol instance first.
+    /// Panics if any argument cannot be obtained.
+    pub fn call<Args, F: Injectable<Args>>(&self, f: F) -> F::Output {
+        unwrap_constructed::<F, _>(self.try_call(f))
+    }
+    /// Call the async function `f`, obtaining each of its arguments asynchronously
+    /// from this aerosol instance first. Requires feature `async`.
+    #[cfg(feature = "async")]
+    pub async fn try_call_async<Args, F: AsyncInjectable<Args>>(
+        &self,
+        f: F,
+    ) -> Result<F::Output, ObtainError> {
+        f.try_call_async(self).await
+    }
+    /// Call the async function `f`, obtaining each of its arguments asynchronously
+    /// from this aerosol instance first. Panics if any argument cannot be obtained.
+    /// Requires feature `async`.
+    #[cfg(feature = "async")]
+    pub async fn call_async<Args, F: AsyncInjectable<Args>>(&self, f: F) -> F::Output {
+        unwrap_constructed::<F, _>(self.try_call_async(f).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, sync::Arc};
+
+    use crate::{Aero, Constructible, MissingResource, ObtainError};
+
+    #[derive(Debug, Clone)]
+    struct Pool(i32);
+
+    impl Constructible for Pool {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(aero.try_get::<i32>().unwrap_or_default()))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Missing;
+
+    impl Constructible for Missing {
+        type Error = MissingResource;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Err(MissingResource::new::<Self>())
+        }
+    }
+
+    #[test]
+    fn call() {
+        let state = Aero::new().with(42);
+        assert_eq!(state.call(|a: Pool, b: Arc<Pool>| a.0 + b.0), 84);
+        assert_eq!(state.call(|| 1), 1);
+    }
+
+    #[test]
+    fn try_call_missing() {
+        let state = Aero::new();
+        assert!(matches!(
+            state.try_call(|_: Pool, _: Missing| ()),
+            Err(ObtainError::Missing(_))
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn call_async() {
+        let state = Aero::new().with(42);
+        let res = state.call_async(|pool: Pool| async move { pool.0 }).await;
+        assert_eq!(res, 42);
+    }
+}
@@ -0,0 +1,249 @@
+use std::{any::type_name, collections::HashMap, fmt::Debug, hash::Hash, task::Poll, thread};
+
+use crate::{
+    resource::{unwrap_constructed, ObtainError, Resource, ResourceList},
+    slot::Slot,
+    state::{Aero, WaitRegistration},
+    sync::safe_park,
+};
+
+/// Bound on the types which can be used as a key for `Aero::insert_by` and
+/// `Aero::get_by`. For example, an enum identifying one of several database
+/// shards.
+pub trait ResourceKey: Hash + Eq + Debug + Send + Sync + 'static {}
+impl<T: Hash + Eq + Debug + Send + Sync + 'static> ResourceKey for T {}
+
+/// Keyed instances of a single resource type. Keys which are being constructed by
+/// `Aero::obtain_keyed_by` hold a placeholder, exactly like unkeyed resources.
+pub(crate) type KeyedSlots<K, T> = HashMap<K, Slot<T>>;
+
+/// Removes the placeholder for the given key if dropped without being filled, eg.
+/// because the constructor failed or panicked, waking anyone waiting for it.
+struct KeyedPlaceholderGuard<'a, R: ResourceList, K: ResourceKey, T: Resource> {
+    aero: &'a Aero<R>,
+    key: Option<K>,
+    value: Option<T>,
+}
+
+impl<R: ResourceList, K: ResourceKey, T: Resource> KeyedPlaceholderGuard<'_, R, K, T> {
+    fn fill(mut self, value: T) {
+        self.value = Some(value);
+    }
+}
+
+impl<R: ResourceList, K: ResourceKey, T: Resource> Drop for KeyedPlaceholderGuard<'_, R, K, T> {
+    fn drop(&mut self) {
+        let key = self.key.take().expect("Key was already taken");
+        let mut guard = self.aero.inner.write();
+        guard.release_waiters(
+            guard
+                .keyed
+                .get::<KeyedSlots<K, T>>()
+                .and_then(|slots| slots.get(&key)),
+        );
+        let slots = guard.keyed.entry::<KeyedSlots<K, T>>().or_default();
+        match self.value.take() {
+            Some(value) => slots.insert(key, Slot::Filled(value)),
+            None => slots.remove(&key),
+        };
+    }
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Directly insert a resource into the collection under the given key. Several
+    /// instances of the same resource type can be stored under different keys, and
+    /// are independent of any instance stored without a key. Panics if a resource
+    /// of the same type already exists with the same key.
+    pub fn insert_by<K: ResourceKey, T: Resource>(&self, key: K, value: T) {
+        let mut guard = self.inner.write();
+        let slots = guard.keyed.entry::<KeyedSlots<K, T>>().or_default();
+        if slots.contains_key(&key) {
+            drop(guard);
+            panic!(
+                "Duplicate resource: attempted to add a second `{}` with key `{:?}`",
+                type_name::<T>(),
+                key
+            );
+        }
+        slots.insert(key, Slot::Filled(value));
+    }
+
+    /// Builder method equivalent to calling `insert_by()` but can be chained.
+    pub fn with_by<K: ResourceKey, T: Resource>(self, key: K, value: T) -> Self {
+        self.insert_by(key, value);
+        self
+    }
+
+    /// Tries to get the instance of `T` stored under the given key. Returns `None`
+    /// if there is no such instance, or if it is still being constructed.
+    pub fn get_by<T: Resource, K: ResourceKey>(&self, key: K) -> Option<T> {
+        match self
+            .inner
+            .read()
+            .keyed
+            .get::<KeyedSlots<K, T>>()?
+            .get(&key)?
+        {
+            Slot::Filled(x) => Some(x.clone()),
+            Slot::Placeholder { .. } | Slot::Failed(_) => None,
+        }
+    }
+
+    /// Poll for the slot for `T` with the given key to not have a placeholder. If the
+    /// slot is empty, a placeholder owned by the current thread is inserted, and
+    /// `None` is returned.
+    fn poll_for_keyed_slot<T: Resource, K: ResourceKey + Clone>(
+        &self,
+        key: &K,
+        registration: &mut Option<WaitRegistration>,
+    ) -> Poll<Result<Option<T>, ObtainError>> {
+        let mut guard = self.inner.write();
+        if let Some(slot) = guard
+            .keyed
+            .get::<KeyedSlots<K, T>>()
+            .and_then(|slots| slots.get(key))
+        {
+            return guard
+                .poll_occupied(slot, registration, thread::current, T::clone)
+                .map(|res| res.map_err(ObtainError::Failed));
+        }
+        guard
+            .keyed
+            .entry::<KeyedSlots<K, T>>()
+            .or_default()
+            .insert(key.clone(), Slot::placeholder(thread::current().into()));
+        guard.finish_wait(registration);
+        Poll::Ready(Ok(None))
+    }
+
+    /// Tries to get the instance of `T` stored under the given key, constructing it
+    /// with `construct` if it does not exist yet. Concurrent calls with the same key
+    /// share a single construction, whilst calls with different keys construct
+    /// independently. The constructed instance can subsequently be accessed via
+    /// `get_by()`.
+    ///
+    /// Like other resources, a constructor which obtains the same resource with the
+    /// same key panics rather than deadlocking, as does a cycle of keyed constructors
+    /// spread across several threads.
+    pub fn try_obtain_keyed_by<T: Resource, K: ResourceKey + Clone, E: Into<anyhow::Error>>(
+        &self,
+        key: K,
+        construct: impl FnOnce(&Aero, &K) -> Result<T, E>,
+    ) -> Result<T, ObtainError> {
+        if let Some(value) = self.get_by::<T, _>(key.clone()) {
+            return Ok(value);
+        }
+        let mut registration = None;
+        loop {
+            match self.poll_for_keyed_slot::<T, K>(&key, &mut registration) {
+                Poll::Pending => safe_park(),
+                Poll::Ready(Ok(Some(value))) => return Ok(value),
+                Poll::Ready(Ok(None)) => break,
+                Poll::Ready(Err(e)) => return Err(e),
+            }
+        }
+
+        let guard = KeyedPlaceholderGuard {
+            aero: self,
+            key: Some(key.clone()),
+            value: None,
+        };
+        let value = construct(self.as_ref(), &key).map_err(|e| self.construct_failed::<T>(e))?;
+        guard.fill(value.clone());
+        Ok(value)
+    }
+
+    /// Get the instance of `T` stored under the given key, constructing it with
+    /// `construct` if it does not exist yet. Panics if construction fails.
+    pub fn obtain_keyed_by<T: Resource, K: ResourceKey + Clone, E: Into<anyhow::Error>>(
+        &self,
+        key: K,
+        construct: impl FnOnce(&Aero, &K) -> Result<T, E>,
+    ) -> T {
+        unwrap_constructed::<T, _>(self.try_obtain_keyed_by(key, construct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::Duration,
+    };
+
+    use crate::{Aero, ObtainError};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Shard {
+        Primary,
+        Replica,
+    }
+
+    #[test]
+    fn get_by() {
+        let state = Aero::new()
+            .with_by(Shard::Primary, "primary")
+            .with_by(Shard::Replica, "replica")
+            .with("default");
+        assert_eq!(state.get_by::<&str, _>(Shard::Primary), Some("primary"));
+        assert_eq!(state.get_by::<&str, _>(Shard::Replica), Some("replica"));
+        assert_eq!(state.try_get::<&str>(), Some("default"));
+        assert_eq!(state.get_by::<i32, _>(Shard::Primary), None);
+    }
+
+    #[test]
+    fn obtain_keyed_by() {
+        let state = Aero::new();
+        let count = AtomicUsize::new(0);
+        let construct = |_: &Aero, key: &Shard| {
+            count.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            Ok::<_, Infallible>(format!("{:?}", key))
+        };
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    assert_eq!(state.obtain_keyed_by(Shard::Primary, construct), "Primary");
+                });
+            }
+        });
+        assert_eq!(state.obtain_keyed_by(Shard::Replica, construct), "Replica");
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            state.get_by::<String, _>(Shard::Primary).as_deref(),
+            Some("Primary")
+        );
+    }
+
+    #[test]
+    fn obtain_keyed_by_failed() {
+        let state = Aero::new();
+        let res = state.try_obtain_keyed_by(Shard::Primary, |_, _| {
+            Err::<String, _>(anyhow::anyhow!("Connection refused"))
+        });
+        assert!(matches!(res, Err(ObtainError::Failed(_))));
+        // The placeholder is removed, so construction can be retried.
+        assert_eq!(state.get_by::<String, _>(Shard::Primary), None);
+        let construct = |_: &Aero, _: &Shard| Ok::<_, Infallible>("retried".to_string());
+        assert_eq!(state.obtain_keyed_by(Shard::Primary, construct), "retried");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cycle detected")]
+    fn obtain_keyed_by_cyclic() {
+        fn construct(aero: &Aero, key: &Shard) -> Result<String, Infallible> {
+            Ok(aero.obtain_keyed_by(*key, construct))
+        }
+        Aero::new().obtain_keyed_by(Shard::Primary, construct);
+    }
+
+    #[test]
+    #[should_panic(expected = "with key `Primary`")]
+    fn duplicate() {
+        Aero::new()
+            .with_by(Shard::Primary, 1)
+            .with_by(Shard::Primary, 2);
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    fmt,
+    ops::Deref,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    resource::ResourceList, state::Aero, sync_constructible::ConstructibleResource, weak::WeakAero,
+};
+
+/// A handle to a resource which is only constructed when it is first dereferenced.
+/// Inserted by `Aero::register_lazy()`, so that `Lazy<T>` is present (and can be
+/// listed as a required resource) even though `T` may never be constructed.
+///
+/// `T` is obtained from the aerosol instance the handle was registered with, so once
+/// materialized it is shared with anything else obtaining `T` from there. Clones of the
+/// handle share the same cell.
+pub struct Lazy<T> {
+    cell: Arc<OnceLock<T>>,
+    // Weak, since the handle is stored within the instance itself.
+    aero: WeakAero,
+}
+
+impl<T> Clone for Lazy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell.clone(),
+            aero: self.aero.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cell.get() {
+            Some(value) => f.debug_tuple("Lazy").field(value).finish(),
+            None => f.write_str("Lazy(<unmaterialized>)"),
+        }
+    }
+}
+
+impl<T: ConstructibleResource> Lazy<T> {
+    /// Get the resource, constructing it if this is the first access. Panics if it
+    /// cannot be constructed, or if the aerosol instance has been dropped.
+    pub fn get(&self) -> &T {
+        self.cell.get_or_init(|| {
+            self.aero
+                .upgrade()
+                .expect("Aerosol instance was dropped before lazy resource was materialized")
+                .obtain::<T>()
+        })
+    }
+}
+
+impl<T> Lazy<T> {
+    /// Returns true if the resource has been constructed.
+    pub fn is_materialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+}
+
+impl<T: ConstructibleResource> Deref for Lazy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Insert a `Lazy<T>` handle, which constructs `T` when it is first dereferenced.
+    /// This allows expensive resources which may never be used to be listed as
+    /// required resources, eg. `Aero![Lazy<SearchIndex>]`, without constructing them
+    /// up front. Does nothing if a `Lazy<T>` is already present.
+    pub fn register_lazy<T: ConstructibleResource>(&self) {
+        if !self.has::<Lazy<T>>() {
+            self.insert(Lazy::<T> {
+                cell: Arc::new(OnceLock::new()),
+                aero: self.downgrade(),
+            });
+        }
+    }
+
+    /// Builder method equivalent to calling `register_lazy()` but can be chained.
+    pub fn with_lazy<T: ConstructibleResource>(self) -> Aero<frunk::HCons<Lazy<T>, R>> {
+        self.register_lazy::<T>();
+        self.assert()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{Aero, Constructible};
+
+    use super::Lazy;
+
+    #[derive(Debug, Clone)]
+    struct SearchIndex;
+
+    impl Constructible for SearchIndex {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(SearchIndex)
+        }
+    }
+
+    #[test]
+    fn lazy() {
+        let state = Aero::new().with_lazy::<SearchIndex>();
+        let lazy: Lazy<SearchIndex> = state.get();
+        assert!(!lazy.is_materialized());
+        assert!(!state.has::<SearchIndex>());
+        let _index: &SearchIndex = &lazy;
+        assert!(lazy.is_materialized());
+        assert!(state.has::<SearchIndex>());
+        assert!(state.get::<Lazy<SearchIndex>, _>().is_materialized());
+    }
+}
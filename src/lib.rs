@@ -7,7 +7,9 @@
 //! first needed. Resources can access and/or initialize other resources on creation.
 //!
 //! The crate will detect dependency cycles (if constructing resource A requires resource B which
-//! itself requires resource A) and will panic rather than stack overflow in that case.
+//! itself requires resource A) and will panic rather than stack overflow in that case. This
+//! includes cycles spanning multiple threads or tasks (thread 1 is constructing A and waiting
+//! for B, whilst thread 2 is constructing B and waiting for A), which would otherwise deadlock.
 //!
 //! The `Aero` type has an optional type parameter to make certain resources *required*. When
 //! a resource is required it can be accessed infallibly. The `Aero![...]` macro exists to
@@ -20,13 +22,98 @@
 //! ### `async`
 //!
 //! Allows resources to be constructed asynchrously, and provides a corresponding
-//! `AsyncConstructibleResource` trait.
+//! `AsyncConstructibleResource` trait. Also allows changes to resources to be observed
+//! via `Aero::events()`. Does not depend on any particular async runtime: enable the
+//! `tokio` feature as well for integration with `tokio`.
 //!
 //! ### `axum`
 //!
 //! Provides integrations with the `axum` web framework. See the `axum` module
 //! for more information.
 //!
+//! ### `clap`
+//!
+//! Provides `Aero::with_clap()`, which parses command-line arguments into a `clap`
+//! parser type and inserts it as a resource, so that other resources can be
+//! constructed from the parsed arguments.
+//!
+//! ### `derive`
+//!
+//! Provides `#[derive(Resource)]`, which checks at compile time that every field of a
+//! resource is cheap to clone, since resources are cloned every time they are obtained.
+//! Also provides `#[derive(Constructible)]`, which constructs a struct by obtaining
+//! each of its fields. A field marked `#[aerosol(container)]` is filled with a handle
+//! to the container instead.
+//!
+//! ### `tracing`
+//!
+//! Resources are constructed within a `construct` span, which for resources constructed
+//! asynchronously is nested within the `tracing` span of the caller which triggered
+//! construction, so that any logs emitted during construction are correlated with eg.
+//! the request which needed the resource. The span identifies the resource by its type
+//! name, or by `Constructible::TRACING_NAME` if set. Enabled automatically by the `axum`
+//! feature.
+//!
+//! ### `figment`
+//!
+//! Provides the `Extracted<T>` resource type, which is constructed by extracting a
+//! section of a `figment::Figment` resource, for applications which use `figment` for
+//! layered configuration.
+//!
+//! ### `global`
+//!
+//! Provides a lazily initialized process-wide `Aero` via the `global()` function, along
+//! with free functions such as `obtain()` which operate on it. This is convenient for
+//! small applications and scripts, but is discouraged for libraries.
+//!
+//! ### `local`
+//!
+//! Provides the `LocalAero` type, a single-threaded container whose resources need not be
+//! `Send` or `Sync`. This is useful for GUI applications or WASM, where resources are
+//! often built on `Rc` or `RefCell`. Only the core API of `Aero` is supported: see the
+//! `LocalAero` documentation for details.
+//!
+//! ### `parking-lot`
+//!
+//! Enabled by default. Allows `parking_lot::Mutex<T>` and `parking_lot::RwLock<T>` to be
+//! constructed automatically when `T` is constructible. These impls were previously
+//! unconditional, so builds using `default-features = false` must now enable this feature
+//! to keep them.
+//!
+//! ### `serde`
+//!
+//! Provides the `ConfigConstructible` trait, which allows resources to be constructed
+//! from a section of a configuration blob, deserialized using `serde`. Also allows
+//! resources to be saved and restored via `Aero::snapshot_json()` and
+//! `Aero::hydrate_from_json()`.
+//!
+//! ### `std-locks`
+//!
+//! Enabled by default. Allows `std::sync::Mutex<T>` and `std::sync::RwLock<T>` to be
+//! constructed automatically when `T` is constructible. These impls were previously
+//! unconditional, so builds using `default-features = false` must now enable this feature
+//! to keep them.
+//!
+//! ### `tokio`
+//!
+//! Enables the `async` feature, and integrates it with the `tokio` runtime: synchronous
+//! constructors passed to `Aero::obtain_blocking_async()` run on tokio's blocking thread
+//! pool by default, `AsyncConstructible::CONSTRUCT_TIMEOUT` is enforced using tokio's
+//! timer by default, and `Aero::prefetch_async()` constructs resources on a background
+//! task. Also provides the `EventBus<E>` resource type for in-process publish/subscribe
+//! messaging.
+//!
+//! ### `tower`
+//!
+//! Provides helpers for configuring `tower` middleware from resources. See the `tower`
+//! module for more information.
+//!
+//! ### `watch`
+//!
+//! Provides the `Watchable<T>` resource type, which allows a resource to be updated after
+//! it has been constructed (eg. for configuration hot-reloading), and allows consumers
+//! to wait for updates.
+//!
 //! ## Example usage
 //!
 //! ```rust
@@ -128,16 +215,26 @@
 //! ## Implementation details
 //!
 //! The `Aero` type manages shared ownership of a map from resource types to "slots".
-//! For a given resource type, the corresponding "slot" can be in one of three state:
+//! For a given resource type, the corresponding "slot" can be in one of four states:
 //! 1) Absent.
-//!     No instance of this resource is present in the map.
+//!    No instance of this resource is present in the map.
 //! 2) Present.
-//!     An instance of this resource exists in the map and can be accessed immediately.
+//!    An instance of this resource exists in the map and can be accessed immediately.
 //! 3) Under construction.
-//!     An instance of this resource is currently under construction, and may be accessed
-//!     once construction has finished.
-//!     The slot maintains a list of threads or tasks waiting for this resource to be
-//!     constructed, and will wake them when the resource becomes available.
+//!    An instance of this resource is currently under construction, and may be accessed
+//!    once construction has finished.
+//!    The slot maintains a list of threads or tasks waiting for this resource to be
+//!    constructed, and will wake them when the resource becomes available. This list
+//!    has its own lock, so waiting on a resource only requires shared access to the map.
+//!    Inserting the placeholder when construction starts, and replacing it when
+//!    construction finishes, still briefly takes exclusive access to the whole map,
+//!    but the constructor itself always runs without any lock held.
+//!    The map is always protected by a `parking_lot::RwLock`. `Aero` is not generic over
+//!    the lock type, since constructors receive a plain `&Aero` and every constructor
+//!    trait would need to carry the lock type as well.
+//! 4) Failed.
+//!    Construction of this resource failed, and failure caching is enabled. Attempts to
+//!    obtain the resource return the cached error until the slot is reset.
 //!
 //! Resources can be constructed synchronously, or (when the feature is enabled) asynchronously.
 //!
@@ -152,28 +249,112 @@
 
 pub use frunk;
 
+#[cfg(feature = "derive")]
+pub use aerosol_derive::{Constructible, Resource};
+
+mod alias;
 #[cfg(feature = "async")]
 mod async_;
 #[cfg(feature = "async")]
 mod async_constructible;
 #[cfg(feature = "axum")]
 pub mod axum;
+mod builder;
+mod cancel;
+#[cfg(feature = "clap")]
+mod cli;
+#[cfg(feature = "serde")]
+mod config;
+mod decorator;
+mod dependencies;
+mod derived;
+#[cfg(feature = "tokio")]
+mod event_bus;
+#[cfg(feature = "async")]
+mod events;
+mod factory;
+mod fallible;
+mod feature;
+#[cfg(feature = "figment")]
+mod figment_config;
+#[cfg(feature = "global")]
+mod global;
+mod group;
+mod inject;
+mod keyed;
+mod lazy;
+mod lifetime;
+mod link;
+#[cfg(feature = "local")]
+mod local;
 mod macros;
+mod ordering;
+mod phase;
+mod post_construct;
+mod primary;
+mod record;
+mod render;
 mod resource;
 mod slot;
+mod slot_map;
+#[cfg(feature = "serde")]
+mod snapshot;
 mod state;
+mod stats;
 mod sync;
 mod sync_constructible;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "tower")]
+pub mod tower;
+mod wait_graph;
+#[cfg(feature = "watch")]
+mod watch;
+mod weak;
 
-pub use resource::{Resource, ResourceList};
-pub use state::Aero;
+pub use builder::{Built, ConstructibleBuilder};
+pub use cancel::CancelToken;
+#[cfg(feature = "serde")]
+pub use config::{ConfigConstructible, ConfigSections, Configured};
+pub use dependencies::{Dependency, DependsOn, DependsOnList};
+pub use derived::Derived;
+#[cfg(feature = "tokio")]
+pub use event_bus::EventBus;
+#[cfg(feature = "async")]
+pub use events::AeroEvent;
+pub use feature::{Feature, FeatureFlags, FeatureGated};
+#[cfg(feature = "figment")]
+pub use figment_config::Extracted;
+#[cfg(feature = "global")]
+pub use global::{global, insert, obtain, try_get, try_obtain};
+pub use group::GroupResource;
+#[cfg(feature = "async")]
+pub use inject::AsyncInjectable;
+pub use inject::Injectable;
+pub use keyed::ResourceKey;
+pub use lazy::Lazy;
+pub use lifetime::Lifetime;
+#[cfg(feature = "local")]
+pub use local::{LocalAero, LocalConstructible, LocalResource, LocalResourceList};
+pub use ordering::{OrderingHint, Priority};
+pub use phase::Phase;
+pub use record::{RecordedEvent, Recorder};
+pub use resource::{
+    DefaultResourceList, MissingResource, ObtainError, ObtainErrorKind, Resource, ResourceList,
+};
+pub use state::{Aero, ReadGuard};
+pub use stats::FailureStat;
+#[cfg(feature = "watch")]
+pub use watch::Watchable;
+pub use weak::WeakAero;
 
 pub use sync_constructible::{
-    Constructible, ConstructibleResource, ConstructibleResourceList, IndirectlyConstructible,
+    ConstructCtx, Constructible, ConstructibleResource, ConstructibleResourceList,
+    IndirectlyConstructible,
 };
 
 #[cfg(feature = "async")]
 pub use async_constructible::{
     AsyncConstructible, AsyncConstructibleResource, AsyncConstructibleResourceList,
-    IndirectlyAsyncConstructible,
+    IndirectlyAsyncConstructible, SharedResourceFuture, UnboxedAsyncConstructible,
 };
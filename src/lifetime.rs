@@ -0,0 +1,190 @@
+use std::{
+    any::{type_name, TypeId},
+    time::Duration,
+};
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+/// Controls how instances of a resource are shared. Set with `Aero::register()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lifetime {
+    /// A single instance is constructed and shared. Scopes created with
+    /// `Aero::scope()` reuse their parent's instance if it has one.
+    #[default]
+    Singleton,
+    /// A new instance is constructed every time the resource is obtained, and
+    /// is never stored. An instance inserted directly is still returned.
+    Transient,
+    /// A single instance is constructed and shared within each scope created
+    /// with `Aero::scope()`. Scopes never reuse their parent's instance.
+    Scoped,
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Set the lifetime of the resource `T`. Applies to this aerosol instance and
+    /// to any scopes created from it. Resources which have not been registered
+    /// are singletons. Registering a lifetime does not affect any instance of `T`
+    /// which already exists.
+    pub fn register<T: Resource>(&self, lifetime: Lifetime) {
+        self.inner
+            .write()
+            .lifetimes
+            .insert(TypeId::of::<T>(), lifetime);
+    }
+
+    /// Expire the instance of `T` once `ttl` has elapsed since it was constructed or
+    /// inserted, so that the next call to `obtain()` constructs a fresh instance. This
+    /// is useful for resources which must be periodically rebuilt, such as a rotating
+    /// access token. Concurrent calls to `obtain()` share a single reconstruction, and
+    /// instances which have already been obtained are unaffected. Until then, an
+    /// expired instance is treated as absent by `get()`, `try_get()` and `has()`.
+    ///
+    /// Resources which are required to be present cannot expire, so this panics if
+    /// `T` is one of the required resources in `R`. Likewise, `with()` and `assert()`
+    /// panic if `T` has a TTL.
+    pub fn register_with_ttl<T: Resource>(&self, ttl: Duration) {
+        if R::contains(TypeId::of::<T>()) {
+            required_ttl::<T>();
+        }
+        self.inner.write().ttls.insert(TypeId::of::<T>(), ttl);
+    }
+
+    /// Panic if `T` has a TTL, as it is about to become a required resource.
+    pub(crate) fn check_requirable<T: Resource>(&self) {
+        if self.inner.read().ttls.contains_key(&TypeId::of::<T>()) {
+            required_ttl::<T>();
+        }
+    }
+
+    /// The lifetime of the resource `T`, as set by `register()` on this aerosol
+    /// instance or one of its parents.
+    pub fn lifetime<T: Resource>(&self) -> Lifetime {
+        self.inner.read().lifetime(TypeId::of::<T>())
+    }
+
+    /// Create a new scope. Resources are looked up in `self` if they are not present
+    /// in the scope, except for resources with a `Scoped` lifetime. Anything constructed
+    /// within the scope is stored in the scope, and is dropped along with it.
+    pub fn scope(&self) -> Aero {
+        let scope = Aero::new();
+        scope.set_parent(self.clone().into());
+        scope
+    }
+}
+
+fn required_ttl<T>() -> ! {
+    panic!(
+        "Resource `{}` cannot be both required and have a TTL",
+        type_name::<T>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{Aero, Constructible};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Dummy;
+
+    impl Constructible for Dummy {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn transient() {
+        let state = Aero::new();
+        state.register::<Dummy>(Lifetime::Transient);
+        state.obtain::<Dummy>();
+        state.obtain::<Dummy>();
+        assert_eq!(state.construction_count::<Dummy>(), 2);
+        assert!(!state.has::<Dummy>());
+    }
+
+    #[test]
+    fn transient_inserted() {
+        let state = Aero::new();
+        state.register::<Dummy>(Lifetime::Transient);
+        state.insert(Dummy);
+        state.obtain::<Dummy>();
+        assert_eq!(state.construction_count::<Dummy>(), 0);
+    }
+
+    #[test]
+    fn singleton() {
+        let state = Aero::new();
+        state.obtain::<Dummy>();
+        let scope = state.scope();
+        scope.obtain::<Dummy>();
+        assert_eq!(state.construction_count::<Dummy>(), 1);
+        assert_eq!(scope.construction_count::<Dummy>(), 0);
+    }
+
+    #[test]
+    fn scoped() {
+        let state = Aero::new();
+        state.register::<Dummy>(Lifetime::Scoped);
+        state.obtain::<Dummy>();
+        let scope = state.scope();
+        assert_eq!(scope.lifetime::<Dummy>(), Lifetime::Scoped);
+        assert!(!scope.has::<Dummy>());
+        scope.obtain::<Dummy>();
+        scope.obtain::<Dummy>();
+        assert_eq!(scope.construction_count::<Dummy>(), 1);
+        assert_eq!(state.scope().construction_count::<Dummy>(), 0);
+    }
+
+    #[test]
+    fn ttl() {
+        let state = Aero::new();
+        state.register_with_ttl::<Dummy>(Duration::from_millis(50));
+        state.obtain::<Dummy>();
+        state.obtain::<Dummy>();
+        assert_eq!(state.construction_count::<Dummy>(), 1);
+        std::thread::sleep(Duration::from_millis(60));
+        state.obtain::<Dummy>();
+        state.obtain::<Dummy>();
+        assert_eq!(state.construction_count::<Dummy>(), 2);
+    }
+
+    #[test]
+    fn ttl_reads() {
+        let state = Aero::new();
+        state.register_with_ttl::<Dummy>(Duration::from_millis(50));
+        state.obtain::<Dummy>();
+        assert!(state.has::<Dummy>());
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!state.has::<Dummy>());
+        assert!(state.try_get::<Dummy>().is_none());
+        assert!(state.read().get::<Dummy>().is_none());
+        assert!(!state.read().has::<Dummy>());
+        state.obtain::<Dummy>();
+        assert!(state.has::<Dummy>());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot be both required and have a TTL")]
+    fn ttl_required() {
+        let state = Aero::new().with(Dummy);
+        state.register_with_ttl::<Dummy>(Duration::from_millis(50));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot be both required and have a TTL")]
+    fn ttl_then_required() {
+        let state = Aero::new();
+        state.register_with_ttl::<Dummy>(Duration::from_millis(50));
+        state.obtain::<Dummy>();
+        let _ = state.assert::<Dummy>();
+    }
+}
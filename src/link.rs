@@ -0,0 +1,96 @@
+use std::any::TypeId;
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+impl<R: ResourceList> Aero<R> {
+    /// Share the resource `T` with `source`, without sharing anything else. Lookups of
+    /// `T` in this instance are delegated to `source`, so both instances see the same
+    /// singleton, and if `T` is not yet present it is obtained (and constructed if
+    /// necessary) within `source`. Later changes to `T` in `source` are visible here.
+    ///
+    /// This is narrower than `scope()`, which inherits every resource from its parent.
+    /// A `T` inserted directly into this instance takes precedence over the link.
+    /// Panics if `source` is this instance, or if `source` already looks up `T` in
+    /// this instance through its own links or parents, as lookups would never end.
+    pub fn link<T: Resource, R2: ResourceList>(&self, source: &Aero<R2>) {
+        let source: Aero = source.clone().into();
+        let mut next = Some(source.clone());
+        while let Some(current) = next {
+            assert!(
+                !self.shares_storage_with(&current),
+                "Cannot link `{}` to the same aerosol instance, directly or through other links",
+                std::any::type_name::<T>()
+            );
+            next = current.inner.read().parent_for::<T>().cloned();
+        }
+        self.inner.write().links.insert(TypeId::of::<T>(), source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{Aero, Constructible};
+
+    #[derive(Debug, Clone)]
+    struct DbPool(u32);
+
+    impl Constructible for DbPool {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(DbPool(aero.try_get().unwrap_or_default()))
+        }
+    }
+
+    #[test]
+    fn link() {
+        let host = Aero::new().with(5u32);
+        let plugin = Aero::new().with("plugin");
+        plugin.link::<DbPool, _>(&host);
+        assert!(!plugin.has::<DbPool>());
+
+        // Constructed within the host, using the host's resources.
+        assert_eq!(plugin.obtain::<DbPool>().0, 5);
+        assert!(host.has::<DbPool>());
+        assert_eq!(host.construction_count::<DbPool>(), 1);
+        assert_eq!(plugin.construction_count::<DbPool>(), 0);
+
+        host.replace(DbPool(7));
+        assert_eq!(plugin.try_get::<DbPool>().unwrap().0, 7);
+
+        // Nothing else is shared.
+        assert!(!plugin.has::<u32>());
+        assert!(!host.has::<&str>());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot link")]
+    fn link_cycle() {
+        let a = Aero::new();
+        let b = Aero::new();
+        a.link::<DbPool, _>(&b);
+        b.link::<DbPool, _>(&a);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot link")]
+    fn link_to_scope() {
+        let state = Aero::new();
+        state.link::<DbPool, _>(&state.scope());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn link_async() {
+        let host = Aero::new().with(3u32);
+        let plugin = Aero::new();
+        plugin.link::<DbPool, _>(&host);
+        assert_eq!(plugin.obtain_async::<DbPool>().await.0, 3);
+        assert!(host.has::<DbPool>());
+    }
+}
@@ -0,0 +1,346 @@
+//! Single-threaded variant of `Aero` for resources which are not `Send` or
+//! `Sync`. Requires feature `local`.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    rc::Rc,
+    thread,
+};
+
+use frunk::{hlist::Plucker, prelude::HList, HCons, HNil};
+
+use crate::{
+    resource::{
+        cyclic_resource, duplicate_resource, missing_resource_among, unwrap_constructed,
+        ObtainError,
+    },
+    slot::Slot,
+    slot_map::{debug_slots, ErasedSlot, SlotEntry, SlotMap},
+};
+
+/// Bound on the types that can be used as a `LocalAero` resource. Unlike
+/// `Resource`, this does not require `Send` or `Sync`.
+pub trait LocalResource: Any + Clone {}
+impl<T: Any + Clone> LocalResource for T {}
+
+/// A compile-time list of resource types which are statically guaranteed to be
+/// present in a `LocalAero`.
+pub trait LocalResourceList: HList + Any {}
+impl LocalResourceList for HNil {}
+impl<H: LocalResource, T: LocalResourceList> LocalResourceList for HCons<H, T> {}
+
+/// Implemented for values which can be constructed from other resources in a
+/// `LocalAero`.
+pub trait LocalConstructible: LocalResource {
+    /// Error type for when resource fails to be constructed.
+    type Error: Into<anyhow::Error>;
+    /// Construct the resource with the provided application state.
+    fn construct(aero: &LocalAero) -> Result<Self, Self::Error>;
+}
+
+/// Removes the placeholder for `T` if dropped without being filled.
+struct LocalPlaceholderGuard<'a, T: LocalResource> {
+    aero: &'a LocalAero,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: LocalResource> LocalPlaceholderGuard<'_, T> {
+    fn fill(self, value: T) {
+        self.aero.inner.borrow_mut().insert(Slot::Filled(value));
+        std::mem::forget(self);
+    }
+}
+
+impl<T: LocalResource> Drop for LocalPlaceholderGuard<'_, T> {
+    fn drop(&mut self) {
+        self.aero.inner.borrow_mut().remove::<T>();
+    }
+}
+
+/// Stores a collection of resources keyed on resource type, for use from a
+/// single thread. Since only one thread can access the collection, there is
+/// never any need to wait for another thread to finish constructing a
+/// resource. Can be cheaply cloned.
+///
+/// Like `Aero`, the type parameter lists resources which are statically known
+/// to be present, and so can be accessed infallibly with `get()`.
+///
+/// Resources are stored in the same slot map as `Aero`, but only the core API is
+/// supported: there are no cached construction failures, lifetimes, aliases,
+/// decorators, hooks, events, async construction or sealing.
+#[repr(transparent)]
+pub struct LocalAero<R: LocalResourceList = HNil> {
+    inner: Rc<RefCell<SlotMap<dyn ErasedSlot>>>,
+    phantom: PhantomData<Rc<R>>,
+}
+
+impl LocalAero {
+    /// Construct a new instance of the type with no initial resources.
+    pub fn new() -> Self {
+        Self {
+            inner: Default::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl Default for LocalAero {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: LocalResourceList> Clone for LocalAero<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Lists the type of each resource along with whether it is present or still
+/// under construction. Resource values are never printed, as they may contain
+/// secrets.
+impl<R: LocalResourceList> Debug for LocalAero<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = self.inner.borrow();
+        let slots = guard.iter().map(|slot| (slot.type_name(), slot.state()));
+        debug_slots(f, "LocalAero", slots)
+    }
+}
+
+impl<R: LocalResourceList> LocalAero<R> {
+    fn with_slot<T: LocalResource, U>(&self, f: impl FnOnce(Option<&Slot<T>>) -> U) -> U {
+        f(self.inner.borrow().get::<T>())
+    }
+
+    /// Directly insert a resource into the collection. Panics if a resource of the
+    /// same type already exists.
+    pub fn insert<T: LocalResource>(&self, value: T) {
+        let mut guard = self.inner.borrow_mut();
+        match guard.entry::<T>() {
+            SlotEntry::Occupied(_) => {
+                drop(guard);
+                duplicate_resource::<T>()
+            }
+            SlotEntry::Vacant(vacant) => vacant.insert(Slot::Filled(value)),
+        }
+    }
+
+    /// Builder method equivalent to calling `insert()` but can be chained.
+    pub fn with<T: LocalResource>(self, value: T) -> LocalAero<HCons<T, R>> {
+        self.insert(value);
+        LocalAero {
+            inner: self.inner,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Assert that a resource exists, returns `self` unchanged if not
+    pub fn try_assert<T: LocalResource>(self) -> Result<LocalAero<HCons<T, R>>, Self> {
+        if self.has::<T>() {
+            Ok(LocalAero {
+                inner: self.inner,
+                phantom: PhantomData,
+            })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Assert that a resource exists, panic if not
+    pub fn assert<T: LocalResource>(self) -> LocalAero<HCons<T, R>> {
+        self.try_assert()
+            .unwrap_or_else(|this| this.missing_resource::<T>())
+    }
+
+    /// Borrow as a `LocalAero` without any required resources, eg. to pass to
+    /// a constructor.
+    pub fn as_erased(&self) -> &LocalAero {
+        // Safety: all LocalAero variants are `#[repr(transparent)]` wrappers
+        // around the same concrete type.
+        unsafe { std::mem::transmute(self) }
+    }
+
+    /// Check if a resource with a specific type is fully constructed in this
+    /// instance.
+    pub fn has<T: LocalResource>(&self) -> bool {
+        self.with_slot::<T, _>(|slot| matches!(slot, Some(Slot::Filled(_))))
+    }
+
+    /// Tries to get an instance of `T`. Returns `None` if there is no such instance.
+    /// This function does not attempt to construct `T` if it does not exist.
+    pub fn try_get<T: LocalResource>(&self) -> Option<T> {
+        self.with_slot::<T, _>(|slot| match slot? {
+            Slot::Filled(x) => Some(x.clone()),
+            Slot::Placeholder { .. } | Slot::Failed(_) => None,
+        })
+    }
+
+    /// Get an instance of `T` which is statically known to be present.
+    pub fn get<T: LocalResource, I>(&self) -> T
+    where
+        R: Plucker<T, I>,
+    {
+        self.try_get()
+            .unwrap_or_else(|| self.missing_resource::<T>())
+    }
+
+    /// Try to get or construct an instance of `T`.
+    pub fn try_obtain<T: LocalConstructible>(&self) -> Result<T, ObtainError> {
+        let mut guard = self.inner.borrow_mut();
+        match guard.entry::<T>() {
+            SlotEntry::Occupied(Slot::Filled(x)) => return Ok(x.clone()),
+            // With a single thread, the only way to encounter a placeholder
+            // is for `T` to depend on itself.
+            SlotEntry::Occupied(Slot::Placeholder { .. }) => {
+                drop(guard);
+                cyclic_resource::<T>()
+            }
+            SlotEntry::Occupied(Slot::Failed(e)) => return Err(ObtainError::Failed(e.clone())),
+            SlotEntry::Vacant(vacant) => {
+                vacant.insert(Slot::placeholder(thread::current().into()));
+            }
+        }
+        drop(guard);
+        let aero = self.as_erased();
+        let guard = LocalPlaceholderGuard {
+            aero,
+            phantom: PhantomData,
+        };
+        let x = T::construct(aero).map_err(ObtainError::from_construct_error)?;
+        guard.fill(x.clone());
+        Ok(x)
+    }
+
+    /// Get or construct an instance of `T`. Panics if unable.
+    pub fn obtain<T: LocalConstructible>(&self) -> T {
+        unwrap_constructed::<T, _>(self.try_obtain::<T>())
+    }
+
+    fn missing_resource<T: LocalResource>(&self) -> ! {
+        let names = self
+            .inner
+            .borrow()
+            .iter()
+            .map(|slot| slot.type_name())
+            .collect();
+        missing_resource_among::<T>(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, convert::Infallible, rc::Rc};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Counter(Rc<Cell<i32>>);
+
+    impl LocalConstructible for Counter {
+        type Error = Infallible;
+
+        fn construct(_aero: &LocalAero) -> Result<Self, Self::Error> {
+            Ok(Self(Rc::new(Cell::new(0))))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct View(Counter);
+
+    impl LocalConstructible for View {
+        type Error = Infallible;
+
+        fn construct(aero: &LocalAero) -> Result<Self, Self::Error> {
+            Ok(Self(aero.obtain()))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Failing;
+
+    impl LocalConstructible for Failing {
+        type Error = anyhow::Error;
+
+        fn construct(_aero: &LocalAero) -> Result<Self, Self::Error> {
+            Err(anyhow::anyhow!("Failed to construct"))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Cyclic;
+
+    impl LocalConstructible for Cyclic {
+        type Error = Infallible;
+
+        fn construct(aero: &LocalAero) -> Result<Self, Self::Error> {
+            Ok(aero.obtain())
+        }
+    }
+
+    #[test]
+    fn insert() {
+        let aero = LocalAero::new().with(Rc::new(42));
+        assert_eq!(*aero.try_get::<Rc<i32>>().unwrap(), 42);
+        assert!(aero.try_get::<Rc<u32>>().is_none());
+    }
+
+    #[test]
+    fn get_required() {
+        let aero = LocalAero::new().with(Rc::new(42));
+        assert_eq!(*aero.get::<Rc<i32>, _>(), 42);
+        let aero = aero.as_erased().clone().assert::<Rc<i32>>();
+        assert_eq!(*aero.get::<Rc<i32>, _>(), 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "Resource `alloc::rc::Rc<u32>` does not exist")]
+    fn assert_missing() {
+        LocalAero::new().with(Rc::new(42)).assert::<Rc<u32>>();
+    }
+
+    #[test]
+    fn debug_redacted() {
+        let aero = LocalAero::new().with(Rc::new("secret"));
+        assert_eq!(
+            format!("{:?}", aero),
+            "LocalAero { alloc::rc::Rc<&str>: Present }"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate resource")]
+    fn duplicate() {
+        let _ = LocalAero::new().with(Rc::new(1)).with(Rc::new(2));
+    }
+
+    #[test]
+    fn obtain() {
+        let aero = LocalAero::new();
+        let view: View = aero.obtain();
+        view.0 .0.set(5);
+        assert!(aero.has::<Counter>());
+        assert_eq!(aero.obtain::<Counter>().0.get(), 5);
+    }
+
+    #[test]
+    fn obtain_failed() {
+        let aero = LocalAero::new();
+        assert!(matches!(
+            aero.try_obtain::<Failing>(),
+            Err(ObtainError::Failed(_))
+        ));
+        assert!(!aero.has::<Failing>());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cycle detected")]
+    fn obtain_cyclic() {
+        LocalAero::new().obtain::<Cyclic>();
+    }
+}
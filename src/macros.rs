@@ -12,3 +12,319 @@ macro_rules! Aero {
         $crate::Aero<$crate::frunk::HList![$($tok)*]>
     };
 }
+
+/// Implement `Constructible` for several instantiations of a generic resource
+/// type at once. The body is used for every instantiation, so should refer to
+/// the resource type as `Self`.
+///
+/// Example usage:
+/// ```rust
+/// use std::{convert::Infallible, marker::PhantomData, sync::Arc};
+///
+/// use aerosol::{impl_constructible_for, Aero, Constructible};
+///
+/// # #[derive(Clone)]
+/// # struct User;
+/// # #[derive(Clone)]
+/// # struct Product;
+/// # #[derive(Clone)]
+/// # struct Order;
+/// #[derive(Clone)]
+/// struct DbPool;
+///
+/// impl Constructible for DbPool {
+///     type Error = Infallible;
+///     fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+///         Ok(DbPool)
+///     }
+/// }
+///
+/// #[derive(Clone)]
+/// struct Repository<T> {
+///     pool: DbPool,
+///     phantom: PhantomData<Arc<T>>,
+/// }
+///
+/// impl<T> Repository<T> {
+///     fn new(pool: DbPool) -> Self {
+///         Self { pool, phantom: PhantomData }
+///     }
+/// }
+///
+/// impl_constructible_for! {
+///     Repository<User, Product, Order> {
+///         type Error = Infallible;
+///         fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+///             Ok(Self::new(aero.obtain::<DbPool>()))
+///         }
+///     }
+/// }
+///
+/// let aero = Aero::new();
+/// aero.obtain::<Repository<User>>();
+/// aero.obtain::<Repository<Order>>();
+/// ```
+#[macro_export]
+macro_rules! impl_constructible_for {
+    ($ty:ident<$($arg:ty),+ $(,)?> $body:tt) => {
+        $(
+            impl $crate::Constructible for $ty<$arg> $body
+        )+
+    };
+}
+
+/// Check at runtime that several resources are present, and convert to an `Aero`
+/// which requires exactly those resources. Unlike chaining calls to `assert()`, every
+/// missing resource is reported in a single panic message.
+///
+/// Example usage:
+/// ```rust
+/// use aerosol::{assert_aero, Aero};
+///
+/// fn run(state: Aero![&'static str, i32]) {
+///     let greeting: &str = state.get();
+///     let count: i32 = state.get();
+/// }
+///
+/// let state = Aero::new().with("Hello, world").with(42);
+/// run(assert_aero![state => &'static str, i32]);
+/// ```
+#[macro_export]
+macro_rules! assert_aero {
+    ($state:expr => $($ty:ty),* $(,)?) => {{
+        let aero = $state;
+        let mut missing: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
+        $(
+            if !aero.has::<$ty>() {
+                missing.push(::std::format!("`{}`", ::std::any::type_name::<$ty>()));
+            }
+        )*
+        if !missing.is_empty() {
+            ::std::panic!("Required resources do not exist: {}", missing.join(", "));
+        }
+        match aero.try_into::<$crate::frunk::HList![$($ty),*]>() {
+            ::std::result::Result::Ok(aero) => aero,
+            ::std::result::Result::Err(_) => {
+                ::std::panic!("Required resources do not exist")
+            }
+        }
+    }};
+}
+
+/// Try to obtain each of several candidate resources in order, returning the first
+/// which can be obtained. Each candidate is coerced to the result type, which must be
+/// known from context, so this is usually used to pick between several implementations
+/// of a trait. If no candidate can be obtained, the error from the last one is returned.
+///
+/// Example usage:
+/// ```rust
+/// use std::{convert::Infallible, sync::Arc};
+///
+/// use aerosol::{obtain_first_of, Aero, Constructible, MissingResource, ObtainError};
+///
+/// trait Client: Send + Sync {}
+///
+/// struct PreferredClient;
+/// impl Client for PreferredClient {}
+/// impl Constructible for PreferredClient {
+///     type Error = MissingResource;
+///     fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+///         Err(MissingResource::new::<&'static str>())
+///     }
+/// }
+///
+/// struct FallbackClient;
+/// impl Client for FallbackClient {}
+/// impl Constructible for FallbackClient {
+///     type Error = Infallible;
+///     fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+///         Ok(FallbackClient)
+///     }
+/// }
+///
+/// let aero = Aero::new();
+/// let client: Result<Arc<dyn Client>, ObtainError> =
+///     obtain_first_of![aero => Arc<PreferredClient>, Arc<FallbackClient>];
+/// assert!(client.is_ok());
+/// ```
+#[macro_export]
+macro_rules! obtain_first_of {
+    ($state:expr => $($ty:ty),+ $(,)?) => {{
+        let aero = &$state;
+        'found: {
+            let mut error = ::std::option::Option::None;
+            $(
+                match aero.try_obtain::<$ty>() {
+                    ::std::result::Result::Ok(value) => {
+                        break 'found ::std::result::Result::Ok(value as _)
+                    }
+                    ::std::result::Result::Err(e) => error = ::std::option::Option::Some(e),
+                }
+            )+
+            ::std::result::Result::Err(error.unwrap())
+        }
+    }};
+}
+
+/// Declare the resources which a resource depends on, so that `Aero::validate_graph()`
+/// can check they are available without constructing anything. Place this next to
+/// the resource's `Constructible` implementation.
+///
+/// The declaration is not registered anywhere automatically: it is only checked once
+/// `Aero::declare()` or `Aero::declare_all()` has been called for the resource at
+/// startup. A resource which is never declared is silently skipped by
+/// `validate_graph()`, so keep a single `declare_all()` call listing every such
+/// resource.
+///
+/// Example usage:
+/// ```rust
+/// use aerosol::{depends_on, Aero};
+///
+/// # #[derive(Clone)]
+/// # struct DbPool;
+/// # #[derive(Clone)]
+/// # struct Cache;
+/// #[derive(Clone)]
+/// struct Repository;
+///
+/// depends_on!(Repository: DbPool, Cache);
+///
+/// let aero = Aero::new().with(DbPool).with_declared::<Repository>();
+/// assert!(aero.validate_graph().is_err());
+/// ```
+#[macro_export]
+macro_rules! depends_on {
+    ($ty:ty: $($dep:ty),* $(,)?) => {
+        impl $crate::DependsOn for $ty {
+            fn dependencies() -> ::std::vec::Vec<$crate::Dependency> {
+                ::std::vec![$($crate::Dependency::of::<$dep>()),*]
+            }
+        }
+    };
+}
+
+/// Define a factory type which builds a new instance of a resource from other
+/// resources. The arguments are obtained from the aerosol instance and passed to the
+/// closure, which returns a `Result` whose error type is `Box<dyn Error + Send + Sync>`,
+/// so that `?` can be used on most errors. The generated type has a `build()` method
+/// which builds a single instance, and a `register()` method which registers it with
+/// `Aero::register_factory()`, so that `Aero::make()` builds a fresh instance on every
+/// call.
+///
+/// Example usage:
+/// ```rust
+/// use aerosol::{factory, Aero, Constructible, MissingResource};
+///
+/// # #[derive(Clone)]
+/// # struct Config { greeting: &'static str }
+/// # impl Constructible for Config {
+/// #     type Error = MissingResource;
+/// #     fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+/// #         Err(MissingResource::new::<Self>())
+/// #     }
+/// # }
+/// struct Greeter(&'static str);
+///
+/// factory!(GreeterFactory: (Config) -> Greeter = |config| Ok(Greeter(config.greeting)));
+///
+/// let aero = Aero::new().with(Config { greeting: "Hello" });
+/// GreeterFactory::register(&aero);
+/// assert_eq!(aero.make::<Greeter>().0, "Hello");
+/// ```
+#[macro_export]
+macro_rules! factory {
+    ($vis:vis $name:ident: ($($ty:ty),* $(,)?) -> $out:ty = |$($arg:ident),* $(,)?| $body:expr) => {
+        $vis struct $name;
+
+        impl $name {
+            /// Obtain the arguments from `aero`, and build a new instance.
+            #[allow(dead_code)]
+            $vis fn build<R: $crate::ResourceList>(
+                aero: &$crate::Aero<R>,
+            ) -> ::std::result::Result<
+                $out,
+                ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>,
+            > {
+                aero.try_call(
+                    |$($arg: $ty),*| -> ::std::result::Result<
+                        $out,
+                        ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>,
+                    > { $body },
+                )?
+            }
+
+            /// Register this factory with `aero`, so that `make()` builds a new instance.
+            /// Panics from `make()` if the instance cannot be built.
+            #[allow(dead_code)]
+            $vis fn register<R: $crate::ResourceList>(aero: &$crate::Aero<R>) {
+                aero.register_factory::<$out>(|aero| match Self::build(aero) {
+                    ::std::result::Result::Ok(value) => ::std::boxed::Box::new(value),
+                    ::std::result::Result::Err(e) => ::std::panic!(
+                        "Failed to make `{}`: {}",
+                        ::std::any::type_name::<$out>(),
+                        e
+                    ),
+                });
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Aero, Constructible, MissingResource};
+
+    #[test]
+    fn assert_aero() {
+        let state = Aero::new().with(42).with("Hello");
+        let typed: Aero![i32, &'static str] = assert_aero![state => i32, &'static str];
+        let x: i32 = typed.get();
+        assert_eq!(x, 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "Required resources do not exist: `u8`, `bool`")]
+    fn assert_aero_missing() {
+        let state = Aero::new().with(42);
+        assert_aero![state => i32, u8, bool];
+    }
+
+    #[derive(Debug, Clone)]
+    struct Host(&'static str);
+
+    impl Constructible for Host {
+        type Error = MissingResource;
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Err(MissingResource::new::<Self>())
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Port(u16);
+
+    impl Constructible for Port {
+        type Error = MissingResource;
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Err(MissingResource::new::<Self>())
+        }
+    }
+
+    struct Address(String);
+
+    factory!(AddressFactory: (Host, Port) -> Address = |host, port| {
+        if port.0 == 0 {
+            return Err("Invalid port".into());
+        }
+        Ok(Address(format!("{}:{}", host.0, port.0)))
+    });
+
+    #[test]
+    fn factory() {
+        let state = Aero::new().with(Host("localhost")).with(Port(8080));
+        AddressFactory::register(&state);
+        assert_eq!(state.make::<Address>().0, "localhost:8080");
+        let invalid = Aero::new().with(Host("localhost")).with(Port(0));
+        assert!(AddressFactory::build(&invalid).is_err());
+        assert!(AddressFactory::build(&Aero::new()).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::any::TypeId;
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+/// Soft constraint on when a resource is constructed by eager builders such as
+/// `construct_remaining()`. Set with `Aero::register_ordering_hint()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderingHint {
+    /// Construct before resources without a hint, eg. for a logger or metrics exporter
+    /// which should be ready before anything else starts up.
+    First,
+    /// Construct after resources without a hint.
+    Last,
+}
+
+/// How urgently a resource should be constructed by concurrent builders such as
+/// `construct_remaining_concurrently_async()`, when more resources are waiting to be
+/// constructed than the concurrency limit allows. Set with `Aero::register_priority()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background resources which are not needed for the application to become ready.
+    Low,
+    /// The priority of resources which have not been given one.
+    #[default]
+    Normal,
+    /// Resources on the critical path to readiness, eg. the dependencies of an HTTP
+    /// listener.
+    High,
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Set the priority with which `T` is scheduled by concurrent builders. Resources
+    /// with a higher priority are started first, and resources with the same priority
+    /// keep their relative order. Priorities only decide which resources start first,
+    /// so a low priority resource may still be constructed early if a higher priority
+    /// resource depends on it.
+    pub fn register_priority<T: Resource>(&self, priority: Priority) {
+        self.inner
+            .write()
+            .priorities
+            .insert(TypeId::of::<T>(), priority);
+    }
+
+    /// Stable sort `items` from highest to lowest priority.
+    #[cfg(feature = "async")]
+    pub(crate) fn sort_by_priority<I>(&self, items: &mut [I], id: impl Fn(&I) -> TypeId) {
+        let guard = self.inner.read();
+        items.sort_by_key(|item| {
+            std::cmp::Reverse(guard.priorities.get(&id(item)).copied().unwrap_or_default())
+        });
+    }
+
+    /// Bias when `T` is constructed by `construct_remaining()`,
+    /// `construct_remaining_async()` and `construct_in_dependency_order()`. Unlike declared dependencies, hints are
+    /// only used to order resources which are otherwise unconstrained: a resource
+    /// constructed first may still cause its own dependencies to be constructed before
+    /// it. Resources with the same hint keep their relative order.
+    pub fn register_ordering_hint<T: Resource>(&self, hint: OrderingHint) {
+        self.inner
+            .write()
+            .ordering_hints
+            .insert(TypeId::of::<T>(), hint);
+    }
+
+    /// Stable sort `items` according to their ordering hints.
+    pub(crate) fn sort_by_ordering_hints<I>(&self, items: &mut [I], id: impl Fn(&I) -> TypeId) {
+        let guard = self.inner.read();
+        items.sort_by_key(|item| match guard.ordering_hints.get(&id(item)) {
+            Some(OrderingHint::First) => 0,
+            None => 1,
+            Some(OrderingHint::Last) => 2,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::{test_resource, ConstructionLog},
+        Aero,
+    };
+
+    use super::OrderingHint;
+    #[cfg(feature = "async")]
+    use super::Priority;
+
+    test_resource!(Logger, Database, Cache);
+
+    #[test]
+    fn construct_remaining_with_hints() {
+        let log = ConstructionLog::default();
+        let state = Aero::new().with(log.clone());
+        state.register_ordering_hint::<Database>(OrderingHint::Last);
+        state.register_ordering_hint::<Logger>(OrderingHint::First);
+        let _state: Aero![ConstructionLog, Database, Cache, Logger] = state.construct_remaining();
+        assert_eq!(*log.lock(), ["Logger", "Cache", "Database"]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn construct_remaining_async_with_hints() {
+        let log = ConstructionLog::default();
+        let state = Aero::new().with(log.clone());
+        state.register_ordering_hint::<Database>(OrderingHint::Last);
+        state.register_ordering_hint::<Logger>(OrderingHint::First);
+        let _state: Aero![ConstructionLog, Database, Cache, Logger] =
+            state.construct_remaining_async().await;
+        assert_eq!(*log.lock(), ["Logger", "Cache", "Database"]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn construct_remaining_concurrently_with_priority() {
+        let log = ConstructionLog::default();
+        let state = Aero::new().with(log.clone());
+        state.register_priority::<Cache>(Priority::Low);
+        state.register_priority::<Database>(Priority::High);
+        let _state: Aero![ConstructionLog, Cache, Logger, Database] =
+            state.construct_remaining_concurrently_async(1).await;
+        assert_eq!(*log.lock(), ["Database", "Logger", "Cache"]);
+    }
+}
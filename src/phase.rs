@@ -0,0 +1,168 @@
+use std::{any::type_name, sync::atomic::Ordering};
+
+use crate::{
+    resource::{ObtainError, Resource, ResourceList},
+    state::{Aero, InnerAero},
+};
+
+/// Limit on the number of constructions recorded by `Aero::runtime_constructions()`,
+/// so that transient resources constructed on every request cannot grow it forever.
+const MAX_RUNTIME_CONSTRUCTIONS: usize = 256;
+
+/// Stage of the application's lifecycle, used to flag resources which are constructed
+/// lazily once the application is expected to be fully initialized. Set with
+/// `Aero::set_phase()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Resources are expected to be constructed, eg. whilst the application starts up.
+    #[default]
+    Startup,
+    /// Every resource is expected to have been constructed already, eg. whilst handling
+    /// requests. Resources can still be constructed, but each construction is reported.
+    Runtime,
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Set the lifecycle phase of this aerosol instance. Whilst in `Phase::Runtime`,
+    /// each resource constructed is logged as a warning (requires feature `tracing`)
+    /// and recorded in `runtime_constructions()`, since lazy construction whilst
+    /// handling requests is often a source of unexpected latency. If
+    /// `with_strict_phase()` has been called, construction fails instead. Unlike
+    /// `seal()`, construction is otherwise still permitted.
+    pub fn set_phase(&self, phase: Phase) {
+        self.inner.write().phase = phase;
+    }
+
+    /// Make constructing a resource whilst in `Phase::Runtime` fail with
+    /// `ObtainError::RuntimePhase`, rather than only reporting it. Resources which
+    /// already exist can still be obtained. Useful for checking that every resource is
+    /// constructed during startup.
+    pub fn with_strict_phase(self) -> Self {
+        self.inner.read().strict_phase.store(true, Ordering::SeqCst);
+        self
+    }
+
+    /// The lifecycle phase set with `set_phase()`.
+    pub fn phase(&self) -> Phase {
+        self.inner.read().phase
+    }
+
+    /// Names of the resources constructed whilst in `Phase::Runtime`, in the order
+    /// their construction began. Resources constructed repeatedly, eg. because they
+    /// are transient, appear once per construction. Only the first 256 constructions
+    /// are kept.
+    pub fn runtime_constructions(&self) -> Vec<&'static str> {
+        self.inner.read().runtime_constructions.clone()
+    }
+}
+
+impl InnerAero {
+    /// Report that `T` is about to be constructed, if that is unexpected in the current
+    /// phase. Fails if construction in `Phase::Runtime` has been made strict.
+    pub(crate) fn check_phase<T: Resource>(&mut self) -> Result<(), ObtainError> {
+        if self.phase != Phase::Runtime {
+            return Ok(());
+        }
+        if self.strict_phase.load(Ordering::SeqCst) {
+            return Err(ObtainError::RuntimePhase(type_name::<T>()));
+        }
+        if self.runtime_constructions.len() < MAX_RUNTIME_CONSTRUCTIONS {
+            self.runtime_constructions.push(type_name::<T>());
+        }
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            resource = type_name::<T>(),
+            "resource constructed during the runtime phase"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{Aero, Constructible, ObtainError};
+
+    use super::Phase;
+
+    #[derive(Debug, Clone)]
+    struct Pool;
+
+    impl Constructible for Pool {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Pool)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Client;
+
+    impl Constructible for Client {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.obtain::<Pool>();
+            Ok(Client)
+        }
+    }
+
+    #[test]
+    fn runtime_constructions() {
+        let state = Aero::new();
+        assert_eq!(state.phase(), Phase::Startup);
+        state.obtain::<Pool>();
+        state.set_phase(Phase::Runtime);
+        state.obtain::<Pool>();
+        assert!(state.runtime_constructions().is_empty());
+        state.obtain::<Client>();
+        state.obtain::<Client>();
+        assert_eq!(
+            state.runtime_constructions(),
+            [std::any::type_name::<Client>()]
+        );
+    }
+
+    #[test]
+    fn runtime_construction_strict() {
+        let state = Aero::new().with_strict_phase();
+        state.obtain::<Pool>();
+        state.set_phase(Phase::Runtime);
+        state.obtain::<Pool>();
+        assert!(matches!(
+            state.try_obtain::<Client>(),
+            Err(ObtainError::RuntimePhase(name)) if name == std::any::type_name::<Client>()
+        ));
+        assert!(!state.has::<Client>());
+        assert_eq!(state.construction_count::<Client>(), 0);
+        assert!(state.runtime_constructions().is_empty());
+    }
+
+    #[test]
+    fn runtime_construction_strict_checks() {
+        // Strict checks do not make construction during the runtime phase fail.
+        let state = Aero::new().with_strict_checks();
+        state.set_phase(Phase::Runtime);
+        state.obtain::<Client>();
+        assert_eq!(
+            state.runtime_constructions(),
+            [
+                std::any::type_name::<Client>(),
+                std::any::type_name::<Pool>()
+            ]
+        );
+    }
+
+    #[test]
+    fn runtime_constructions_capped() {
+        let state = Aero::new();
+        state.register::<Pool>(crate::Lifetime::Transient);
+        state.set_phase(Phase::Runtime);
+        for _ in 0..300 {
+            state.obtain::<Pool>();
+        }
+        assert_eq!(state.runtime_constructions().len(), 256);
+    }
+}
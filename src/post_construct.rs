@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+/// Coercions registered for a single resource type, each of which passes the resource
+/// to the hooks registered for one trait.
+pub(crate) type Coercions<T> = Vec<Arc<dyn Fn(&T, &Aero) + Send + Sync>>;
+
+/// Coercion from the resource `T` to `Arc<Tr>`, used by `obtain_as()`.
+pub(crate) type Coercion<T, Tr> = Arc<dyn Fn(T) -> Arc<Tr> + Send + Sync>;
+
+/// Hooks registered for a single trait, in registration order.
+pub(crate) type PostConstructHooks<Tr> = Vec<Arc<dyn Fn(&Arc<Tr>, &Aero) + Send + Sync>>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Register a hook which is called with every newly constructed resource that
+    /// can be coerced to `Arc<Tr>`, eg. to add each `dyn Component` to a registry.
+    /// Since Rust can't discover these coercions automatically, each resource type
+    /// must also be registered with `register_coercion()`. Hooks are called after
+    /// the resource has been stored, in the order they were registered. Instances
+    /// inserted directly are not passed to hooks.
+    pub fn register_post_construct_for<Tr: ?Sized + Send + Sync + 'static>(
+        &self,
+        f: impl Fn(&Arc<Tr>, &Aero) + Send + Sync + 'static,
+    ) {
+        self.inner
+            .write()
+            .post_construct_hooks
+            .entry::<PostConstructHooks<Tr>>()
+            .or_default()
+            .push(Arc::new(f));
+    }
+
+    /// Register how to coerce the resource `T` to `Arc<Tr>`, so that hooks registered
+    /// with `register_post_construct_for::<Tr>()` are called whenever `T` is
+    /// constructed, eg. `aero.register_coercion::<Login, dyn Component>(|x| Arc::new(x))`.
+    /// Coercions from `Arc<C>` are also used by `obtain_as::<C, Tr>()`, in which case
+    /// the coercion is usually just `|x| x`.
+    pub fn register_coercion<T: Resource, Tr: ?Sized + Send + Sync + 'static>(
+        &self,
+        coerce: impl Fn(T) -> Arc<Tr> + Send + Sync + 'static,
+    ) {
+        let coerce: Coercion<T, Tr> = Arc::new(coerce);
+        let mut guard = self.inner.write();
+        guard.coercions.insert(coerce.clone());
+        guard
+            .coercions
+            .entry::<Coercions<T>>()
+            .or_default()
+            .push(Arc::new(move |value: &T, aero: &Aero| {
+                // Only coerce if there is something to pass the result to.
+                let hooks = match aero
+                    .inner
+                    .read()
+                    .post_construct_hooks
+                    .get::<PostConstructHooks<Tr>>()
+                {
+                    Some(hooks) => hooks.clone(),
+                    None => return,
+                };
+                let value = coerce(value.clone());
+                for hook in &hooks {
+                    hook(&value, aero);
+                }
+            }));
+    }
+
+    /// The coercion from `T` to `Arc<Tr>` registered with `register_coercion()`, if any.
+    pub(crate) fn coercion<T: Resource, Tr: ?Sized + Send + Sync + 'static>(
+        &self,
+    ) -> Option<Coercion<T, Tr>> {
+        self.inner
+            .read()
+            .coercions
+            .get::<Coercion<T, Tr>>()
+            .cloned()
+    }
+
+    /// Call the hooks for every trait which a newly constructed `T` can be coerced to.
+    pub(crate) fn run_post_construct<T: Resource>(&self, value: &T) {
+        // Don't hold the lock whilst calling the hooks, as they may access other
+        // resources.
+        let coercions = match self.inner.read().coercions.get::<Coercions<T>>() {
+            Some(coercions) => coercions.clone(),
+            None => return,
+        };
+        for coercion in &coercions {
+            coercion(value, self.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{Aero, Constructible};
+
+    trait Component: Send + Sync {
+        fn name(&self) -> &'static str;
+    }
+
+    #[derive(Clone)]
+    struct Database;
+
+    impl Component for Database {
+        fn name(&self) -> &'static str {
+            "database"
+        }
+    }
+
+    impl Constructible for Database {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Database)
+        }
+    }
+
+    #[derive(Clone)]
+    struct Cache;
+
+    impl Component for Cache {
+        fn name(&self) -> &'static str {
+            "cache"
+        }
+    }
+
+    impl Constructible for Cache {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.obtain::<Database>();
+            Ok(Cache)
+        }
+    }
+
+    #[test]
+    fn post_construct_for_trait() {
+        let registry = Arc::new(Mutex::new(Vec::new()));
+        let state = Aero::new();
+        state.register_coercion::<Database, dyn Component>(|x| Arc::new(x));
+        state.register_coercion::<Cache, dyn Component>(|x| Arc::new(x));
+        state.register_post_construct_for::<dyn Component>({
+            let registry = registry.clone();
+            move |component, aero| {
+                // The resource is already stored when hooks are called.
+                assert!(aero.has::<Database>());
+                registry.lock().unwrap().push(component.name());
+            }
+        });
+        state.obtain::<Cache>();
+        state.obtain::<Cache>();
+        assert_eq!(*registry.lock().unwrap(), ["database", "cache"]);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{any::type_name, sync::Arc};
+
+use crate::{
+    resource::{MissingResource, ObtainError, ResourceList},
+    state::Aero,
+    sync_constructible::ConstructibleResource,
+};
+
+/// Produces an implementation of the interface `T`, constructing it if necessary.
+type Implementation<T> = Arc<dyn Fn(&Aero) -> Result<Arc<T>, ObtainError> + Send + Sync>;
+
+/// Implementations registered for a single interface type.
+pub(crate) type Implementations<T> = Vec<Implementation<T>>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Register `value` as the implementation of the interface `T`, which is usually a
+    /// trait object, eg. `aero.register_primary::<dyn Mailer>(Arc::new(SmtpMailer))`.
+    /// Exactly one implementation must be registered for `primary()` to succeed.
+    pub fn register_primary<T: ?Sized + Send + Sync + 'static>(&self, value: Arc<T>) {
+        self.push_implementation::<T>(Arc::new(move |_| Ok(value.clone())));
+    }
+
+    /// Register the resource `C` as the implementation of the interface `T`, eg.
+    /// `aero.register_primary_constructed::<dyn Mailer, Arc<SmtpMailer>>(|x| x)`.
+    /// Unlike `register_primary()`, `C` is not constructed until `primary()` is first
+    /// called, and is then obtained like any other resource. `coerce` converts `C` to
+    /// the interface, and is usually just `|x| x`.
+    pub fn register_primary_constructed<
+        T: ?Sized + Send + Sync + 'static,
+        C: ConstructibleResource,
+    >(
+        &self,
+        coerce: impl Fn(C) -> Arc<T> + Send + Sync + 'static,
+    ) {
+        self.push_implementation::<T>(Arc::new(move |aero| aero.try_obtain::<C>().map(&coerce)));
+    }
+
+    fn push_implementation<T: ?Sized + Send + Sync + 'static>(&self, f: Implementation<T>) {
+        self.inner
+            .write()
+            .primaries
+            .entry::<Implementations<T>>()
+            .or_default()
+            .push(f);
+    }
+
+    /// Get the implementation of the interface `T` registered with `register_primary()`
+    /// or `register_primary_constructed()`. Returns `ObtainError::Missing` if no
+    /// implementation was registered, and `ObtainError::Ambiguous` if more than one was.
+    pub fn try_primary<T: ?Sized + Send + Sync + 'static>(&self) -> Result<Arc<T>, ObtainError> {
+        let implementation = match self
+            .inner
+            .read()
+            .primaries
+            .get::<Implementations<T>>()
+            .map(Vec::as_slice)
+        {
+            Some([implementation]) => implementation.clone(),
+            Some([_, _, ..]) => return Err(ObtainError::Ambiguous(type_name::<T>())),
+            Some([]) | None => return Err(ObtainError::Missing(MissingResource::new::<Arc<T>>())),
+        };
+        // Called without holding the lock, as it may construct the implementation.
+        implementation(self.as_ref())
+    }
+
+    /// Get the implementation of the interface `T` registered with `register_primary()`.
+    /// Panics unless exactly one implementation was registered.
+    pub fn primary<T: ?Sized + Send + Sync + 'static>(&self) -> Arc<T> {
+        self.try_primary().unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, sync::Arc};
+
+    use crate::{Aero, Constructible, ObtainError};
+
+    trait Mailer: Send + Sync {
+        fn name(&self) -> &'static str;
+    }
+
+    struct Smtp;
+
+    impl Mailer for Smtp {
+        fn name(&self) -> &'static str {
+            "smtp"
+        }
+    }
+
+    struct Postmark;
+
+    impl Mailer for Postmark {
+        fn name(&self) -> &'static str {
+            "postmark"
+        }
+    }
+
+    #[derive(Clone)]
+    struct Sendgrid;
+
+    impl Mailer for Sendgrid {
+        fn name(&self) -> &'static str {
+            "sendgrid"
+        }
+    }
+
+    impl Constructible for Sendgrid {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Sendgrid)
+        }
+    }
+
+    #[test]
+    fn primary_constructed() {
+        let state = Aero::new();
+        state.register_primary_constructed::<dyn Mailer, Arc<Sendgrid>>(|x| x);
+        assert!(!state.has::<Arc<Sendgrid>>());
+        assert_eq!(state.primary::<dyn Mailer>().name(), "sendgrid");
+        assert_eq!(state.primary::<dyn Mailer>().name(), "sendgrid");
+        assert_eq!(state.construction_count::<Arc<Sendgrid>>(), 1);
+        state.register_primary::<dyn Mailer>(Arc::new(Smtp));
+        assert!(matches!(
+            state.try_primary::<dyn Mailer>(),
+            Err(ObtainError::Ambiguous(_))
+        ));
+    }
+
+    #[test]
+    fn primary() {
+        let state = Aero::new();
+        assert!(matches!(
+            state.try_primary::<dyn Mailer>(),
+            Err(ObtainError::Missing(_))
+        ));
+        state.register_primary::<dyn Mailer>(Arc::new(Smtp));
+        assert_eq!(state.primary::<dyn Mailer>().name(), "smtp");
+        state.register_primary::<dyn Mailer>(Arc::new(Postmark));
+        assert!(matches!(
+            state.try_primary::<dyn Mailer>(),
+            Err(ObtainError::Ambiguous(_))
+        ));
+    }
+}
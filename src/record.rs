@@ -0,0 +1,130 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use parking_lot::Mutex;
+
+use crate::{
+    resource::ResourceList,
+    state::{Aero, InnerAero},
+};
+
+/// Events captured by a `Recorder`, in the order they occurred.
+pub(crate) type RecordLog = Mutex<Vec<RecordedEvent>>;
+
+/// An event captured by a `Recorder`. Each event contains the name of the resource type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordedEvent {
+    /// A resource was requested with one of the `obtain` methods.
+    Obtain(&'static str),
+    /// Construction of a resource was started.
+    Construct(&'static str),
+}
+
+/// Records the resources obtained and constructed in an aerosol instance, in order.
+/// Returned from `Aero::record()`. Recording stops when the recorder is dropped.
+#[derive(Debug)]
+pub struct Recorder {
+    log: Arc<RecordLog>,
+}
+
+impl Recorder {
+    /// The events recorded so far, in the order they occurred.
+    pub fn log(&self) -> Vec<RecordedEvent> {
+        self.log.lock().clone()
+    }
+    /// Take the events recorded so far, leaving the log empty.
+    pub fn take(&self) -> Vec<RecordedEvent> {
+        std::mem::take(&mut self.log.lock())
+    }
+}
+
+impl InnerAero {
+    /// Append `event` to the log of every live recorder.
+    pub(crate) fn record(&self, event: RecordedEvent) {
+        if !self.recording.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut recorders = self.recorders.lock();
+        recorders.retain(|log| match log.upgrade() {
+            Some(log) => {
+                log.lock().push(event);
+                true
+            }
+            None => false,
+        });
+        if recorders.is_empty() {
+            self.recording.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Start recording the sequence of resources obtained and constructed in this
+    /// aerosol instance. Unlike `construction_count()`, the recorder keeps the order of
+    /// events, so tests can assert that an operation obtains and constructs exactly the
+    /// expected resources, eg. by comparing the log against a snapshot.
+    ///
+    /// Resources obtained from a parent or linked instance are recorded here, but their
+    /// construction is only recorded by the instance which constructs them.
+    pub fn record(&self) -> Recorder {
+        let log = Arc::new(RecordLog::default());
+        let guard = self.inner.read();
+        guard.recorders.lock().push(Arc::downgrade(&log));
+        guard.recording.store(true, Ordering::Relaxed);
+        Recorder { log }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{any::type_name, convert::Infallible};
+
+    use crate::{Aero, Constructible};
+
+    use super::RecordedEvent::{Construct, Obtain};
+
+    #[derive(Debug, Clone)]
+    struct Pool;
+
+    impl Constructible for Pool {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Pool)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Repo;
+
+    impl Constructible for Repo {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.obtain::<Pool>();
+            Ok(Repo)
+        }
+    }
+
+    #[test]
+    fn record() {
+        let state = Aero::new();
+        let recorder = state.record();
+        state.obtain::<Repo>();
+        state.obtain::<Repo>();
+        assert_eq!(
+            recorder.take(),
+            [
+                Obtain(type_name::<Repo>()),
+                Construct(type_name::<Repo>()),
+                Obtain(type_name::<Pool>()),
+                Construct(type_name::<Pool>()),
+                Obtain(type_name::<Repo>()),
+            ]
+        );
+
+        drop(recorder);
+        state.obtain::<Pool>();
+        let recorder = state.record();
+        assert!(recorder.log().is_empty());
+    }
+}
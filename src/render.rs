@@ -0,0 +1,68 @@
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
+};
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+/// Renders a type-erased resource as a string, see `Aero::register_renderer()`.
+pub(crate) type Renderer = Arc<dyn Fn(&dyn Any) -> Option<String> + Send + Sync>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Register a function which renders the resource `T` as a human-readable string,
+    /// for display by debugging or admin tools via `render()`. Resources may contain
+    /// secrets, so renderers should take care to omit them. Replaces any previous
+    /// renderer for `T`.
+    pub fn register_renderer<T: Resource>(&self, f: impl Fn(&T) -> String + Send + Sync + 'static) {
+        let renderer: Renderer = Arc::new(move |value| value.downcast_ref().map(&f));
+        self.inner
+            .write()
+            .renderers
+            .insert(TypeId::of::<T>(), renderer);
+    }
+
+    /// Render the resource with the given type ID using the renderer registered with
+    /// `register_renderer()`. Returns `None` if the resource is not fully constructed,
+    /// or if no renderer is registered for it.
+    pub fn render(&self, id: TypeId) -> Option<String> {
+        let renderer = self.inner.read().renderers.get(&id)?.clone();
+        // Don't hold the lock whilst rendering, as the renderer may access other resources.
+        renderer(&*self.get_any(id)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use crate::Aero;
+
+    #[derive(Debug, Clone)]
+    struct Config {
+        url: &'static str,
+        #[allow(dead_code)]
+        password: &'static str,
+    }
+
+    #[test]
+    fn render() {
+        let state = Aero::new().with(Config {
+            url: "localhost",
+            password: "hunter2",
+        });
+        state.register_renderer::<Config>(|config| format!("Config {{ url: {} }}", config.url));
+        assert_eq!(
+            state.render(TypeId::of::<Config>()).unwrap(),
+            "Config { url: localhost }"
+        );
+        assert!(state.render(TypeId::of::<i32>()).is_none());
+        assert!(state
+            .get_any(TypeId::of::<Config>())
+            .unwrap()
+            .downcast_ref::<Config>()
+            .is_some());
+    }
+}
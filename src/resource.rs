@@ -1,10 +1,22 @@
-use std::any::{type_name, Any};
+use std::{
+    any::{type_name, Any, TypeId},
+    error::Error,
+    fmt,
+    sync::Arc,
+};
 
 use frunk::{prelude::HList, HCons, HNil};
 
-use crate::Aero;
+use crate::{state::construction_stack, Aero};
 
 /// Bound on the types that can be used as an aerosol resource.
+///
+/// Resources are cloned every time they are obtained, so any state which should be
+/// shared between the copies must be behind an `Arc`. In particular, a resource
+/// containing a lazily initialized `OnceLock` should store an `Arc<OnceLock<T>>`:
+/// otherwise each copy gets its own cell, and initialization is repeated by each
+/// caller. Prefer letting aerosol handle laziness where possible, by making the
+/// lazily initialized value a resource in its own right.
 pub trait Resource: Any + Send + Sync + Clone {}
 impl<T: Any + Send + Sync + Clone> Resource for T {}
 
@@ -12,34 +24,326 @@ impl<T: Any + Send + Sync + Clone> Resource for T {}
 pub trait ResourceList: HList + Any + Send + Sync + Clone {
     /// Test at runtmie whether every resource in this list is present in the given Aero instance.
     fn test<R: ResourceList>(aero: &Aero<R>) -> bool;
+    /// Get every resource in this list from the given Aero instance, or `None` if any
+    /// of them are not present.
+    fn try_get_all<R: ResourceList>(aero: &Aero<R>) -> Option<Self>;
+    /// Get every resource in this list from the given Aero instance, panicking with
+    /// the name of the first one which is not present.
+    fn get_all<R: ResourceList>(aero: &Aero<R>) -> Self;
+    /// Append the name of every resource in this list which is not present in the
+    /// given Aero instance to `missing`.
+    fn collect_missing<R: ResourceList>(aero: &Aero<R>, missing: &mut Vec<&'static str>);
+    /// Whether the resource with this type ID is in this list.
+    fn contains(id: TypeId) -> bool;
 }
 impl ResourceList for HNil {
     fn test<R: ResourceList>(_aero: &Aero<R>) -> bool {
         true
     }
+    fn try_get_all<R: ResourceList>(_aero: &Aero<R>) -> Option<Self> {
+        Some(HNil)
+    }
+    fn get_all<R: ResourceList>(_aero: &Aero<R>) -> Self {
+        HNil
+    }
+    fn collect_missing<R: ResourceList>(_aero: &Aero<R>, _missing: &mut Vec<&'static str>) {}
+    fn contains(_id: TypeId) -> bool {
+        false
+    }
 }
 impl<H: Resource, T: ResourceList> ResourceList for HCons<H, T> {
     fn test<R: ResourceList>(aero: &Aero<R>) -> bool {
         aero.has::<H>() && T::test(aero)
     }
+    fn try_get_all<R: ResourceList>(aero: &Aero<R>) -> Option<Self> {
+        Some(HCons {
+            head: aero.try_get()?,
+            tail: T::try_get_all(aero)?,
+        })
+    }
+    fn get_all<R: ResourceList>(aero: &Aero<R>) -> Self {
+        HCons {
+            head: unwrap_resource(aero, aero.try_get()),
+            tail: T::get_all(aero),
+        }
+    }
+    fn collect_missing<R: ResourceList>(aero: &Aero<R>, missing: &mut Vec<&'static str>) {
+        if !aero.has::<H>() {
+            missing.push(type_name::<H>());
+        }
+        T::collect_missing(aero, missing)
+    }
+    fn contains(id: TypeId) -> bool {
+        id == TypeId::of::<H>() || T::contains(id)
+    }
+}
+
+/// Strip the module path from every path segment in a type name, eg.
+/// `alloc::sync::Arc<my_crate::Foo>` becomes `Arc<Foo>`.
+fn short_type_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut segment = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            result.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            result.push(c);
+        }
+    }
+    result.push_str(segment.rsplit("::").next().unwrap_or_default());
+    result
 }
 
-pub(crate) fn missing_resource<T: Resource>() -> ! {
-    panic!("Resource `{}` does not exist", type_name::<T>())
+/// Automatically implemented for resource lists where every resource implements `Default`.
+pub trait DefaultResourceList: ResourceList {
+    /// Insert the default value of every resource in this list which is not already present
+    /// in the given Aero instance.
+    fn insert_defaults<R: ResourceList>(aero: &Aero<R>);
+}
+impl DefaultResourceList for HNil {
+    fn insert_defaults<R: ResourceList>(_aero: &Aero<R>) {}
+}
+impl<H: Resource + Default, T: DefaultResourceList> DefaultResourceList for HCons<H, T> {
+    fn insert_defaults<R: ResourceList>(aero: &Aero<R>) {
+        if !aero.has::<H>() {
+            aero.insert(H::default());
+        }
+        T::insert_defaults(aero)
+    }
+}
+
+pub(crate) fn missing_resource<T: Resource, R: ResourceList>(aero: &Aero<R>) -> ! {
+    missing_resource_among::<T>(aero.resource_names())
+}
+
+/// Panics because `T` is missing, suggesting any of the resources in `names` which
+/// have a similar type name.
+pub(crate) fn missing_resource_among<T>(names: Vec<&'static str>) -> ! {
+    let name = type_name::<T>();
+    let short_name = short_type_name(name);
+    let suggestions: Vec<_> = names
+        .into_iter()
+        .filter(|other| *other != name && short_type_name(other) == short_name)
+        .map(|other| format!("`{}`", other))
+        .collect();
+    if suggestions.is_empty() {
+        panic!("Resource `{}` does not exist", name)
+    } else {
+        panic!(
+            "Resource `{}` does not exist. Did you mean {}?",
+            name,
+            suggestions.join(" or ")
+        )
+    }
 }
 
-pub(crate) fn unwrap_resource<T: Resource>(opt: Option<T>) -> T {
+pub(crate) fn unwrap_resource<T: Resource, R: ResourceList>(aero: &Aero<R>, opt: Option<T>) -> T {
     if let Some(value) = opt {
         value
     } else {
-        missing_resource::<T>()
+        missing_resource::<T, R>(aero)
     }
 }
 
-pub(crate) fn unwrap_constructed<T: Resource, U>(res: Result<U, impl Into<anyhow::Error>>) -> U {
+/// Error indicating that a resource does not exist. Resource constructors can return
+/// this (or an error whose source chain contains it) to report that a dependency is
+/// unavailable, rather than that construction itself failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingResource {
+    name: &'static str,
+}
+
+impl MissingResource {
+    /// Construct an error indicating that a resource of type `T` does not exist.
+    pub fn new<T: Resource>() -> Self {
+        Self {
+            name: type_name::<T>(),
+        }
+    }
+    pub(crate) fn named(name: &'static str) -> Self {
+        Self { name }
+    }
+    /// Name of the missing resource type.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl fmt::Display for MissingResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Resource `{}` does not exist", self.name)
+    }
+}
+
+impl Error for MissingResource {}
+
+/// Error returned when a resource could not be obtained.
+///
+/// Errors returned by a constructor are classified as `ObtainError::Missing` if they
+/// contain a `MissingResource` in their source chain, as `ObtainError::Cancelled`,
+/// `ObtainError::WaitTimedOut` or `ObtainError::ConstructTimedOut` if they contain one
+/// of those, and as `ObtainError::Failed` otherwise. More variants may be added in future.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ObtainError {
+    /// The resource, or one of the resources it depends on, does not exist and
+    /// could not be constructed.
+    Missing(MissingResource),
+    /// The resource constructor returned an error. The error is shared, as it may
+    /// be cached and returned from multiple attempts to obtain the resource.
+    Failed(Arc<anyhow::Error>),
+    /// The resource does not exist, and could not be constructed because the
+    /// Aero instance has been sealed. Contains the name of the resource type.
+    Sealed(&'static str),
+    /// Gave up waiting for another thread or task to finish constructing the
+    /// resource. Contains the name of the resource type.
+    WaitTimedOut(&'static str),
+    /// Construction of the resource took longer than its `CONSTRUCT_TIMEOUT`. Contains
+    /// the name of the resource type.
+    ConstructTimedOut(&'static str),
+    /// More than one implementation of an interface was registered with
+    /// `Aero::register_primary()`. Contains the name of the interface type.
+    Ambiguous(&'static str),
+    /// The resource constructor gave up because `Aero::cancel_constructions()` was
+    /// called. Contains the name of the resource type.
+    Cancelled(&'static str),
+    /// The resource does not exist, and could not be constructed because the Aero
+    /// instance is in `Phase::Runtime` and `Aero::with_strict_phase()` was called.
+    /// Contains the name of the resource type.
+    RuntimePhase(&'static str),
+}
+
+/// The kind of an `ObtainError`, returned from `ObtainError::kind()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ObtainErrorKind {
+    /// See `ObtainError::Missing`.
+    Missing,
+    /// See `ObtainError::Failed`.
+    Failed,
+    /// See `ObtainError::Sealed`.
+    Sealed,
+    /// See `ObtainError::WaitTimedOut`.
+    WaitTimedOut,
+    /// See `ObtainError::ConstructTimedOut`.
+    ConstructTimedOut,
+    /// See `ObtainError::Ambiguous`.
+    Ambiguous,
+    /// See `ObtainError::Cancelled`.
+    Cancelled,
+    /// See `ObtainError::RuntimePhase`.
+    RuntimePhase,
+}
+
+impl ObtainError {
+    /// The kind of error.
+    pub fn kind(&self) -> ObtainErrorKind {
+        match self {
+            Self::Missing(_) => ObtainErrorKind::Missing,
+            Self::Failed(_) => ObtainErrorKind::Failed,
+            Self::Sealed(_) => ObtainErrorKind::Sealed,
+            Self::WaitTimedOut(_) => ObtainErrorKind::WaitTimedOut,
+            Self::ConstructTimedOut(_) => ObtainErrorKind::ConstructTimedOut,
+            Self::Ambiguous(_) => ObtainErrorKind::Ambiguous,
+            Self::Cancelled(_) => ObtainErrorKind::Cancelled,
+            Self::RuntimePhase(_) => ObtainErrorKind::RuntimePhase,
+        }
+    }
+
+    /// Classify an error returned from a resource constructor.
+    pub(crate) fn from_construct_error(error: impl Into<anyhow::Error>) -> Self {
+        let error = error.into();
+        if let Some(missing) = error
+            .chain()
+            .find_map(|e| e.downcast_ref::<MissingResource>())
+        {
+            return Self::Missing(*missing);
+        }
+        match error.chain().find_map(|e| match e.downcast_ref() {
+            Some(Self::Cancelled(name)) => Some(Self::Cancelled(name)),
+            Some(Self::WaitTimedOut(name)) => Some(Self::WaitTimedOut(name)),
+            Some(Self::ConstructTimedOut(name)) => Some(Self::ConstructTimedOut(name)),
+            _ => None,
+        }) {
+            Some(error) => error,
+            None => Self::Failed(Arc::new(error)),
+        }
+    }
+}
+
+impl fmt::Display for ObtainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(missing) => missing.fmt(f),
+            Self::Failed(error) => error.fmt(f),
+            Self::Sealed(name) => write!(
+                f,
+                "Resource `{}` cannot be constructed because the container is sealed",
+                name
+            ),
+            Self::WaitTimedOut(name) => write!(
+                f,
+                "Timed out waiting for resource `{}` to be constructed",
+                name
+            ),
+            Self::ConstructTimedOut(name) => {
+                write!(f, "Construction of resource `{}` timed out", name)
+            }
+            Self::Ambiguous(name) => write!(
+                f,
+                "More than one implementation of `{}` is registered",
+                name
+            ),
+            Self::Cancelled(name) => write!(f, "Construction of resource `{}` was cancelled", name),
+            Self::RuntimePhase(name) => write!(
+                f,
+                "Resource `{}` cannot be constructed during the runtime phase",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for ObtainError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Missing(missing) => Some(missing),
+            Self::Failed(error) => Some(error.as_ref().as_ref()),
+            Self::Sealed(_)
+            | Self::WaitTimedOut(_)
+            | Self::ConstructTimedOut(_)
+            | Self::Ambiguous(_)
+            | Self::Cancelled(_)
+            | Self::RuntimePhase(_) => None,
+        }
+    }
+}
+
+/// Describe the resources whose constructors are running on this thread, so that
+/// panics from deep within a dependency graph show how the resource was reached.
+fn describe_construction_stack() -> String {
+    let stack = construction_stack();
+    if stack.is_empty() {
+        return String::new();
+    }
+    let mut result = String::from("\nWhilst constructing:");
+    for name in stack.iter().rev() {
+        result.push_str(&format!("\n    `{}`", name));
+    }
+    result
+}
+
+pub(crate) fn unwrap_constructed<T, U>(res: Result<U, impl Into<anyhow::Error>>) -> U {
     match res {
         Ok(x) => x,
-        Err(e) => panic!("Failed to construct `{}`: {}", type_name::<T>(), e.into()),
+        Err(e) => panic!(
+            "Failed to construct `{}`: {}{}",
+            type_name::<T>(),
+            e.into(),
+            describe_construction_stack()
+        ),
     }
 }
 
@@ -54,16 +358,66 @@ pub(crate) fn unwrap_constructed_hlist<T, U>(res: Result<U, impl Into<anyhow::Er
     }
 }
 
-pub(crate) fn duplicate_resource<T: Resource>() -> ! {
+pub(crate) fn duplicate_resource<T>() -> ! {
     panic!(
         "Duplicate resource: attempted to add a second `{}`",
         type_name::<T>()
     )
 }
 
-pub(crate) fn cyclic_resource<T: Resource>() -> ! {
+/// Like `duplicate_resource()`, but identifies which resources were being constructed
+/// when each instance was added, eg. from within `after_construction()`.
+pub(crate) fn duplicate_resource_from<T>(
+    first: Option<&'static str>,
+    second: Option<&'static str>,
+) -> ! {
+    let origin = |name: Option<&'static str>| match name {
+        Some(name) => format!("whilst constructing `{}`", name),
+        None => "directly".to_string(),
+    };
+    panic!(
+        "Duplicate resource: attempted to add a second `{}`. The first was added {}, and the second {}. Use `insert_if_absent()` if either may be registered.",
+        type_name::<T>(),
+        origin(first),
+        origin(second)
+    )
+}
+
+pub(crate) fn required_reset<T>() -> ! {
+    panic!(
+        "Resource `{}` is required, so cannot be reset",
+        type_name::<T>()
+    )
+}
+
+pub(crate) fn missing_coercion<T, Tr: ?Sized>() -> ! {
+    panic!(
+        "No coercion from `{}` to `{}` has been registered. Call `register_coercion()` first.",
+        type_name::<T>(),
+        type_name::<Arc<Tr>>()
+    )
+}
+
+pub(crate) fn cyclic_resource<T>() -> ! {
     panic!(
         "Cycle detected when constructing resource `{}`",
         type_name::<T>()
     )
 }
+
+pub(crate) fn depth_exceeded<T>(max_depth: usize) -> ! {
+    panic!(
+        "Construction depth exceeded when constructing resource `{}` (maximum depth is {}), possible unbounded recursion",
+        type_name::<T>(),
+        max_depth
+    )
+}
+
+pub(crate) fn deadlocked_resource(cycle: &[&'static str]) -> ! {
+    let path: Vec<_> = cycle.iter().map(|name| format!("`{}`", name)).collect();
+    panic!(
+        "Deadlock detected when waiting for resource `{}`: {}",
+        cycle[0],
+        path.join(" -> ")
+    )
+}
@@ -1,8 +1,14 @@
 #[cfg(feature = "async")]
 use std::task::Waker;
-use std::thread::Thread;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread::Thread,
+};
 
-use crate::resource::Resource;
+use parking_lot::Mutex;
 
 #[derive(Debug, Clone)]
 pub enum ThreadOrWaker {
@@ -46,35 +52,60 @@ impl ThreadOrWaker {
     }
 }
 
-pub enum Slot<T: Resource> {
+/// State of a single resource. Shared by `Aero` and `LocalAero`, so places no bounds
+/// on the resource type.
+pub enum Slot<T> {
     Filled(T),
     Placeholder {
+        /// Distinguishes this placeholder from any other placeholder which may
+        /// later occupy the same slot.
+        id: u64,
         owner: ThreadOrWaker,
-        waiting: Vec<ThreadOrWaker>,
+        /// Has its own lock, so that waiters can register themselves whilst only
+        /// holding a read lock on the slot map.
+        waiting: Mutex<Vec<ThreadOrWaker>>,
     },
+    /// Construction failed, and failure caching is enabled.
+    Failed(Arc<anyhow::Error>),
 }
 
-impl<T: Resource> Slot<T> {
-    pub fn desc(&self) -> SlotDesc<T> {
-        if let Slot::Filled(x) = self {
-            SlotDesc::Filled(x.clone())
-        } else {
-            SlotDesc::Placeholder
+impl<T> Slot<T> {
+    pub fn placeholder(owner: ThreadOrWaker) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Slot::Placeholder {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            owner,
+            waiting: Mutex::new(Vec::new()),
+        }
+    }
+    pub fn desc(&self) -> SlotDesc<T>
+    where
+        T: Clone,
+    {
+        match self {
+            Slot::Filled(x) => SlotDesc::Filled(x.clone()),
+            Slot::Placeholder { .. } => SlotDesc::Placeholder,
+            Slot::Failed(e) => SlotDesc::Failed(e.clone()),
         }
     }
 }
 
-impl<T: Resource> Drop for Slot<T> {
+impl<T> Drop for Slot<T> {
     fn drop(&mut self) {
         if let Self::Placeholder { waiting, .. } = self {
-            for item in waiting.drain(..) {
+            for item in waiting.get_mut().drain(..) {
                 item.unpark_or_wake();
             }
         }
     }
 }
 
-pub enum SlotDesc<T: Resource> {
+pub enum SlotDesc<T> {
     Filled(T),
     Placeholder,
+    Failed(Arc<anyhow::Error>),
 }
+
+/// Result of waiting for a slot to not have a placeholder. `Ok(None)` indicates
+/// that the slot is empty (or that the caller now owns the placeholder).
+pub type SlotResult<T> = Result<Option<T>, Arc<anyhow::Error>>;
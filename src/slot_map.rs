@@ -0,0 +1,303 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    fmt::{self, Debug},
+    marker::PhantomData,
+    sync::{Arc, OnceLock},
+};
+
+use anymap::hashbrown::RawMap;
+
+use crate::{resource::Resource, slot::Slot};
+
+/// Summary of a slot which does not expose the resource itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SlotState {
+    Present,
+    Constructing,
+    Failed,
+}
+
+/// Type-erased interface to a `Slot<T>`, allowing the contents of the map to
+/// be inspected without knowing the concrete resource types. Implemented for
+/// resources which need not be `Send` or `Sync`, for use by `LocalAero`.
+pub(crate) trait ErasedSlot: Any {
+    /// Name of the resource type stored in this slot.
+    fn type_name(&self) -> &'static str;
+    /// Type ID of the resource type stored in this slot.
+    fn resource_id(&self) -> TypeId;
+    fn state(&self) -> SlotState;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+/// Type-erased interface to the slot for a `Resource`.
+pub(crate) trait AnySlot: ErasedSlot + Send + Sync {
+    /// The resource stored in this slot, if it is filled, as returned from
+    /// `Aero::into_raw()`.
+    fn to_raw(&self) -> Option<Arc<dyn Any + Send + Sync>>;
+}
+
+impl<T: 'static> ErasedSlot for Slot<T> {
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+    fn resource_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+    fn state(&self) -> SlotState {
+        match self {
+            Slot::Filled(_) => SlotState::Present,
+            Slot::Placeholder { .. } => SlotState::Constructing,
+            Slot::Failed(_) => SlotState::Failed,
+        }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl<T: Resource> AnySlot for Slot<T> {
+    fn to_raw(&self) -> Option<Arc<dyn Any + Send + Sync>> {
+        match self {
+            Slot::Filled(x) => Some(Arc::new(x.clone())),
+            Slot::Placeholder { .. } | Slot::Failed(_) => None,
+        }
+    }
+}
+
+/// A resource imported with `Aero::from_raw()`. Its type is only known by ID until
+/// it is first accessed, at which point it is converted into a `Slot<T>`.
+struct RawSlot {
+    id: TypeId,
+    value: Arc<dyn Any + Send + Sync>,
+    typed: OnceLock<Box<dyn AnySlot>>,
+}
+
+impl RawSlot {
+    fn typed<T: Resource>(&self) -> &dyn AnySlot {
+        &**self.typed.get_or_init(|| {
+            let value = self
+                .value
+                .downcast_ref::<T>()
+                .expect("Slot stored under the wrong type");
+            Box::new(Slot::Filled(value.clone()))
+        })
+    }
+}
+
+impl ErasedSlot for RawSlot {
+    fn type_name(&self) -> &'static str {
+        // The name is not known until the resource has been accessed.
+        self.typed
+            .get()
+            .map_or("<unknown>", |slot| slot.type_name())
+    }
+    fn resource_id(&self) -> TypeId {
+        self.id
+    }
+    fn state(&self) -> SlotState {
+        SlotState::Present
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl AnySlot for RawSlot {
+    fn to_raw(&self) -> Option<Arc<dyn Any + Send + Sync>> {
+        Some(self.value.clone())
+    }
+}
+
+impl Debug for dyn AnySlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.type_name())
+    }
+}
+
+/// Lists the type of each slot along with its state, without printing the resources
+/// themselves, as they may contain secrets.
+pub(crate) fn debug_slots(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    slots: impl IntoIterator<Item = (&'static str, SlotState)>,
+) -> fmt::Result {
+    let mut slots: Vec<_> = slots.into_iter().collect();
+    slots.sort_by_key(|(type_name, _)| *type_name);
+    let mut s = f.debug_struct(name);
+    for (type_name, state) in slots {
+        s.field(type_name, &state);
+    }
+    s.finish()
+}
+
+/// How a `Slot<T>` is stored in a `SlotMap<S>`, where `S` is the type-erased slot
+/// type. This allows `Aero` and `LocalAero` to share the same map, even though they
+/// place different bounds on their resources.
+pub(crate) trait StoredSlot<S: ?Sized>: Sized {
+    fn boxed(self) -> Box<S>;
+    /// Get the slot for this type from the slot stored under its type ID.
+    fn downcast(slot: &S) -> &Self;
+    /// Convert the slot stored under this type's ID into a `Self`, if it was stored
+    /// in some other form, so that it can be mutated or removed.
+    fn resolve(slot: &mut Box<S>);
+}
+
+impl<T: Resource> StoredSlot<dyn AnySlot> for Slot<T> {
+    fn boxed(self) -> Box<dyn AnySlot> {
+        Box::new(self)
+    }
+    fn downcast(slot: &dyn AnySlot) -> &Self {
+        let slot = match slot.as_any().downcast_ref::<RawSlot>() {
+            Some(raw) => raw.typed::<T>(),
+            None => slot,
+        };
+        slot.as_any()
+            .downcast_ref()
+            .expect("Slot stored under the wrong type")
+    }
+    fn resolve(slot: &mut Box<dyn AnySlot>) {
+        if let Some(raw) = slot.as_any_mut().downcast_mut::<RawSlot>() {
+            raw.typed::<T>();
+            *slot = raw.typed.take().unwrap();
+        }
+    }
+}
+
+impl<T: 'static> StoredSlot<dyn ErasedSlot> for Slot<T> {
+    fn boxed(self) -> Box<dyn ErasedSlot> {
+        Box::new(self)
+    }
+    fn downcast(slot: &dyn ErasedSlot) -> &Self {
+        slot.as_any()
+            .downcast_ref()
+            .expect("Slot stored under the wrong type")
+    }
+    fn resolve(_slot: &mut Box<dyn ErasedSlot>) {}
+}
+
+/// Map from resource type to the slot for that resource. `S` is the type-erased
+/// slot type, which determines the bounds placed on the resources.
+pub(crate) struct SlotMap<S: ?Sized = dyn AnySlot> {
+    raw: RawMap<S>,
+}
+
+impl<S: ?Sized> Default for SlotMap<S> {
+    fn default() -> Self {
+        Self {
+            raw: RawMap::default(),
+        }
+    }
+}
+
+pub(crate) enum SlotEntry<'a, T, S: ?Sized = dyn AnySlot> {
+    Occupied(&'a mut Slot<T>),
+    Vacant(VacantSlot<'a, T, S>),
+}
+
+pub(crate) struct VacantSlot<'a, T, S: ?Sized = dyn AnySlot> {
+    map: &'a mut SlotMap<S>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static, S: ?Sized + ErasedSlot> VacantSlot<'_, T, S>
+where
+    Slot<T>: StoredSlot<S>,
+{
+    pub fn insert(self, slot: Slot<T>) {
+        self.map.insert(slot);
+    }
+}
+
+impl<S: ?Sized + ErasedSlot> SlotMap<S> {
+    pub fn get<T: 'static>(&self) -> Option<&Slot<T>>
+    where
+        Slot<T>: StoredSlot<S>,
+    {
+        self.raw
+            .get(&TypeId::of::<T>())
+            .map(|slot| Slot::<T>::downcast(slot))
+    }
+    pub fn entry<T: 'static>(&mut self) -> SlotEntry<'_, T, S>
+    where
+        Slot<T>: StoredSlot<S>,
+    {
+        let id = TypeId::of::<T>();
+        if !self.raw.contains_key(&id) {
+            return SlotEntry::Vacant(VacantSlot {
+                map: self,
+                phantom: PhantomData,
+            });
+        }
+        let slot = self.raw.get_mut(&id).unwrap();
+        Slot::<T>::resolve(slot);
+        SlotEntry::Occupied(
+            slot.as_any_mut()
+                .downcast_mut()
+                .expect("Slot stored under the wrong type"),
+        )
+    }
+    pub fn get_raw(&self, id: TypeId) -> Option<&S> {
+        self.raw.get(&id).map(|slot| &**slot)
+    }
+    pub fn insert<T: 'static>(&mut self, slot: Slot<T>)
+    where
+        Slot<T>: StoredSlot<S>,
+    {
+        self.raw.insert(TypeId::of::<T>(), slot.boxed());
+    }
+    pub fn remove<T: 'static>(&mut self) -> Option<Slot<T>>
+    where
+        Slot<T>: StoredSlot<S>,
+    {
+        let mut slot = self.raw.remove(&TypeId::of::<T>())?;
+        Slot::<T>::resolve(&mut slot);
+        Some(
+            *slot
+                .into_any()
+                .downcast()
+                .expect("Slot stored under the wrong type"),
+        )
+    }
+    /// Remove every slot matching the predicate, returning the removed slots.
+    pub fn remove_where(&mut self, mut f: impl FnMut(&S) -> bool) -> Vec<Box<S>> {
+        let ids: Vec<_> = self
+            .raw
+            .iter()
+            .filter(|(_, slot)| f(slot))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.iter().filter_map(|id| self.raw.remove(id)).collect()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &S> {
+        self.raw.values().map(|slot| &**slot)
+    }
+}
+
+impl SlotMap {
+    /// Insert a resource whose concrete type is only known by ID. The value must
+    /// be of the type identified by `id`.
+    pub fn insert_raw(&mut self, id: TypeId, value: Arc<dyn Any + Send + Sync>) {
+        self.raw.insert(
+            id,
+            Box::new(RawSlot {
+                id,
+                value,
+                typed: OnceLock::new(),
+            }),
+        );
+    }
+}
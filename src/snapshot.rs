@@ -0,0 +1,116 @@
+use std::{
+    any::{type_name, TypeId},
+    sync::Arc,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    resource::{Resource, ResourceList},
+    state::Aero,
+};
+
+type SerializeFn = Arc<dyn Fn(&Aero) -> Option<serde_json::Result<Value>> + Send + Sync>;
+/// Deserializes a resource, returning a function to insert it.
+type DeserializeFn = fn(&Value) -> serde_json::Result<Box<dyn FnOnce(&Aero)>>;
+
+/// Serializes and deserializes a resource, see `Aero::register_serializable()`.
+#[derive(Clone)]
+pub(crate) struct Serializer {
+    name: &'static str,
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Register `T` to be included in `snapshot_json()` and restored by
+    /// `hydrate_from_json()`. `T` is keyed on its type name within the snapshot.
+    /// Requires feature `serde`.
+    pub fn register_serializable<T: Resource + Serialize + DeserializeOwned>(&self) {
+        let serializer = Serializer {
+            name: type_name::<T>(),
+            serialize: Arc::new(|aero| aero.try_get::<T>().map(serde_json::to_value)),
+            deserialize: |value| {
+                let value = T::deserialize(value)?;
+                Ok(Box::new(move |aero: &Aero| {
+                    aero.replace(value);
+                }))
+            },
+        };
+        self.inner
+            .write()
+            .serializers
+            .insert(TypeId::of::<T>(), serializer);
+    }
+
+    /// Serialize every resource registered with `register_serializable()` which is
+    /// currently present into a JSON object, keyed on type name. Requires feature
+    /// `serde`.
+    pub fn snapshot_json(&self) -> serde_json::Result<Value> {
+        let serializers: Vec<_> = self.inner.read().serializers.values().cloned().collect();
+        let mut map = Map::new();
+        for serializer in serializers {
+            if let Some(value) = (serializer.serialize)(self.as_ref()) {
+                map.insert(serializer.name.into(), value?);
+            }
+        }
+        Ok(Value::Object(map))
+    }
+
+    /// Restore resources from a snapshot produced by `snapshot_json()`. Each resource
+    /// registered with `register_serializable()` which is present in the snapshot is
+    /// deserialized and inserted, replacing any existing value. Entries for other types
+    /// are ignored. If any resource fails to deserialize, nothing is inserted.
+    /// Requires feature `serde`.
+    pub fn hydrate_from_json(&self, value: &Value) -> serde_json::Result<()> {
+        let serializers: Vec<_> = self.inner.read().serializers.values().cloned().collect();
+        let inserts = serializers
+            .iter()
+            .filter_map(|serializer| value.get(serializer.name).map(serializer.deserialize))
+            .collect::<serde_json::Result<Vec<_>>>()?;
+        for insert in inserts {
+            insert(self.as_ref());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::Aero;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        verbose: bool,
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let state = Aero::new().with(Settings { verbose: true }).with(5u32);
+        state.register_serializable::<Settings>();
+        let snapshot = state.snapshot_json().unwrap();
+        assert_eq!(snapshot.as_object().unwrap().len(), 1);
+
+        let restored = Aero::new();
+        restored.register_serializable::<Settings>();
+        restored.hydrate_from_json(&snapshot).unwrap();
+        assert_eq!(restored.try_get(), Some(Settings { verbose: true }));
+    }
+
+    #[test]
+    fn hydrate_invalid() {
+        let state = Aero::new();
+        state.register_serializable::<Settings>();
+        state.register_serializable::<u32>();
+        let snapshot = json!({
+            std::any::type_name::<Settings>(): { "verbose": false },
+            "u32": "not a number",
+        });
+        assert!(state.hydrate_from_json(&snapshot).is_err());
+        assert!(!state.has::<Settings>());
+    }
+}
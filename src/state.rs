@@ -1,20 +1,430 @@
-use std::{any::Any, fmt::Debug, marker::PhantomData, sync::Arc, task::Poll};
+#[cfg(feature = "async")]
+use std::task::Waker;
+use std::{
+    any::{type_name, Any, TypeId},
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    marker::PhantomData,
+    ops::Add,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    task::Poll,
+    time::{Duration, Instant},
+};
 
-use anymap::hashbrown::{Entry, Map};
+use anymap::hashbrown::Map;
 use frunk::{
     hlist::{HFoldRightable, Sculptor},
     HCons, HNil, Poly,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+#[cfg(feature = "async")]
+use crate::events::AeroEvent;
 use crate::{
-    resource::{cyclic_resource, duplicate_resource, missing_resource, Resource, ResourceList},
-    slot::{Slot, SlotDesc, ThreadOrWaker},
+    alias::Alias,
+    dependencies::DeclaredDependencies,
+    lifetime::Lifetime,
+    record::{RecordLog, RecordedEvent},
+    resource::{
+        cyclic_resource, deadlocked_resource, depth_exceeded, duplicate_resource,
+        duplicate_resource_from, missing_resource, required_reset, DefaultResourceList,
+        ObtainError, Resource, ResourceList,
+    },
+    slot::{Slot, SlotDesc, SlotResult, ThreadOrWaker},
+    slot_map::{debug_slots, SlotEntry, SlotMap, SlotState},
+    stats::FailureStats,
+    wait_graph::WaitGraph,
 };
 
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub(crate) struct InnerAero {
-    items: Map<dyn Any + Send + Sync>,
+    items: SlotMap,
+    wait_graph: Mutex<WaitGraph>,
+    sealed: AtomicBool,
+    strict_checks: AtomicBool,
+    pub(crate) strict_phase: AtomicBool,
+    cache_failures: AtomicBool,
+    track_usage: AtomicBool,
+    /// Set whilst any recorder returned from `record()` may still be alive.
+    pub(crate) recording: AtomicBool,
+    /// Logs of the recorders returned from `record()`.
+    pub(crate) recorders: Mutex<Vec<Weak<RecordLog>>>,
+    /// Resources being constructed when each resource was inserted, if any.
+    inserted_by: HashMap<TypeId, &'static str>,
+    /// Resources accessed whilst usage tracking was enabled.
+    accessed: Mutex<HashSet<TypeId>>,
+    /// Number of times construction of each resource type has been attempted.
+    construction_counts: HashMap<TypeId, usize>,
+    /// Dependencies recorded with `declare()`.
+    pub(crate) declared: DeclaredDependencies,
+    /// Construction failures for each resource type, see `failure_stats()`.
+    pub(crate) failures: FailureStats,
+    /// Number of times each resource type has been filled or reset.
+    generations: HashMap<TypeId, u64>,
+    /// Factories registered with `register_factory()`, keyed on their type.
+    pub(crate) factories: Map<dyn Any + Send + Sync>,
+    /// Aliases registered with `alias()`, keyed on their type.
+    pub(crate) aliases: Map<dyn Any + Send + Sync>,
+    /// Implementations registered with `register_primary()`, keyed on their type.
+    pub(crate) primaries: Map<dyn Any + Send + Sync>,
+    /// Derivations registered with `register_derived()`, keyed on their type.
+    pub(crate) derivations: Map<dyn Any + Send + Sync>,
+    /// Decorators registered with `register_decorator()`, keyed on their type.
+    pub(crate) decorators: Map<dyn Any + Send + Sync>,
+    /// Coercions registered with `register_coercion()`, keyed on their type.
+    pub(crate) coercions: Map<dyn Any + Send + Sync>,
+    /// Timed constructions whose caller gave up waiting, keyed on their type. See
+    /// `Constructible::CONSTRUCT_TIMEOUT`.
+    pub(crate) abandoned: Map<dyn Any + Send + Sync>,
+    /// Hooks registered with `register_post_construct_for()`, keyed on their trait.
+    pub(crate) post_construct_hooks: Map<dyn Any + Send + Sync>,
+    /// Renderers registered with `register_renderer()`.
+    pub(crate) renderers: HashMap<TypeId, crate::render::Renderer>,
+    /// Hints set with `register_ordering_hint()`.
+    pub(crate) ordering_hints: HashMap<TypeId, crate::ordering::OrderingHint>,
+    /// Priorities set with `register_priority()`.
+    pub(crate) priorities: HashMap<TypeId, crate::ordering::Priority>,
+    /// Members registered with `register_in_group()`, keyed on their group.
+    pub(crate) groups: Map<dyn Any + Send + Sync>,
+    /// Resources stored under a user-defined key, in addition to their type.
+    pub(crate) keyed: Map<dyn Any + Send + Sync>,
+    /// Raised by `cancel_constructions()`.
+    pub(crate) cancel: crate::cancel::CancelToken,
+    /// Set with `set_phase()`.
+    pub(crate) phase: crate::phase::Phase,
+    /// Resources constructed whilst in `Phase::Runtime`.
+    pub(crate) runtime_constructions: Vec<&'static str>,
+    /// Limit set with `with_max_depth()`, or `None` to use `DEFAULT_MAX_DEPTH`.
+    max_depth: Option<usize>,
+    /// Lifetimes set with `register()`.
+    pub(crate) lifetimes: HashMap<TypeId, Lifetime>,
+    /// Expiry times set with `register_with_ttl()`.
+    pub(crate) ttls: HashMap<TypeId, Duration>,
+    /// When the resource with each type ID was last filled.
+    filled_at: HashMap<TypeId, Instant>,
+    /// Resources not present in this instance are looked up in the parent.
+    parent: Option<Aero>,
+    /// Resources linked with `link()`, which are looked up in another instance.
+    pub(crate) links: HashMap<TypeId, Aero>,
+    /// Section keys set with `register_figment_section()`.
+    #[cfg(feature = "figment")]
+    pub(crate) figment_sections: HashMap<TypeId, String>,
+    /// Resources registered with `register_serializable()`.
+    #[cfg(feature = "serde")]
+    pub(crate) serializers: HashMap<TypeId, crate::snapshot::Serializer>,
+    /// Wakers for `subscribe()` futures, keyed by resource type and then subscription.
+    #[cfg(feature = "async")]
+    pub(crate) subscribers: HashMap<TypeId, HashMap<u64, Waker>>,
+    /// Set with `set_blocking_executor()`.
+    #[cfg(feature = "async")]
+    pub(crate) blocking_executor: Option<crate::async_constructible::BlockingExecutor>,
+    /// Set with `set_timer()`.
+    #[cfg(feature = "async")]
+    pub(crate) timer: Option<crate::async_constructible::Timer>,
+    /// Senders for each subscriber to `events()`.
+    #[cfg(feature = "async")]
+    pub(crate) events: Vec<futures_channel::mpsc::Sender<AeroEvent>>,
+}
+
+impl InnerAero {
+    pub(crate) fn lifetime(&self, id: TypeId) -> Lifetime {
+        match (self.lifetimes.get(&id), &self.parent) {
+            (Some(lifetime), _) => *lifetime,
+            (None, Some(parent)) => parent.inner.read().lifetime(id),
+            (None, None) => Lifetime::Singleton,
+        }
+    }
+
+    /// Look up `T` in this instance, following aliases, links and parents in the same
+    /// way as `Aero::try_get()`, but without waiting for resources which are under
+    /// construction. Resources whose TTL has elapsed are treated as absent.
+    pub(crate) fn get_slot<T: Resource>(&self) -> Option<SlotDesc<T>> {
+        match self.items.get::<T>() {
+            Some(_) if self.is_expired(TypeId::of::<T>()) => None,
+            Some(slot) => Some(Slot::desc(slot)),
+            None => {
+                if let Some(alias) = self.aliases.get::<Alias<T>>() {
+                    return (alias.get_locked)(self).map(SlotDesc::Filled);
+                }
+                self.parent_for::<T>()?.try_get_slot()
+            }
+        }
+    }
+
+    /// The parent to look up `T` in, if `T` is not present in this instance.
+    pub(crate) fn parent_for<T: Resource>(&self) -> Option<&Aero> {
+        if let Some(source) = self.links.get(&TypeId::of::<T>()) {
+            return Some(source);
+        }
+        let parent = self.parent.as_ref()?;
+        match self.lifetime(TypeId::of::<T>()) {
+            Lifetime::Scoped => None,
+            Lifetime::Singleton | Lifetime::Transient => Some(parent),
+        }
+    }
+
+    fn record_origin(&mut self, id: TypeId, origin: Option<&'static str>) {
+        match origin {
+            Some(origin) => self.inserted_by.insert(id, origin),
+            None => self.inserted_by.remove(&id),
+        };
+    }
+
+    fn mark_accessed<T: Resource>(&self) {
+        if self.track_usage.load(Ordering::SeqCst) {
+            self.accessed.lock().insert(TypeId::of::<T>());
+        }
+    }
+
+    /// Wake any tasks waiting for the resource with this type ID to become available,
+    /// and advance its generation.
+    fn notify_filled(&mut self, id: TypeId) {
+        self.advance_generation(id);
+        self.filled_at.insert(id, Instant::now());
+        #[cfg(feature = "async")]
+        for waker in self
+            .subscribers
+            .remove(&id)
+            .into_iter()
+            .flat_map(HashMap::into_values)
+        {
+            waker.wake();
+        }
+    }
+
+    /// Publish an event to any subscribers, forgetting those which have gone away.
+    #[cfg(feature = "async")]
+    fn emit(&mut self, event: AeroEvent) {
+        // Events are dropped for subscribers which have fallen too far behind.
+        self.events
+            .retain_mut(|events| match events.try_send(event) {
+                Ok(()) => true,
+                Err(e) => !e.is_disconnected(),
+            });
+    }
+
+    pub(crate) fn has_strict_checks(&self) -> bool {
+        self.strict_checks.load(Ordering::SeqCst)
+    }
+
+    /// Check that a resource about to be stored as a `T` is really what the caller
+    /// intended, if strict checks are enabled. See `Aero::with_strict_checks()`.
+    fn check_stored<T: Resource>(&self, value: &T) {
+        if !self.has_strict_checks() {
+            return;
+        }
+        let expected = TypeId::of::<T>();
+        if let Some(slot) = self.items.get_raw(expected) {
+            assert!(
+                slot.resource_id() == expected,
+                "Strict checks: the slot for `{}` holds a different resource type",
+                type_name::<T>()
+            );
+        }
+        if stored_type_id(value) != expected {
+            panic!(
+                "Strict checks: attempted to store a type-erased `{}` as a resource. It can only be obtained as `{0}`, not as the type it contains",
+                type_name::<T>()
+            );
+        }
+    }
+
+    fn advance_generation(&mut self, id: TypeId) {
+        *self.generations.entry(id).or_default() += 1;
+    }
+
+    /// Whether the TTL of the resource with this type ID has elapsed.
+    fn is_expired(&self, id: TypeId) -> bool {
+        match (self.ttls.get(&id), self.filled_at.get(&id)) {
+            (Some(ttl), Some(filled_at)) => filled_at.elapsed() >= *ttl,
+            _ => false,
+        }
+    }
+
+    /// Handle polling a slot which is filled, failed or under construction. If it is
+    /// under construction, the caller is added to the waiting list, which has its own
+    /// lock so that only shared access to the slot map is required.
+    pub(crate) fn poll_occupied<T: Resource, U, C: Into<ThreadOrWaker>>(
+        &self,
+        slot: &Slot<T>,
+        registration: &mut Option<WaitRegistration>,
+        thread_or_waker_fn: impl Fn() -> C,
+        map: fn(&T) -> U,
+    ) -> Poll<SlotResult<U>> {
+        let res = match slot {
+            Slot::Filled(x) => Ok(Some(map(x))),
+            Slot::Failed(e) => Err(e.clone()),
+            Slot::Placeholder { id, owner, waiting } => {
+                let current: ThreadOrWaker = thread_or_waker_fn().into();
+                if current == *owner {
+                    cyclic_resource::<T>()
+                }
+                if let Err(cycle) = self.wait_graph.lock().add(
+                    current.clone(),
+                    owner.clone(),
+                    *id,
+                    type_name::<T>(),
+                ) {
+                    deadlocked_resource(&cycle)
+                }
+                let mut waiting = waiting.lock();
+                match registration {
+                    // The placeholder may have been replaced by a different one since
+                    // we last registered, in which case our index is meaningless.
+                    Some(reg) if reg.placeholder == *id => {
+                        waiting[reg.index] = current.clone();
+                        reg.waiter = current;
+                    }
+                    _ => {
+                        *registration = Some(WaitRegistration {
+                            placeholder: *id,
+                            index: waiting.len(),
+                            waiter: current.clone(),
+                        });
+                        waiting.push(current);
+                    }
+                }
+                return Poll::Pending;
+            }
+        };
+        self.finish_wait(registration);
+        Poll::Ready(res)
+    }
+
+    /// Forget that anyone is waiting on `slot`, if it is a placeholder, as it is about
+    /// to be filled or removed. Waiters only remove their own edge from the wait graph
+    /// when they next poll, and until then a stale edge could be mistaken for a
+    /// deadlock if the owner goes on to wait for a resource one of them is constructing.
+    pub(crate) fn release_waiters<T>(&self, slot: Option<&Slot<T>>) {
+        if let Some(Slot::Placeholder { id, .. }) = slot {
+            self.wait_graph.lock().remove_placeholder(*id);
+        }
+    }
+
+    /// Record that the caller is no longer waiting for a placeholder, if it was.
+    pub(crate) fn finish_wait(&self, registration: &mut Option<WaitRegistration>) {
+        if let Some(reg) = registration.take() {
+            self.wait_graph.lock().remove(&reg.waiter);
+        }
+    }
+}
+
+/// Type ID of the value a resource actually holds, looking through type-erased
+/// wrappers such as `Arc<dyn Any + Send + Sync>`.
+fn stored_type_id(value: &dyn Any) -> TypeId {
+    if let Some(value) = value.downcast_ref::<Arc<dyn Any + Send + Sync>>() {
+        return (**value).type_id();
+    }
+    if let Some(value) = value.downcast_ref::<Box<dyn Any + Send + Sync>>() {
+        return (**value).type_id();
+    }
+    value.type_id()
+}
+
+/// Result of `Aero::lookup_for_obtain()`.
+pub(crate) enum Lookup<T: Resource> {
+    /// `T` has a transient lifetime, so must be constructed afresh.
+    Transient,
+    /// The slot for `T`, if there is one.
+    Slot(Option<SlotDesc<T>>),
+    /// `T` is linked to another instance, so must be obtained from there.
+    Linked(Aero),
+}
+
+/// Records that a thread or task has added itself to the waiting list of a
+/// placeholder.
+pub(crate) struct WaitRegistration {
+    placeholder: u64,
+    index: usize,
+    waiter: ThreadOrWaker,
+}
+
+/// Default limit on how deeply resource constructors may be nested. See
+/// `Aero::with_max_depth()`.
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 256;
+
+thread_local! {
+    /// Names of the resources whose constructors are currently running on this
+    /// thread, outermost first.
+    static CONSTRUCTION_STACK: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Names of the resources whose constructors are currently running on this thread,
+/// outermost first.
+pub(crate) fn construction_stack() -> Vec<&'static str> {
+    CONSTRUCTION_STACK.with(|stack| stack.borrow().clone())
+}
+
+/// Continue the construction stack of another thread on this thread, for
+/// constructors which are moved to a new thread part way through, so that depth
+/// limits and diagnostics behave as if they had stayed on the original thread.
+pub(crate) fn inherit_construction_stack(stack: Vec<&'static str>) {
+    CONSTRUCTION_STACK.with(|current| *current.borrow_mut() = stack);
+}
+
+/// Held whilst a resource constructor is running on the current thread.
+pub(crate) struct DepthGuard(());
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        CONSTRUCTION_STACK.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+/// Held whilst constructing a resource. Removes the placeholder if dropped
+/// without being filled, so that waiters are woken even if construction
+/// panics or is cancelled.
+pub(crate) struct PlaceholderGuard<'a, R: ResourceList, T: Resource> {
+    aero: &'a Aero<R>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<'a, R: ResourceList, T: Resource> PlaceholderGuard<'a, R, T> {
+    pub(crate) fn new(aero: &'a Aero<R>) -> Self {
+        Self {
+            aero,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn fill(self, value: T) {
+        self.aero.fill_placeholder::<T>(value);
+        std::mem::forget(self);
+    }
+
+    /// Record that construction failed. If failure caching is enabled, the
+    /// error is stored in the slot, otherwise the placeholder is removed.
+    pub(crate) fn fail(self, error: ObtainError) -> ObtainError {
+        let cached = match &error {
+            ObtainError::Failed(e) => Some(e.clone()),
+            ObtainError::ConstructTimedOut(name) => Some(Arc::new(anyhow::Error::new(
+                ObtainError::ConstructTimedOut(name),
+            ))),
+            _ => None,
+        };
+        if let Some(e) = cached {
+            if self.aero.caches_failures() {
+                let mut guard = self.aero.inner.write();
+                guard.release_waiters(guard.items.get::<T>());
+                guard.items.insert(Slot::<T>::Failed(e));
+                #[cfg(feature = "async")]
+                guard.emit(AeroEvent::ConstructionFailed(type_name::<T>()));
+                drop(guard);
+                std::mem::forget(self);
+            }
+        }
+        error
+    }
+}
+
+impl<R: ResourceList, T: Resource> Drop for PlaceholderGuard<'_, R, T> {
+    fn drop(&mut self) {
+        self.aero.clear_placeholder::<T>();
+    }
 }
 
 /// Stores a collection of resources keyed on resource type.
@@ -26,9 +436,43 @@ pub struct Aero<R: ResourceList = HNil> {
     pub(crate) phantom: PhantomData<Arc<R>>,
 }
 
+/// A consistent view of the resources in an `Aero` instance, returned from
+/// `Aero::read()`. No resources can be inserted, replaced or reset whilst the
+/// guard is held.
+pub struct ReadGuard<'a> {
+    guard: RwLockReadGuard<'a, InnerAero>,
+}
+
+impl ReadGuard<'_> {
+    /// Tries to get an instance of `T`. Returns `None` if there is no such instance,
+    /// or if `T` is still under construction.
+    pub fn get<T: Resource>(&self) -> Option<T> {
+        match self.guard.get_slot::<T>()? {
+            SlotDesc::Filled(x) => {
+                self.guard.mark_accessed::<T>();
+                Some(x)
+            }
+            SlotDesc::Placeholder | SlotDesc::Failed(_) => None,
+        }
+    }
+
+    /// Check if a resource with a specific type is fully constructed.
+    pub fn has<T: Resource>(&self) -> bool {
+        matches!(self.guard.get_slot::<T>(), Some(SlotDesc::Filled(_)))
+    }
+}
+
+/// Lists the type of each resource along with whether it is present or still
+/// under construction. Resource values are never printed, as they may contain
+/// secrets.
 impl<R: ResourceList> Debug for Aero<R> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.inner.fmt(f)
+        let guard = self.inner.read();
+        let slots = guard
+            .items
+            .iter()
+            .map(|slot| (slot.type_name(), slot.state()));
+        debug_slots(f, "Aero", slots)
     }
 }
 
@@ -40,6 +484,27 @@ impl Aero {
             phantom: PhantomData,
         }
     }
+
+    /// Construct a new instance from a map of type-erased resources, eg. one
+    /// previously returned from `into_raw()`. Each value must be of the type
+    /// identified by its key, otherwise this will panic.
+    ///
+    /// The type names of these resources are not known until they are first
+    /// accessed, so they are shown as `<unknown>` when debug formatting.
+    pub fn from_raw(resources: HashMap<TypeId, Arc<dyn Any + Send + Sync>>) -> Self {
+        let aero = Self::new();
+        let mut guard = aero.inner.write();
+        for (id, value) in resources {
+            assert!(
+                (*value).type_id() == id,
+                "Raw resource is not of the type identified by its key"
+            );
+            guard.items.insert_raw(id, value);
+            guard.advance_generation(id);
+        }
+        drop(guard);
+        aero
+    }
 }
 
 impl<R: ResourceList> Clone for Aero<R> {
@@ -70,18 +535,61 @@ impl<
 
 impl<R: ResourceList> Aero<R> {
     /// Directly insert a resource into the collection. Panics if a resource of the
-    /// same type already exists.
+    /// same type already exists. Replaces any cached construction failure.
+    ///
+    /// If a resource is inserted whilst another is being constructed, eg. from
+    /// `Constructible::after_construction()`, the panic message identifies which
+    /// resources added each instance.
     pub fn insert<T: Resource>(&self, value: T) {
-        match self.inner.write().items.entry() {
-            Entry::Occupied(_) => duplicate_resource::<T>(),
-            Entry::Vacant(vac) => {
+        let id = TypeId::of::<T>();
+        let origin = construction_stack().last().copied();
+        let mut guard = self.inner.write();
+        guard.check_stored(&value);
+        match guard.items.entry() {
+            SlotEntry::Occupied(slot @ Slot::Failed(_)) => *slot = Slot::Filled(value),
+            SlotEntry::Occupied(Slot::Filled(_)) => {
+                duplicate_resource_from::<T>(guard.inserted_by.get(&id).copied(), origin)
+            }
+            SlotEntry::Occupied(_) => duplicate_resource::<T>(),
+            SlotEntry::Vacant(vac) => {
                 vac.insert(Slot::Filled(value));
             }
         }
+        guard.record_origin(id, origin);
+        guard.notify_filled(id);
+        #[cfg(feature = "async")]
+        guard.emit(AeroEvent::Inserted(type_name::<T>()));
+    }
+
+    /// Insert a resource into the collection, unless a resource of the same type
+    /// already exists or is being constructed. Returns true if `value` was inserted.
+    /// Replaces any cached construction failure.
+    ///
+    /// Useful in `Constructible::after_construction()` when several resources may
+    /// provide the same interface, eg. an `Arc<dyn EmailSender>`, and the first should
+    /// win rather than panicking.
+    pub fn insert_if_absent<T: Resource>(&self, value: T) -> bool {
+        let id = TypeId::of::<T>();
+        let origin = construction_stack().last().copied();
+        let mut guard = self.inner.write();
+        guard.check_stored(&value);
+        match guard.items.entry() {
+            SlotEntry::Occupied(slot @ Slot::Failed(_)) => *slot = Slot::Filled(value),
+            SlotEntry::Occupied(_) => return false,
+            SlotEntry::Vacant(vac) => {
+                vac.insert(Slot::Filled(value));
+            }
+        }
+        guard.record_origin(id, origin);
+        guard.notify_filled(id);
+        #[cfg(feature = "async")]
+        guard.emit(AeroEvent::Inserted(type_name::<T>()));
+        true
     }
 
     /// Builder method equivalent to calling `insert()` but can be chained.
     pub fn with<T: Resource>(self, value: T) -> Aero<HCons<T, R>> {
+        self.check_requirable::<T>();
         self.insert(value);
         Aero {
             inner: self.inner,
@@ -89,6 +597,31 @@ impl<R: ResourceList> Aero<R> {
         }
     }
 
+    /// Wrap a value in an `Arc` and insert it into the collection as an `Arc<T>`.
+    /// Panics if an `Arc<T>` already exists.
+    pub fn insert_arc<T: Send + Sync + 'static>(&self, value: T) {
+        self.insert(Arc::new(value));
+    }
+
+    /// Builder method equivalent to calling `insert_arc()` but can be chained.
+    pub fn with_arc<T: Send + Sync + 'static>(self, value: T) -> Aero<HCons<Arc<T>, R>> {
+        self.with(Arc::new(value))
+    }
+
+    /// Insert the default value of every resource in `L` which is not already present.
+    /// Unlike `Aero::default()`, this can be used to top up a partially built instance.
+    pub fn with_defaults_for<L>(self) -> Aero<<L as Add<R>>::Output>
+    where
+        L: DefaultResourceList + Add<R>,
+        <L as Add<R>>::Output: ResourceList,
+    {
+        L::insert_defaults(&self);
+        Aero {
+            inner: self.inner,
+            phantom: PhantomData,
+        }
+    }
+
     /// Convert into a different variant of the Aero type. The new variant must
     /// not require any resources which are not required as part of this type.
     pub fn into<R2: ResourceList, I>(self) -> Aero<R2>
@@ -113,6 +646,21 @@ impl<R: ResourceList> Aero<R> {
         unsafe { std::mem::transmute(self) }
     }
 
+    /// Get an owned handle to this aerosol instance, without any required resources.
+    /// This is as cheap as cloning an `Arc`. Constructors only receive a borrowed
+    /// `&Aero`, so a resource which needs to obtain other resources lazily (eg. a
+    /// router which resolves handlers on demand) should store a handle instead.
+    ///
+    /// Note that a resource which stores a handle keeps the whole instance alive, so
+    /// the instance will never be dropped unless the resource is reset. Store a handle
+    /// from `downgrade()` instead to avoid this.
+    pub fn handle(&self) -> Aero {
+        Aero {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+
     /// Try to convert into a different variant of the Aero type. Returns the
     /// original type if one or more of the required resources are not fully
     /// constructed.
@@ -127,6 +675,15 @@ impl<R: ResourceList> Aero<R> {
         }
     }
 
+    /// Names of the resources required by `R2` which are not fully constructed, in
+    /// the order they are listed. If this is empty, `try_into::<R2>()` will succeed.
+    /// Useful for reporting every missing resource at once during startup validation.
+    pub fn missing_for<R2: ResourceList>(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        R2::collect_missing(self, &mut missing);
+        missing
+    }
+
     /// Try to convert into a different variant of the Aero type. Returns
     /// `None` if one or more of the required resources are not fully
     /// constructed.
@@ -145,14 +702,33 @@ impl<R: ResourceList> Aero<R> {
     /// Check if a resource with a specific type is fully constructed in this
     /// aerosol instance
     pub fn has<T: Resource>(&self) -> bool {
-        matches!(
-            self.inner.read().items.get::<Slot<T>>(),
-            Some(Slot::Filled(_))
-        )
+        self.read().has::<T>()
+    }
+
+    /// Lock the collection for reading, so that several resources can be read from
+    /// a single consistent snapshot, without any of them being replaced or reset
+    /// part way through.
+    ///
+    /// Holding the guard blocks anything which needs to modify the collection,
+    /// including inserting and constructing resources, so it should be released as
+    /// soon as possible. Calling any such method on this instance whilst holding the
+    /// guard will deadlock.
+    pub fn read(&self) -> ReadGuard<'_> {
+        ReadGuard {
+            guard: self.inner.read(),
+        }
+    }
+
+    /// Call `f` with the collection locked for reading, so that it can look up any
+    /// number of resources whilst only acquiring the lock once. Resources cannot be
+    /// constructed from within `f`. The same caveats apply as for `read()`.
+    pub fn batch<T>(&self, f: impl FnOnce(&ReadGuard<'_>) -> T) -> T {
+        f(&self.read())
     }
 
     /// Assert that a resource exists, returns `self` unchanged if not
     pub fn try_assert<T: Resource>(self) -> Result<Aero<HCons<T, R>>, Self> {
+        self.check_requirable::<T>();
         if self.has::<T>() {
             Ok(Aero {
                 inner: self.inner,
@@ -166,53 +742,535 @@ impl<R: ResourceList> Aero<R> {
     /// Assert that a resource exists, panic if not
     pub fn assert<T: Resource>(self) -> Aero<HCons<T, R>> {
         self.try_assert()
-            .unwrap_or_else(|_| missing_resource::<T>())
+            .unwrap_or_else(|this| missing_resource::<T, R>(&this))
+    }
+
+    /// Finish building an aerosol instance, checking that every resource it claims to
+    /// require is actually present. The required resources are normally guaranteed
+    /// by the type system, but this catches cases where they have since been removed,
+    /// eg. with `reset()`. Returns the names of the missing resources on failure.
+    pub fn try_build(self) -> Result<Self, Vec<&'static str>> {
+        let missing = self.missing_for::<R>();
+        if missing.is_empty() {
+            Ok(self)
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Finish building an aerosol instance, panicking if any resource it claims to
+    /// require is not present. See `try_build()`.
+    ///
+    /// ```rust
+    /// use aerosol::Aero;
+    ///
+    /// let aero: Aero![&str, i32] = Aero::new().with(42).with("Hello").build();
+    /// ```
+    pub fn build(self) -> Self {
+        self.try_build().unwrap_or_else(|missing| {
+            let missing: Vec<_> = missing.iter().map(|name| format!("`{}`", name)).collect();
+            panic!("Required resources do not exist: {}", missing.join(", "))
+        })
+    }
+
+    /// Prevent any further resources from being constructed. Attempts to construct a
+    /// resource after this point will fail with `ObtainError::Sealed`, although resources
+    /// which already exist can still be obtained and resources can still be inserted
+    /// directly. This is useful during shutdown, to stop late requests spinning up new
+    /// resources.
+    pub fn seal(&self) {
+        self.inner.read().sealed.store(true, Ordering::SeqCst);
+    }
+
+    /// Set the maximum depth to which resource constructors may be nested, ie. the
+    /// length of the longest chain of resources which each need the next in order
+    /// to be constructed. Exceeding the limit panics, rather than risking a stack
+    /// overflow when the dependency graph is unbounded (eg. a generic resource which
+    /// depends on a different instantiation of itself). Defaults to 256.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        self.inner.write().max_depth = Some(max_depth);
+        self
+    }
+
+    /// Record that a constructor for `T` is about to run on the current thread. Panics
+    /// if this would exceed the maximum depth.
+    pub(crate) fn enter_construction<T: Resource>(&self) -> DepthGuard {
+        let max_depth = self.inner.read().max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+        let depth = CONSTRUCTION_STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            stack.push(type_name::<T>());
+            stack.len()
+        });
+        let guard = DepthGuard(());
+        if depth > max_depth {
+            depth_exceeded::<T>(max_depth);
+        }
+        guard
+    }
+
+    /// Enable additional checks when resources are inserted or constructed, to catch
+    /// wiring mistakes early. Each resource stored as a `T` must have the type ID of
+    /// `T`, which rejects type-erased resources such as `Arc<dyn Any + Send + Sync>`,
+    /// usually the result of forgetting to downcast in `after_construction()`, and
+    /// the slot it is stored in must belong to `T`. Intended for use in tests, as the
+    /// checks add overhead to every insertion.
+    pub fn with_strict_checks(self) -> Self {
+        self.inner
+            .read()
+            .strict_checks
+            .store(true, Ordering::SeqCst);
+        self
+    }
+
+    /// Returns true if `seal()` has been called on this aerosol instance.
+    pub fn is_sealed(&self) -> bool {
+        self.inner.read().sealed.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn check_sealed<T: Resource>(&self) -> Result<(), ObtainError> {
+        if self.is_sealed() {
+            Err(ObtainError::Sealed(type_name::<T>()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enable or disable caching of construction failures. When enabled, if constructing
+    /// a resource fails, the error is stored and returned from subsequent attempts to
+    /// obtain the resource, rather than construction being retried. Call `reset()` to
+    /// discard a cached failure. Construction timing out is cached like any other
+    /// failure, but errors which indicate a missing dependency (`ObtainError::Missing`)
+    /// are never cached.
+    pub fn cache_failures(&self, enabled: bool) {
+        self.inner
+            .read()
+            .cache_failures
+            .store(enabled, Ordering::SeqCst);
+    }
+
+    pub(crate) fn caches_failures(&self) -> bool {
+        self.inner.read().cache_failures.load(Ordering::SeqCst)
+    }
+
+    /// Remove the instance of `T` or the cached construction failure for `T`, if
+    /// any, so that it will be constructed again the next time it is obtained.
+    /// Returns the removed instance. A resource which is currently under
+    /// construction is left untouched.
+    ///
+    /// Panics if `T` is one of the required resources in `R`, as it would no longer
+    /// be present.
+    pub fn reset<T: Resource>(&self) -> Option<T> {
+        if R::contains(TypeId::of::<T>()) {
+            required_reset::<T>();
+        }
+        let mut guard = self.inner.write();
+        match guard.items.get::<T>()? {
+            Slot::Placeholder { .. } => None,
+            Slot::Filled(_) | Slot::Failed(_) => match &guard.items.remove::<T>() {
+                Some(Slot::Filled(x)) => {
+                    guard.accessed.get_mut().remove(&TypeId::of::<T>());
+                    guard.advance_generation(TypeId::of::<T>());
+                    #[cfg(feature = "async")]
+                    guard.emit(AeroEvent::Removed(type_name::<T>()));
+                    Some(x.clone())
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Remove every resource and cached construction failure, leaving the aerosol
+    /// instance empty. This is useful for reusing a shared instance between test
+    /// cases.
+    ///
+    /// Resources which are currently under construction are left untouched, as the
+    /// thread or task constructing them cannot be interrupted, and will fill their
+    /// slot once construction finishes. Registrations such as lifetimes, aliases and
+    /// factories are also kept, as are the required resources in `R`, so that they
+    /// remain present.
+    pub fn clear(&self) {
+        let mut guard = self.inner.write();
+        let removed = guard.items.remove_where(|slot| {
+            slot.state() != SlotState::Constructing && !R::contains(slot.resource_id())
+        });
+        for slot in &removed {
+            if slot.state() == SlotState::Present {
+                guard.advance_generation(slot.resource_id());
+                #[cfg(feature = "async")]
+                guard.emit(AeroEvent::Removed(slot.type_name()));
+            }
+        }
+        guard.accessed.get_mut().clear();
+        drop(guard);
+        // Resources are dropped without holding the lock, in case their destructors
+        // access this instance.
+        drop(removed);
+    }
+
+    /// Enable or disable tracking of which resources are accessed. Whilst enabled, every
+    /// resource returned from `get()`, `try_get()`, `obtain()` or their async equivalents
+    /// is recorded, so that `unused_resources()` can report resources which were never
+    /// needed. This is useful for trimming resources which are constructed eagerly at
+    /// startup but never used.
+    pub fn track_usage(&self, enabled: bool) {
+        self.inner
+            .read()
+            .track_usage
+            .store(enabled, Ordering::SeqCst);
+    }
+
+    pub(crate) fn mark_accessed<T: Resource>(&self) {
+        self.inner.read().mark_accessed::<T>();
+    }
+
+    /// Names of the fully constructed resources which have not been accessed whilst
+    /// usage tracking was enabled. See `track_usage()`.
+    pub fn unused_resources(&self) -> Vec<&'static str> {
+        let guard = self.inner.read();
+        let accessed = guard.accessed.lock();
+        let mut names: Vec<_> = guard
+            .items
+            .iter()
+            .filter(|slot| slot.state() == SlotState::Present)
+            .filter(|slot| !accessed.contains(&slot.resource_id()))
+            .map(|slot| slot.type_name())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Insert a resource into the collection, replacing any existing instance of `T`,
+    /// which is returned. If `T` is under construction, anyone waiting for it is woken
+    /// with the new value, although the constructed value will replace it once
+    /// construction finishes.
+    pub fn replace<T: Resource>(&self, value: T) -> Option<T> {
+        let mut guard = self.inner.write();
+        guard.release_waiters(guard.items.get::<T>());
+        let old = match &guard.items.remove::<T>() {
+            Some(Slot::Filled(x)) => Some(x.clone()),
+            _ => None,
+        };
+        guard.items.insert(Slot::Filled(value));
+        guard.notify_filled(TypeId::of::<T>());
+        #[cfg(feature = "async")]
+        guard.emit(if old.is_some() {
+            AeroEvent::Replaced(type_name::<T>())
+        } else {
+            AeroEvent::Inserted(type_name::<T>())
+        });
+        old
+    }
+
+    /// Number of times construction of `T` has been attempted in this aerosol instance,
+    /// whether or not construction succeeded. Resources inserted directly are not
+    /// counted. Useful in tests to check that concurrent attempts to obtain a resource
+    /// only construct it once.
+    pub fn construction_count<T: Resource>(&self) -> usize {
+        self.inner
+            .read()
+            .construction_counts
+            .get(&TypeId::of::<T>())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Generation of `T` in this aerosol instance. The generation starts at zero, and
+    /// is advanced whenever `T` is inserted, constructed, replaced or reset, so a
+    /// consumer holding an instance of `T` can cheaply check whether it is stale.
+    /// If `T` is looked up in a parent instance, the parent's generation is returned.
+    pub fn current_generation<T: Resource>(&self) -> u64 {
+        let guard = self.inner.read();
+        if guard.items.get::<T>().is_none() {
+            if let Some(parent) = guard.parent_for::<T>() {
+                return parent.current_generation::<T>();
+            }
+        }
+        guard
+            .generations
+            .get(&TypeId::of::<T>())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Record that construction of `T` is about to begin. Fails if constructing `T` is
+    /// not allowed in the current phase.
+    pub(crate) fn record_construction<T: Resource>(&self) -> Result<(), ObtainError> {
+        let mut guard = self.inner.write();
+        guard.check_phase::<T>()?;
+        *guard
+            .construction_counts
+            .entry(TypeId::of::<T>())
+            .or_default() += 1;
+        guard.record(RecordedEvent::Construct(type_name::<T>()));
+        Ok(())
+    }
+
+    /// Type IDs of every fully constructed resource in this aerosol instance.
+    fn present_ids(&self) -> HashSet<TypeId> {
+        self.inner
+            .read()
+            .items
+            .iter()
+            .filter(|slot| slot.state() == SlotState::Present)
+            .map(|slot| slot.resource_id())
+            .collect()
+    }
+
+    /// Returns true if both aerosol instances contain exactly the same set of fully
+    /// constructed resource types. Resource values are not compared. This is useful in
+    /// tests, eg. to check that `construct_remaining()` produced the expected resources.
+    pub fn same_resources<R2: ResourceList>(&self, other: &Aero<R2>) -> bool {
+        self.shares_storage_with(other) || self.present_ids() == other.present_ids()
+    }
+
+    /// Returns true if both handles refer to the same aerosol instance, in which case
+    /// they share every resource, and a resource constructed through one handle will
+    /// never be constructed again through the other. This is the case for clones and
+    /// type conversions of an `Aero`, but not for scopes created with `scope()`, which
+    /// only share the resources they inherit from their parent.
+    pub fn shares_storage_with<R2: ResourceList>(&self, other: &Aero<R2>) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Convert into a map of type-erased resources, for interoperability with other
+    /// registries. Every fully constructed resource is cloned into the map, keyed on
+    /// its type ID, and can be recovered with `downcast_ref()`. Resources which are
+    /// under construction or failed are not included, and neither are resources
+    /// inherited from a parent.
+    pub fn into_raw(self) -> HashMap<TypeId, Arc<dyn Any + Send + Sync>> {
+        self.inner
+            .read()
+            .items
+            .iter()
+            .filter_map(|slot| Some((slot.resource_id(), slot.to_raw()?)))
+            .collect()
+    }
+
+    /// Get a clone of the resource with the given type ID, if it is fully constructed
+    /// in this aerosol instance. Useful for tools which inspect resources without
+    /// knowing their types at compile time. Resources inherited from a parent are not
+    /// included.
+    pub fn get_any(&self, id: TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.inner.read().items.get_raw(id)?.to_raw()
+    }
+
+    /// Names of every resource type with a slot in this aerosol instance.
+    pub(crate) fn resource_names(&self) -> Vec<&'static str> {
+        self.inner
+            .read()
+            .items
+            .iter()
+            .map(|slot| slot.type_name())
+            .collect()
     }
 
     pub(crate) fn try_get_slot<T: Resource>(&self) -> Option<SlotDesc<T>> {
-        self.inner.read().items.get().map(Slot::desc)
+        let guard = self.inner.read();
+        if guard.items.get::<T>().is_none() {
+            if let Some(alias) = guard.aliases.get::<Alias<T>>().cloned() {
+                // Release the lock so that the alias can wait for the resource it
+                // looks up, if that is under construction.
+                drop(guard);
+                return (alias.get)(self.as_ref()).map(SlotDesc::Filled);
+            }
+        }
+        guard.get_slot()
+    }
+
+    /// Look up `T` in preparation for obtaining it. The common case, where `T` is
+    /// already present in this instance, only acquires the read lock once.
+    pub(crate) fn lookup_for_obtain<T: Resource>(&self) -> Lookup<T> {
+        let id = TypeId::of::<T>();
+        {
+            let guard = self.inner.read();
+            guard.record(RecordedEvent::Obtain(type_name::<T>()));
+            if guard.items.get::<T>().is_none() {
+                if let Some(source) = guard.links.get(&id) {
+                    return Lookup::Linked(source.clone());
+                }
+            }
+            // An instance which was inserted directly is used even if `T` is transient.
+            if !guard.is_expired(id) {
+                if let Some(Slot::Filled(x)) = guard.items.get::<T>() {
+                    return Lookup::Slot(Some(SlotDesc::Filled(x.clone())));
+                }
+            }
+            if guard.lifetime(id) == Lifetime::Transient {
+                return Lookup::Transient;
+            }
+        }
+        self.expire_if_stale::<T>();
+        Lookup::Slot(self.try_get_slot())
+    }
+
+    /// Remove `T` if its TTL has elapsed, so that it will be constructed again. If
+    /// several threads or tasks notice at once, only the first removes it, and the
+    /// rest will wait on the placeholder of whichever constructs it first.
+    pub(crate) fn expire_if_stale<T: Resource>(&self) {
+        let id = TypeId::of::<T>();
+        if !self.inner.read().is_expired(id) {
+            return;
+        }
+        let mut guard = self.inner.write();
+        if guard.is_expired(id) && matches!(guard.items.get::<T>(), Some(Slot::Filled(_))) {
+            guard.items.remove::<T>();
+            guard.filled_at.remove(&id);
+            guard.advance_generation(id);
+            #[cfg(feature = "async")]
+            guard.emit(AeroEvent::Removed(type_name::<T>()));
+        }
+    }
+
+    /// Look up any resources not present in `self` in `parent` instead.
+    pub(crate) fn set_parent(&self, parent: Aero) {
+        self.inner.write().parent = Some(parent);
     }
-    pub(crate) fn poll_for_slot<T: Resource, C: Into<ThreadOrWaker>>(
+
+    /// Prepare to construct `T` in `self`, with any other resources it needs looked up
+    /// in `base` if they are not overridden in `self`. Panics if `self` already contains
+    /// `T`, or has already been layered over an instance other than `base`.
+    pub(crate) fn layer_overrides<T: Resource>(&self, base: Aero, owner: ThreadOrWaker) {
+        let mut guard = self.inner.write();
+        if guard.items.get::<T>().is_some() {
+            drop(guard);
+            duplicate_resource::<T>();
+        }
+        match &guard.parent {
+            Some(parent) if !Arc::ptr_eq(&parent.inner, &base.inner) => {
+                drop(guard);
+                panic!("Overrides are already layered over a different aerosol instance");
+            }
+            _ => guard.parent = Some(base),
+        }
+        guard.items.insert(Slot::<T>::placeholder(owner));
+        #[cfg(feature = "async")]
+        guard.emit(AeroEvent::ConstructionStarted(type_name::<T>()));
+    }
+
+    /// Poll for the slot for `T` to not have a placeholder. If the slot is filled,
+    /// `map` is used to extract the result from the resource, which allows callers
+    /// that only care whether the slot is filled to avoid cloning the resource.
+    ///
+    /// Only a read lock is needed unless the slot is vacant, so waiting on a resource
+    /// under construction does not hold up access to unrelated resources. Inserting a
+    /// placeholder into a vacant slot takes the write lock for the whole map.
+    pub(crate) fn poll_for_slot<T: Resource, U, C: Into<ThreadOrWaker>>(
         &self,
-        wait_index: &mut Option<usize>,
+        registration: &mut Option<WaitRegistration>,
         thread_or_waker_fn: impl Fn() -> C,
         insert_placeholder: bool,
-    ) -> Poll<Option<T>> {
+        map: fn(&T) -> U,
+    ) -> Poll<SlotResult<U>> {
+        {
+            let guard = self.inner.read();
+            if let Some(slot) = guard.items.get::<T>() {
+                return guard.poll_occupied(slot, registration, &thread_or_waker_fn, map);
+            }
+        }
         let mut guard = self.inner.write();
-        match guard.items.entry::<Slot<T>>() {
-            Entry::Occupied(mut occ) => match occ.get_mut() {
-                Slot::Filled(x) => Poll::Ready(Some(x.clone())),
-                Slot::Placeholder { owner, waiting } => {
-                    let current = thread_or_waker_fn().into();
-                    if current == *owner {
-                        cyclic_resource::<T>()
-                    }
-                    if let Some(idx) = *wait_index {
-                        waiting[idx] = current;
-                    } else {
-                        *wait_index = Some(waiting.len());
-                        waiting.push(current);
+        let parent = guard.parent_for::<T>().cloned();
+        let res = match guard.items.entry::<T>() {
+            // Filled or replaced by a placeholder whilst we were not holding the lock.
+            SlotEntry::Occupied(_) => {
+                let guard = RwLockWriteGuard::downgrade(guard);
+                let slot = guard.items.get::<T>().expect("Slot was just occupied");
+                return guard.poll_occupied(slot, registration, &thread_or_waker_fn, map);
+            }
+            SlotEntry::Vacant(vac) => {
+                if let Some(SlotDesc::Filled(x)) = parent.as_ref().and_then(Aero::try_get_slot) {
+                    Poll::Ready(Ok(Some(map(&x))))
+                } else {
+                    if insert_placeholder {
+                        vac.insert(Slot::placeholder(thread_or_waker_fn().into()));
+                        #[cfg(feature = "async")]
+                        guard.emit(AeroEvent::ConstructionStarted(type_name::<T>()));
                     }
-                    Poll::Pending
+                    Poll::Ready(Ok(None))
                 }
-            },
-            Entry::Vacant(vac) => {
-                if insert_placeholder {
-                    vac.insert(Slot::Placeholder {
-                        owner: thread_or_waker_fn().into(),
-                        waiting: Vec::new(),
-                    });
-                }
-                Poll::Ready(None)
+            }
+        };
+        guard.finish_wait(registration);
+        res
+    }
+
+    /// Forget about a wait which will never be polled again.
+    #[cfg(feature = "async")]
+    pub(crate) fn cancel_wait(&self, registration: WaitRegistration) {
+        self.inner
+            .read()
+            .wait_graph
+            .lock()
+            .remove(&registration.waiter);
+    }
+
+    /// Every instance and resource type which `try_get_slot::<T>()` may look in, so
+    /// that a subscriber to `T` can be woken when any of them is filled.
+    #[cfg(feature = "async")]
+    pub(crate) fn subscription_sources<T: Resource>(&self, out: &mut Vec<(Aero, TypeId)>) {
+        let id = TypeId::of::<T>();
+        let mut current: Aero = self.as_ref().clone();
+        loop {
+            if out
+                .iter()
+                .any(|(aero, other)| *other == id && Arc::ptr_eq(&aero.inner, &current.inner))
+            {
+                return;
+            }
+            out.push((current.clone(), id));
+            let guard = current.inner.read();
+            let alias = guard.aliases.get::<Alias<T>>().cloned();
+            let next = guard.parent_for::<T>().cloned();
+            drop(guard);
+            if let Some(alias) = alias {
+                (alias.sources)(&current, out);
+            }
+            match next {
+                Some(next) => current = next,
+                None => return,
+            }
+        }
+    }
+
+    /// Wake `waker` when the resource with this type ID is filled in this instance.
+    #[cfg(feature = "async")]
+    pub(crate) fn add_subscriber(&self, id: TypeId, subscription: u64, waker: &Waker) {
+        self.inner
+            .write()
+            .subscribers
+            .entry(id)
+            .or_default()
+            .insert(subscription, waker.clone());
+    }
+
+    /// Undo `add_subscriber()`, if the subscriber has not already been woken.
+    #[cfg(feature = "async")]
+    pub(crate) fn remove_subscriber(&self, id: TypeId, subscription: u64) {
+        let mut guard = self.inner.write();
+        if let Some(wakers) = guard.subscribers.get_mut(&id) {
+            wakers.remove(&subscription);
+            if wakers.is_empty() {
+                guard.subscribers.remove(&id);
             }
         }
     }
 
     pub(crate) fn fill_placeholder<T: Resource>(&self, value: T) {
-        self.inner.write().items.insert(Slot::Filled(value));
+        let mut guard = self.inner.write();
+        guard.check_stored(&value);
+        guard.release_waiters(guard.items.get::<T>());
+        guard.items.insert(Slot::Filled(value));
+        guard.notify_filled(TypeId::of::<T>());
+        #[cfg(feature = "async")]
+        guard.emit(AeroEvent::ConstructionFinished(type_name::<T>()));
     }
     pub(crate) fn clear_placeholder<T: Resource>(&self) {
-        self.inner.write().items.remove::<Slot<T>>();
+        let mut guard = self.inner.write();
+        if let slot @ Some(Slot::Placeholder { .. }) = guard.items.get::<T>() {
+            guard.release_waiters(slot);
+            guard.items.remove::<T>();
+            #[cfg(feature = "async")]
+            guard.emit(AeroEvent::ConstructionFailed(type_name::<T>()));
+        }
     }
 }
 
@@ -230,7 +1288,25 @@ impl<H: Resource, T: ResourceList> From<Aero<HCons<H, T>>> for Aero {
 
 #[cfg(test)]
 mod tests {
-    use crate::Aero;
+    use std::{
+        any::{Any, TypeId},
+        collections::HashMap,
+        sync::Arc,
+        task::Poll,
+        thread,
+    };
+
+    use crate::{slot::ThreadOrWaker, Aero, Constructible, Resource, ResourceList};
+
+    use super::Slot;
+
+    fn insert_placeholder<T: Resource>(state: &Aero<impl ResourceList>, owner: ThreadOrWaker) {
+        state
+            .inner
+            .write()
+            .items
+            .insert(Slot::<T>::placeholder(owner));
+    }
 
     #[test]
     fn create() {
@@ -245,12 +1321,72 @@ mod tests {
         state.insert(42);
     }
 
+    #[derive(Debug, Clone)]
+    struct SendgridMailer;
+
+    impl Constructible for SendgridMailer {
+        type Error = std::convert::Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self)
+        }
+
+        fn after_construction(
+            _this: &(dyn Any + Send + Sync),
+            aero: &Aero,
+        ) -> Result<(), Self::Error> {
+            aero.insert("sendgrid");
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "whilst constructing `aerosol::state::tests::SendgridMailer`")]
+    fn duplicate_after_construction() {
+        let state = Aero::new().with("postmark");
+        state.obtain::<SendgridMailer>();
+    }
+
+    #[test]
+    fn insert_if_absent() {
+        let state = Aero::new();
+        assert!(state.insert_if_absent(1));
+        assert!(!state.insert_if_absent(2));
+        assert_eq!(state.try_get::<i32>(), Some(1));
+    }
+
     #[test]
     fn default() {
         let state: Aero![i32] = Aero::default();
         state.insert("Hello, world!");
     }
 
+    #[test]
+    fn raw_round_trip() {
+        let state = Aero::new().with(42).with("Hello, world!");
+        let raw = state.into_raw();
+        assert_eq!(raw.len(), 2);
+        assert_eq!(raw[&TypeId::of::<i32>()].downcast_ref::<i32>(), Some(&42));
+
+        let state = Aero::from_raw(raw);
+        assert_eq!(state.try_get::<i32>(), Some(42));
+        state.replace(13);
+        assert_eq!(state.try_get::<i32>(), Some(13));
+        assert_eq!(state.try_get::<&str>(), Some("Hello, world!"));
+        assert_eq!(state.into_raw().len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_raw_wrong_type() {
+        let mut raw = HashMap::new();
+        raw.insert(
+            TypeId::of::<i32>(),
+            Arc::new(42u8) as Arc<dyn Any + Send + Sync>,
+        );
+        Aero::from_raw(raw);
+    }
+
     #[test]
     fn convert() {
         let state: Aero![i32, String, f32] = Aero::default();
@@ -265,4 +1401,231 @@ mod tests {
         state.insert("Hello, world!");
         let _state2: Aero![&str, f32] = state.assert::<&str>().into();
     }
+
+    #[test]
+    fn with_arc() {
+        let state = Aero::new().with_arc(42);
+        state.insert_arc("Hello, world!");
+        assert_eq!(*state.get::<Arc<i32>, _>(), 42);
+        assert_eq!(*state.try_get::<Arc<&str>>().unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn read() {
+        let state = Aero::new().with(42).with("Hello, world!");
+        let guard = state.read();
+        assert_eq!(guard.get::<i32>(), Some(42));
+        assert_eq!(guard.get::<&str>(), Some("Hello, world!"));
+        assert!(!guard.has::<f32>());
+    }
+
+    #[test]
+    fn read_follows_aliases_and_parents() {
+        let host = Aero::new().with(7u8);
+        let parent = Aero::new().with("Hello, world!");
+        let state = parent.scope().with(42);
+        state.alias::<i32, i64>(i64::from);
+        state.link::<u8, _>(&host);
+
+        let guard = state.read();
+        assert_eq!(guard.get::<i64>(), Some(42));
+        assert_eq!(guard.get::<&str>(), Some("Hello, world!"));
+        assert_eq!(guard.get::<u8>(), Some(7));
+        assert!(guard.has::<i64>() && guard.has::<&str>() && guard.has::<u8>());
+        drop(guard);
+        assert!(state.has::<i64>());
+        let (a, b) = state.batch(|ctx| (ctx.get::<i64>(), ctx.has::<u8>()));
+        assert_eq!((a, b), (Some(42), true));
+    }
+
+    #[test]
+    fn wait_for_recreated_placeholder() {
+        let other = || thread::spawn(thread::current).join().unwrap();
+        let (owner, first_waiter) = (other(), other());
+        let state = Aero::new();
+        insert_placeholder::<i32>(&state, owner.clone().into());
+
+        let mut first = None;
+        let poll =
+            state.poll_for_slot::<i32, _, _>(&mut first, || first_waiter.clone(), true, |x| *x);
+        assert!(poll.is_pending());
+        let mut second = None;
+        let poll = state.poll_for_slot::<i32, _, _>(&mut second, thread::current, true, |x| *x);
+        assert!(poll.is_pending());
+
+        // Construction fails, and someone else starts constructing the resource
+        // before the second waiter gets to poll again.
+        state.clear_placeholder::<i32>();
+        insert_placeholder::<i32>(&state, owner.into());
+        let poll = state.poll_for_slot::<i32, _, _>(&mut second, thread::current, true, |x| *x);
+        assert!(poll.is_pending());
+
+        state.fill_placeholder(42);
+        let poll = state.poll_for_slot::<i32, _, _>(&mut second, thread::current, true, |x| *x);
+        assert!(matches!(poll, Poll::Ready(Ok(Some(42)))));
+    }
+
+    #[test]
+    fn no_deadlock_on_filled_placeholder() {
+        let other = || thread::spawn(thread::current).join().unwrap();
+        let (first, second) = (other(), other());
+        let state = Aero::new();
+        // The first thread is constructing an `i32`, which needs a `u8` being
+        // constructed by the second thread.
+        insert_placeholder::<i32>(&state, first.clone().into());
+        insert_placeholder::<u8>(&state, second.clone().into());
+        let mut registration = None;
+        let poll =
+            state.poll_for_slot::<u8, _, _>(&mut registration, || first.clone(), true, |x| *x);
+        assert!(poll.is_pending());
+
+        // The second thread finishes the `u8` and then needs the `i32` before the
+        // first thread has been woken.
+        state.fill_placeholder(7u8);
+        let mut registration = None;
+        let poll =
+            state.poll_for_slot::<i32, _, _>(&mut registration, || second.clone(), true, |x| *x);
+        assert!(poll.is_pending());
+    }
+
+    #[test]
+    fn batch() {
+        let state = Aero::new().with(42).with("Hello, world!");
+        let (a, b) = state.batch(|ctx| (ctx.get::<i32>(), ctx.get::<&str>()));
+        assert_eq!(a, Some(42));
+        assert_eq!(b, Some("Hello, world!"));
+    }
+
+    #[test]
+    fn with_defaults_for() {
+        let state = Aero::new().with("hi");
+        state.insert(42);
+        let state: Aero![i32, String, &str] =
+            state.with_defaults_for::<frunk::HList![i32, String]>();
+        assert_eq!(state.get::<i32, _>(), 42);
+        assert_eq!(state.get::<String, _>(), "");
+    }
+
+    #[test]
+    fn debug() {
+        let state = Aero::new().with(42).with("secret");
+        assert_eq!(
+            format!("{:?}", state),
+            "Aero { &str: Present, i32: Present }"
+        );
+    }
+
+    #[test]
+    fn unused_resources() {
+        let state: Aero = Aero::new().with(42).with("unused").into();
+        state.track_usage(true);
+        state.insert(1.0f32);
+        assert_eq!(state.try_get::<i32>(), Some(42));
+        assert_eq!(state.unused_resources(), vec!["&str", "f32"]);
+        state.reset::<i32>();
+        state.insert(7);
+        assert_eq!(state.unused_resources(), vec!["&str", "f32", "i32"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Strict checks: attempted to store a type-erased")]
+    fn strict_checks() {
+        let state = Aero::new().with_strict_checks().with(42);
+        state.insert(Arc::new(42) as Arc<dyn std::any::Any + Send + Sync>);
+    }
+
+    #[test]
+    fn strict_checks_matching_types() {
+        // Resources stored as their own type pass, however they are stored.
+        let state = Aero::new().with_strict_checks().with(42);
+        state.insert(Arc::new(7u8));
+        state.replace(43);
+        state.obtain::<SendgridMailer>();
+        assert_eq!(state.try_get::<i32>(), Some(43));
+        assert_eq!(state.try_get::<Arc<u8>>().as_deref(), Some(&7));
+        assert_eq!(state.try_get::<&str>(), Some("sendgrid"));
+    }
+
+    #[test]
+    fn same_resources() {
+        let a = Aero::new().with(42).with("hello");
+        let b = Aero::new().with("world").with(7);
+        assert!(a.same_resources(&b));
+        assert!(a.same_resources(&a));
+        b.insert(1.0f32);
+        assert!(!a.same_resources(&b));
+    }
+
+    #[test]
+    fn clear() {
+        let state: Aero = Aero::new().with(42).with("hello").into();
+        insert_placeholder::<u8>(&state, thread::current().into());
+        state.clear();
+        assert!(!state.has::<i32>());
+        assert!(!state.has::<&str>());
+        assert_eq!(state.current_generation::<i32>(), 2);
+        assert_eq!(format!("{:?}", state), "Aero { u8: Constructing }");
+        state.insert(7);
+        assert_eq!(state.try_get::<i32>(), Some(7));
+    }
+
+    #[test]
+    fn clear_required() {
+        let state = Aero::new().with(42).with("hello");
+        state.insert(1.0f32);
+        state.clear();
+        assert_eq!(state.get::<i32, _>(), 42);
+        assert_eq!(state.get::<&str, _>(), "hello");
+        assert!(!state.has::<f32>());
+    }
+
+    mod first {
+        #[derive(Clone)]
+        pub struct Config;
+    }
+
+    mod second {
+        #[derive(Clone)]
+        pub struct Config;
+    }
+
+    #[test]
+    #[should_panic(expected = "Did you mean `aerosol::state::tests::first::Config`?")]
+    fn assert_suggestion() {
+        let state = Aero::new().with(first::Config);
+        state.assert::<second::Config>();
+    }
+
+    #[test]
+    fn shares_storage_with() {
+        let state = Aero::new().with(42);
+        let typed: Aero![i32] = state.clone().try_into().unwrap();
+        assert!(state.shares_storage_with(&typed));
+        assert!(!state.shares_storage_with(&state.scope()));
+        assert!(!state.shares_storage_with(&Aero::new().with(42)));
+    }
+
+    #[test]
+    fn missing_for() {
+        let state = Aero::new().with(42);
+        assert_eq!(
+            state.missing_for::<frunk::HList![i32, String, f32]>(),
+            [std::any::type_name::<String>(), "f32"]
+        );
+        assert!(state.missing_for::<frunk::HList![i32]>().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Required resources do not exist: `i32`")]
+    fn build_missing() {
+        let state = Aero::new().with(42).with("Hello, world!");
+        state.as_ref::<frunk::HNil, _>().reset::<i32>();
+        state.build();
+    }
+
+    #[test]
+    #[should_panic(expected = "Resource `i32` is required, so cannot be reset")]
+    fn reset_required() {
+        Aero::new().with(42).reset::<i32>();
+    }
 }
@@ -0,0 +1,149 @@
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+};
+
+use crate::{
+    resource::{ObtainError, ObtainErrorKind, Resource, ResourceList},
+    state::Aero,
+};
+
+/// Construction failures recorded for a single resource type. See
+/// `Aero::failure_stats()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureStat {
+    type_name: &'static str,
+    count: usize,
+    counts: HashMap<ObtainErrorKind, usize>,
+    last_error: String,
+    last_kind: ObtainErrorKind,
+}
+
+impl FailureStat {
+    /// Name of the resource type.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+    /// Number of times construction of this resource has failed.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// Number of times construction of this resource has failed with an error of the
+    /// given kind, eg. to tell a missing dependency apart from a failed connection.
+    pub fn count_of(&self, kind: ObtainErrorKind) -> usize {
+        self.counts.get(&kind).copied().unwrap_or_default()
+    }
+    /// The error returned by the most recent failed construction.
+    pub fn last_error(&self) -> &str {
+        &self.last_error
+    }
+    /// The kind of the error returned by the most recent failed construction.
+    pub fn last_kind(&self) -> ObtainErrorKind {
+        self.last_kind
+    }
+}
+
+pub(crate) type FailureStats = HashMap<TypeId, FailureStat>;
+
+impl<R: ResourceList> Aero<R> {
+    /// Classify an error returned from the constructor for `T`, and record the failure.
+    pub(crate) fn construct_failed<T: Resource>(
+        &self,
+        error: impl Into<anyhow::Error>,
+    ) -> ObtainError {
+        let error = ObtainError::from_construct_error(error);
+        let mut guard = self.inner.write();
+        let stat = guard
+            .failures
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| FailureStat {
+                type_name: type_name::<T>(),
+                count: 0,
+                counts: HashMap::new(),
+                last_error: String::new(),
+                last_kind: error.kind(),
+            });
+        stat.count += 1;
+        *stat.counts.entry(error.kind()).or_default() += 1;
+        stat.last_error = error.to_string();
+        stat.last_kind = error.kind();
+        error
+    }
+
+    /// Construction failures recorded in this aerosol instance, for each resource type
+    /// which has failed to construct at least once, sorted by type name. Useful for
+    /// reporting the health of an application, eg. "`DbPool` failed to construct 5
+    /// times, last error: connection refused".
+    pub fn failure_stats(&self) -> Vec<FailureStat> {
+        let mut stats: Vec<_> = self.inner.read().failures.values().cloned().collect();
+        stats.sort_by_key(|stat| stat.type_name);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::anyhow;
+
+    use crate::{Aero, Constructible, MissingResource, ObtainErrorKind};
+
+    #[derive(Debug, Clone)]
+    struct DummyFailing;
+
+    impl Constructible for DummyFailing {
+        type Error = anyhow::Error;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Err(anyhow!("connection refused"))
+        }
+    }
+
+    #[test]
+    fn failure_stats() {
+        let state = Aero::new();
+        assert!(state.failure_stats().is_empty());
+        for _ in 0..2 {
+            assert!(state.try_obtain::<DummyFailing>().is_err());
+        }
+        assert!(state.try_init::<DummyFailing>().is_err());
+        let stats = state.failure_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].count(), 3);
+        assert_eq!(stats[0].last_error(), "connection refused");
+        assert!(stats[0].type_name().ends_with("DummyFailing"));
+        assert_eq!(stats[0].count_of(ObtainErrorKind::Failed), 3);
+        assert_eq!(stats[0].last_kind(), ObtainErrorKind::Failed);
+    }
+
+    #[derive(Debug, Clone)]
+    struct DummyFlaky;
+
+    static FLAKY_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    impl Constructible for DummyFlaky {
+        type Error = anyhow::Error;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            match FLAKY_ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(anyhow!("connection refused")),
+                _ => Err(MissingResource::new::<u32>().into()),
+            }
+        }
+    }
+
+    #[test]
+    fn failure_stats_by_kind() {
+        let state = Aero::new();
+        for _ in 0..3 {
+            assert!(state.try_obtain::<DummyFlaky>().is_err());
+        }
+        let stats = state.failure_stats();
+        assert_eq!(stats[0].count(), 3);
+        assert_eq!(stats[0].count_of(ObtainErrorKind::Failed), 1);
+        assert_eq!(stats[0].count_of(ObtainErrorKind::Missing), 2);
+        assert_eq!(stats[0].count_of(ObtainErrorKind::Cancelled), 0);
+        assert_eq!(stats[0].last_kind(), ObtainErrorKind::Missing);
+    }
+}
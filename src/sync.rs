@@ -3,8 +3,8 @@ use std::{task::Poll, thread};
 use frunk::hlist::Plucker;
 
 use crate::{
-    resource::{unwrap_resource, Resource, ResourceList},
-    slot::SlotDesc,
+    resource::{unwrap_resource, MissingResource, Resource, ResourceList},
+    slot::{SlotDesc, SlotResult},
     state::Aero,
 };
 
@@ -21,10 +21,27 @@ pub fn safe_park() {
 impl<R: ResourceList> Aero<R> {
     /// Synchronously wait for the slot for `T` to not have a placeholder.
     /// Returns immediately if there is no `T` present, or if `T`'s slot is filled.
-    pub(crate) fn wait_for_slot<T: Resource>(&self, insert_placeholder: bool) -> Option<T> {
-        let mut wait_index = None;
+    pub(crate) fn wait_for_slot<T: Resource>(&self, insert_placeholder: bool) -> SlotResult<T> {
+        self.wait_for_slot_with(insert_placeholder, T::clone)
+    }
+
+    /// Like `wait_for_slot`, but only reports whether the slot is filled, without
+    /// cloning the resource.
+    pub(crate) fn wait_for_presence<T: Resource>(
+        &self,
+        insert_placeholder: bool,
+    ) -> SlotResult<()> {
+        self.wait_for_slot_with::<T, _>(insert_placeholder, |_| ())
+    }
+
+    fn wait_for_slot_with<T: Resource, U>(
+        &self,
+        insert_placeholder: bool,
+        map: fn(&T) -> U,
+    ) -> SlotResult<U> {
+        let mut registration = None;
         loop {
-            match self.poll_for_slot(&mut wait_index, thread::current, insert_placeholder) {
+            match self.poll_for_slot(&mut registration, thread::current, insert_placeholder, map) {
                 Poll::Pending => safe_park(),
                 Poll::Ready(x) => break x,
             }
@@ -34,17 +51,38 @@ impl<R: ResourceList> Aero<R> {
     /// Tries to get an instance of `T` from the AppState. Returns `None` if there is no such instance.
     /// This function does not attempt to construct `T` if it does not exist.
     pub fn try_get<T: Resource>(&self) -> Option<T> {
-        match self.try_get_slot()? {
+        let x = match self.try_get_slot()? {
             SlotDesc::Filled(x) => Some(x),
-            SlotDesc::Placeholder => self.wait_for_slot::<T>(false),
-        }
+            SlotDesc::Placeholder => self.wait_for_slot::<T>(false).ok().flatten(),
+            SlotDesc::Failed(_) => None,
+        }?;
+        self.mark_accessed::<T>();
+        Some(x)
     }
     /// Get an instance of `T` from the AppState which is statically known to be present.
     pub fn get<T: Resource, I>(&self) -> T
     where
         R: Plucker<T, I>,
     {
-        unwrap_resource(self.try_get())
+        unwrap_resource(self, self.try_get())
+    }
+    /// Get an instance of `T` from the AppState which is statically known to be present,
+    /// returning an error rather than panicking if it is missing after all. Useful for
+    /// defensive code paths.
+    pub fn try_get_required<T: Resource, I>(&self) -> Result<T, MissingResource>
+    where
+        R: Plucker<T, I>,
+    {
+        self.try_get().ok_or_else(MissingResource::new::<T>)
+    }
+    /// Get every required resource at once. The result can be converted into a tuple
+    /// or an `HList` of the required resources, eg.
+    /// `let (a, b): (A, B) = state.get_all();` for an `Aero![A, B]`.
+    pub fn get_all<T>(&self) -> T
+    where
+        R: Into<T>,
+    {
+        R::get_all(self).into()
     }
 }
 
@@ -58,6 +96,17 @@ mod tests {
         assert_eq!(state.get::<i32, _>(), 42);
     }
 
+    #[test]
+    fn try_get_required() {
+        let state = Aero::new().with(42);
+        assert_eq!(state.try_get_required::<i32, _>(), Ok(42));
+        state.as_ref::<frunk::HNil, _>().reset::<i32>();
+        assert_eq!(
+            state.try_get_required::<i32, _>(),
+            Err(MissingResource::new::<i32>())
+        );
+    }
+
     #[test]
     fn try_get_some() {
         let state = Aero::new().with(42);
@@ -69,4 +118,20 @@ mod tests {
         let state = Aero::new().with("Hello");
         assert_eq!(state.try_get::<i32>(), None);
     }
+
+    #[test]
+    fn get_all() {
+        let state = Aero::new().with(42).with("Hello");
+        let (a, b) = state.get_all();
+        assert_eq!(a, "Hello");
+        assert_eq!(b, 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "Resource `i32` does not exist")]
+    fn get_all_removed() {
+        let state = Aero::new().with(42).with("Hello");
+        state.as_ref::<frunk::HNil, _>().reset::<i32>();
+        let _: (&str, i32) = state.get_all();
+    }
 }
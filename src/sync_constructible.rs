@@ -1,19 +1,58 @@
-use std::{any::Any, marker::PhantomData, sync::Arc};
+use std::{
+    any::{type_name, Any, TypeId},
+    marker::PhantomData,
+    panic,
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
 
 use frunk::{hlist::Sculptor, HCons, HNil};
+use parking_lot::Mutex;
 
 use crate::{
-    resource::{unwrap_constructed, unwrap_constructed_hlist, Resource, ResourceList},
+    cancel::CancelToken,
+    resource::{
+        missing_coercion, unwrap_constructed, unwrap_constructed_hlist, MissingResource,
+        ObtainError, Resource, ResourceList,
+    },
     slot::SlotDesc,
-    state::Aero,
+    state::{construction_stack, inherit_construction_stack, Aero, Lookup, PlaceholderGuard},
 };
 
 /// Implemented for values which can be constructed from other resources.
 pub trait Constructible: Sized + Any + Send + Sync {
     /// Error type for when resource fails to be constructed.
     type Error: Into<anyhow::Error> + Send + Sync;
+    /// Name used to identify this resource in the `tracing` span emitted whilst it is
+    /// being constructed, eg. `"app::db"`. Defaults to the full name of the type.
+    const TRACING_NAME: Option<&'static str> = None;
+    /// How long construction of this resource may take before `try_obtain()` gives up
+    /// with `ObtainError::ConstructTimedOut`. When set, the constructor runs on a
+    /// separate thread so that the caller can stop waiting for it. Defaults to no
+    /// timeout.
+    ///
+    /// Threads cannot be stopped, so after a timeout the constructor is left running in
+    /// the background. Rather than starting another thread, the next attempt to
+    /// construct the resource waits for that constructor to finish, and uses its result
+    /// if it arrives in time. A stuck constructor therefore occupies at most one thread
+    /// per aerosol instance, however often construction is retried. Any other resources
+    /// it constructs in the meantime are stored as usual.
+    ///
+    /// The timeout is also enforced by `try_obtain_async()`, but only if a timer is
+    /// available, and only at the points where the constructor yields. A synchronous
+    /// constructor never yields, so it blocks the executor and the timeout cannot fire
+    /// until it returns. Use `try_obtain_blocking_async()` to construct such resources
+    /// from async code with the timeout enforced.
+    const CONSTRUCT_TIMEOUT: Option<Duration> = None;
     /// Construct the resource with the provided application state.
     fn construct(aero: &Aero) -> Result<Self, Self::Error>;
+    /// Construct the resource with access to the context it is being constructed in.
+    /// Override this instead of `construct()` if the context is needed, eg. to log
+    /// how the resource was reached. Defaults to calling `construct()`.
+    fn construct_in_ctx(aero: &Aero, _ctx: &ConstructCtx) -> Result<Self, Self::Error> {
+        Self::construct(aero)
+    }
 
     /// Called after construction with the concrete resource to allow the callee
     /// to provide additional resources. Can be used by eg. an `Arc<Foo>` to also
@@ -26,10 +65,62 @@ pub trait Constructible: Sized + Any + Send + Sync {
     }
 }
 
+/// Context in which a resource is being constructed. See `Constructible::construct_in_ctx()`.
+#[derive(Debug, Clone)]
+pub struct ConstructCtx {
+    chain: Vec<&'static str>,
+    cancel: CancelToken,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl ConstructCtx {
+    pub(crate) fn current<R: ResourceList>(aero: &Aero<R>) -> Self {
+        Self {
+            chain: construction_stack(),
+            cancel: aero.cancel_token(),
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+        }
+    }
+
+    /// Names of the resources being constructed on this thread, outermost first. The
+    /// last entry is the resource currently being constructed.
+    pub fn chain(&self) -> &[&'static str] {
+        &self.chain
+    }
+
+    /// Signal raised by `Aero::cancel_constructions()`. Long-running constructors
+    /// can check or wait on this to stop early, eg. during shutdown.
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+
+    /// Returns `ObtainError::Cancelled` for the resource being constructed if
+    /// construction has been cancelled, so that a constructor can bail out with `?`.
+    pub fn check_cancelled(&self) -> Result<(), ObtainError> {
+        match (self.cancel.is_cancelled(), self.chain.last()) {
+            (true, Some(name)) => Err(ObtainError::Cancelled(name)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The `construct` span for this resource. Requires feature `tracing`.
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+}
+
 /// Automatically implemented for values which can be indirectly constructed from other resources.
 pub trait IndirectlyConstructible: Sized + Any + Send + Sync {
     /// Error type for when resource fails to be constructed.
     type Error: Into<anyhow::Error> + Send + Sync;
+    /// Name used to identify this resource in `tracing` spans. See
+    /// `Constructible::TRACING_NAME`.
+    const TRACING_NAME: Option<&'static str> = None;
+    /// Construction timeout. See `Constructible::CONSTRUCT_TIMEOUT`.
+    const CONSTRUCT_TIMEOUT: Option<Duration> = None;
     /// Construct the resource with the provided application state.
     fn construct(aero: &Aero) -> Result<Self, Self::Error>;
     /// Called after construction with the concrete resource to allow the callee
@@ -45,9 +136,11 @@ pub trait IndirectlyConstructible: Sized + Any + Send + Sync {
 
 impl<T: Constructible> IndirectlyConstructible for T {
     type Error = T::Error;
+    const TRACING_NAME: Option<&'static str> = T::TRACING_NAME;
+    const CONSTRUCT_TIMEOUT: Option<Duration> = T::CONSTRUCT_TIMEOUT;
 
     fn construct(aero: &Aero) -> Result<Self, Self::Error> {
-        let res = <T as Constructible>::construct(aero)?;
+        let res = <T as Constructible>::construct_in_ctx(aero, &ConstructCtx::current(aero))?;
         <T as Constructible>::after_construction(&res, aero)?;
         Ok(res)
     }
@@ -62,6 +155,8 @@ macro_rules! impl_constructible {
         $(
             impl<$t: IndirectlyConstructible> IndirectlyConstructible for $x {
                 type Error = $t::Error;
+                const TRACING_NAME: Option<&'static str> = $t::TRACING_NAME;
+                const CONSTRUCT_TIMEOUT: Option<Duration> = $t::CONSTRUCT_TIMEOUT;
 
                 fn construct(aero: &Aero) -> Result<Self, Self::Error> {
                     let res = $y($t::construct(aero)?);
@@ -79,11 +174,46 @@ macro_rules! impl_constructible {
 impl_constructible! {
     <T>;
     Arc<T>: Arc::new;
+}
+#[cfg(feature = "std-locks")]
+impl_constructible! {
+    <T>;
     std::sync::Mutex<T>: std::sync::Mutex::new;
-    parking_lot::Mutex<T>: parking_lot::Mutex::new;
     std::sync::RwLock<T>: std::sync::RwLock::new;
+}
+#[cfg(feature = "parking-lot")]
+impl_constructible! {
+    <T>;
+    parking_lot::Mutex<T>: parking_lot::Mutex::new;
     parking_lot::RwLock<T>: parking_lot::RwLock::new;
 }
+#[cfg(feature = "watch")]
+impl_constructible! {
+    <T>;
+    crate::Watchable<T>: crate::Watchable::new;
+}
+
+// `Box` is a fundamental type, so implementing `IndirectlyConstructible` for it would
+// overlap with the blanket implementation above. Implementing `Constructible` instead
+// also makes `Box<T>` asynchronously constructible, via the blanket implementation of
+// `AsyncConstructible` for synchronously constructible types.
+impl<T: Constructible> Constructible for Box<T> {
+    type Error = T::Error;
+    const TRACING_NAME: Option<&'static str> = T::TRACING_NAME;
+    const CONSTRUCT_TIMEOUT: Option<Duration> = T::CONSTRUCT_TIMEOUT;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        T::construct(aero).map(Box::new)
+    }
+
+    fn construct_in_ctx(aero: &Aero, ctx: &ConstructCtx) -> Result<Self, Self::Error> {
+        T::construct_in_ctx(aero, ctx).map(Box::new)
+    }
+
+    fn after_construction(this: &(dyn Any + Send + Sync), aero: &Aero) -> Result<(), Self::Error> {
+        T::after_construction(this, aero)
+    }
+}
 
 /// Implemented for resources which can be constructed from other resources.
 /// Do not implement this trait directly, instead implement `Constructible` and ensure
@@ -93,74 +223,310 @@ impl<T: Resource + IndirectlyConstructible> ConstructibleResource for T {}
 
 /// Automatically implemented for resource lists where every resource can be constructed.
 pub trait ConstructibleResourceList: ResourceList {
-    /// Construct every resource in this list in the provided aerosol instance
-    fn construct<R: ResourceList>(aero: &Aero<R>) -> anyhow::Result<()>;
+    /// Construct every resource in this list in the provided aerosol instance. `on_each`
+    /// is called with the name of each resource before it is initialized.
+    fn construct<R: ResourceList>(
+        aero: &Aero<R>,
+        on_each: &mut dyn FnMut(&'static str),
+    ) -> anyhow::Result<()>;
+    /// Append an initializer for every resource in this list to `out`, so that they
+    /// can be constructed in an order determined at runtime.
+    #[doc(hidden)]
+    fn initializers(out: &mut Vec<Initializer>);
+}
+
+/// Type-erased initializer for a single resource, see `ConstructibleResourceList::initializers()`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct Initializer {
+    pub(crate) id: TypeId,
+    pub(crate) name: &'static str,
+    pub(crate) init: fn(&Aero) -> Result<(), ObtainError>,
 }
 
 impl ConstructibleResourceList for HNil {
-    fn construct<R: ResourceList>(_aero: &Aero<R>) -> anyhow::Result<()> {
+    fn construct<R: ResourceList>(
+        _aero: &Aero<R>,
+        _on_each: &mut dyn FnMut(&'static str),
+    ) -> anyhow::Result<()> {
         Ok(())
     }
+    fn initializers(_out: &mut Vec<Initializer>) {}
 }
 
 impl<H: ConstructibleResource, T: ConstructibleResourceList> ConstructibleResourceList
     for HCons<H, T>
 {
-    fn construct<R: ResourceList>(aero: &Aero<R>) -> anyhow::Result<()> {
-        aero.try_init::<H>().map_err(Into::into)?;
-        T::construct(aero)
+    fn construct<R: ResourceList>(
+        aero: &Aero<R>,
+        on_each: &mut dyn FnMut(&'static str),
+    ) -> anyhow::Result<()> {
+        on_each(type_name::<H>());
+        aero.try_init::<H>()?;
+        T::construct(aero, on_each)
+    }
+    fn initializers(out: &mut Vec<Initializer>) {
+        out.push(Initializer {
+            id: TypeId::of::<H>(),
+            name: type_name::<H>(),
+            init: Aero::try_init::<H>,
+        });
+        T::initializers(out)
     }
 }
 
+/// Result of running the constructor for `T` on a separate thread.
+type ConstructResult<T> = thread::Result<Result<T, <T as IndirectlyConstructible>::Error>>;
+
+/// A timed construction of `T` whose caller gave up waiting for it. The next attempt to
+/// construct `T` waits for it rather than starting another thread.
+struct AbandonedConstruction<T: IndirectlyConstructible>(Mutex<mpsc::Receiver<ConstructResult<T>>>);
+
+/// Name used to identify `T` in `tracing` spans.
+#[cfg(feature = "tracing")]
+pub(crate) fn tracing_name<T: IndirectlyConstructible>() -> &'static str {
+    T::TRACING_NAME.unwrap_or_else(std::any::type_name::<T>)
+}
+
 impl<R: ResourceList> Aero<R> {
-    /// Try to get or construct an instance of `T`.
-    pub fn try_obtain<T: ConstructibleResource>(&self) -> Result<T, T::Error> {
-        match self.try_get_slot() {
-            Some(SlotDesc::Filled(x)) => Ok(x),
-            Some(SlotDesc::Placeholder) | None => match self.wait_for_slot::<T>(true) {
-                Some(x) => Ok(x),
-                None => match T::construct(self.as_ref()) {
-                    Ok(x) => {
-                        self.fill_placeholder::<T>(x.clone());
-                        Ok(x)
-                    }
-                    Err(e) => {
-                        self.clear_placeholder::<T>();
-                        Err(e)
-                    }
-                },
-            },
+    /// Construct a new instance of `T` which is not stored.
+    fn construct_transient<T: ConstructibleResource>(&self) -> Result<T, ObtainError> {
+        self.check_sealed::<T>()?;
+        self.record_construction::<T>()?;
+        let depth = self.enter_construction::<T>();
+        let res = self
+            .run_constructor::<T>()
+            .map_err(|e| self.construct_failed::<T>(e))?;
+        drop(depth);
+        match res {
+            Ok(x) => {
+                let x = self.decorate(x);
+                self.run_post_construct(&x);
+                Ok(x)
+            }
+            Err(e) => Err(self.construct_failed::<T>(e)),
+        }
+    }
+    /// Construct `T` into the placeholder owned by the current thread.
+    fn construct_placeholder<T: ConstructibleResource>(&self) -> Result<T, ObtainError> {
+        let guard = PlaceholderGuard::new(self);
+        self.check_sealed::<T>()?;
+        self.record_construction::<T>()?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("construct", resource = tracing_name::<T>()).entered();
+        let depth = self.enter_construction::<T>();
+        let res = match self.run_constructor::<T>() {
+            Ok(res) => res,
+            Err(e) => return Err(guard.fail(self.construct_failed::<T>(e))),
+        };
+        drop(depth);
+        let x = match res {
+            Ok(x) => self.decorate(x),
+            Err(e) => return Err(guard.fail(self.construct_failed::<T>(e))),
+        };
+        guard.fill(x.clone());
+        self.run_post_construct(&x);
+        Ok(x)
+    }
+    /// Run the constructor for `T`, enforcing its `CONSTRUCT_TIMEOUT` if it has one.
+    fn run_constructor<T: ConstructibleResource>(
+        &self,
+    ) -> Result<Result<T, T::Error>, ObtainError> {
+        match T::CONSTRUCT_TIMEOUT {
+            Some(timeout) => self.construct_with_timeout::<T>(timeout),
+            None => Ok(T::construct(self.as_ref())),
         }
     }
+    /// Construct `T` on a separate thread, giving up if it takes longer than `timeout`.
+    /// If an earlier construction of `T` was abandoned, this waits for it instead of
+    /// starting another. Panics in the constructor are propagated to the caller.
+    fn construct_with_timeout<T: ConstructibleResource>(
+        &self,
+        timeout: Duration,
+    ) -> Result<Result<T, T::Error>, ObtainError> {
+        let abandoned = self
+            .inner
+            .write()
+            .abandoned
+            .remove::<AbandonedConstruction<T>>();
+        let rx = match abandoned {
+            Some(AbandonedConstruction(rx)) => rx.into_inner(),
+            None => self.spawn_constructor::<T>(),
+        };
+        match rx.recv_timeout(timeout) {
+            Ok(Ok(res)) => Ok(res),
+            Ok(Err(payload)) => panic::resume_unwind(payload),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                self.inner
+                    .write()
+                    .abandoned
+                    .insert(AbandonedConstruction::<T>(Mutex::new(rx)));
+                Err(ObtainError::ConstructTimedOut(type_name::<T>()))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                unreachable!("Constructor thread exited without a result")
+            }
+        }
+    }
+    /// Run the constructor for `T` on a new thread, returning a receiver for its result.
+    fn spawn_constructor<T: ConstructibleResource>(&self) -> mpsc::Receiver<ConstructResult<T>> {
+        let (tx, rx) = mpsc::channel();
+        let aero: Aero = self.clone().into();
+        let stack = construction_stack();
+        #[cfg(feature = "tracing")]
+        let span = tracing::Span::current();
+        thread::spawn(move || {
+            inherit_construction_stack(stack);
+            #[cfg(feature = "tracing")]
+            let _span = span.entered();
+            let res = panic::catch_unwind(panic::AssertUnwindSafe(|| T::construct(&aero)));
+            // If the caller times out, the receiver is kept until the next attempt
+            // to construct `T` collects the result.
+            let _ = tx.send(res);
+        });
+        rx
+    }
+    /// Try to get or construct an instance of `T`. As well as errors from the
+    /// constructor, this reports the container being sealed, construction timing out,
+    /// and failures cached by `cache_failures()`.
+    pub fn try_obtain<T: ConstructibleResource>(&self) -> Result<T, ObtainError> {
+        let slot = match self.lookup_for_obtain::<T>() {
+            Lookup::Transient => return self.construct_transient::<T>(),
+            Lookup::Linked(source) => return source.try_obtain::<T>(),
+            Lookup::Slot(slot) => slot,
+        };
+        let x = match slot {
+            Some(SlotDesc::Filled(x)) => x,
+            Some(SlotDesc::Failed(e)) => return Err(ObtainError::Failed(e)),
+            Some(SlotDesc::Placeholder) | None => {
+                match self.wait_for_slot::<T>(true).map_err(ObtainError::Failed)? {
+                    Some(x) => x,
+                    None => self.construct_placeholder::<T>()?,
+                }
+            }
+        };
+        self.mark_accessed::<T>();
+        Ok(x)
+    }
+    /// Construct a fresh instance of `T` against `overrides` layered over `self`.
+    /// Resources needed during construction are taken from `overrides` if present
+    /// there, and from `self` otherwise. Anything constructed along the way,
+    /// including `T` itself, is stored in `overrides` rather than `self`, so `self`
+    /// is left unchanged. Useful for testing a `Constructible` implementation in
+    /// isolation.
+    ///
+    /// Panics if `overrides` already contains `T`, or is already layered over an
+    /// instance other than `self`.
+    pub fn try_obtain_with_overrides<T: ConstructibleResource>(
+        &self,
+        overrides: Aero,
+    ) -> Result<T, ObtainError> {
+        overrides.layer_overrides::<T>(self.clone().into(), thread::current().into());
+        overrides.construct_placeholder::<T>()
+    }
     /// Get or construct an instance of `T`. Panics if unable.
     pub fn obtain<T: ConstructibleResource>(&self) -> T {
         unwrap_constructed::<T, _>(self.try_obtain::<T>())
     }
+    /// Get or construct an instance of `Arc<T>`. Panics if unable. Equivalent to
+    /// `obtain::<Arc<T>>()`.
+    pub fn obtain_arc<T>(&self) -> Arc<T>
+    where
+        Arc<T>: ConstructibleResource,
+    {
+        self.obtain()
+    }
+    /// Try to get or construct a shared instance of `T`. `T` does not need to implement
+    /// `Clone`: it is stored as an `Arc<T>`, so this is equivalent to calling
+    /// `try_obtain::<Arc<T>>()`.
+    pub fn try_obtain_shared<T: IndirectlyConstructible>(&self) -> Result<Arc<T>, ObtainError> {
+        self.try_obtain()
+    }
+    /// Get or construct a shared instance of `T`. Panics if unable. See `try_obtain_shared()`.
+    pub fn obtain_shared<T: IndirectlyConstructible>(&self) -> Arc<T> {
+        self.obtain()
+    }
+    /// Try to get or construct a shared instance of `C`, upcast to `Arc<Tr>` using the
+    /// coercion registered with `register_coercion::<Arc<C>, Tr>()`, eg.
+    /// `aero.try_obtain_as::<SmtpMailer, dyn Mailer>()`. Only the `Arc<C>` is stored,
+    /// so there is no need to also store an `Arc<dyn Tr>` from `after_construction()`.
+    /// Returns `ObtainError::Missing` for `Arc<Tr>` if no such coercion has been
+    /// registered.
+    pub fn try_obtain_as<C: IndirectlyConstructible, Tr: ?Sized + Send + Sync + 'static>(
+        &self,
+    ) -> Result<Arc<Tr>, ObtainError> {
+        let coerce = self
+            .coercion::<Arc<C>, Tr>()
+            .ok_or_else(|| ObtainError::Missing(MissingResource::new::<Arc<Tr>>()))?;
+        Ok(coerce(self.try_obtain::<Arc<C>>()?))
+    }
+    /// Get or construct a shared instance of `C`, upcast to `Arc<Tr>`. Panics if unable,
+    /// including if no coercion has been registered. See `try_obtain_as()`.
+    pub fn obtain_as<C: IndirectlyConstructible, Tr: ?Sized + Send + Sync + 'static>(
+        &self,
+    ) -> Arc<Tr> {
+        if self.coercion::<Arc<C>, Tr>().is_none() {
+            missing_coercion::<Arc<C>, Tr>()
+        }
+        unwrap_constructed::<Arc<C>, _>(self.try_obtain_as::<C, Tr>())
+    }
+    /// Try to get or construct an instance of `T`, along with the generation it belongs
+    /// to. Compare the generation against `current_generation()` to check whether the
+    /// instance has since been replaced or reset.
+    pub fn try_obtain_versioned<T: ConstructibleResource>(&self) -> Result<(T, u64), ObtainError> {
+        loop {
+            let generation = self.current_generation::<T>();
+            let value = self.try_obtain::<T>()?;
+            // Retry if `T` was constructed or replaced whilst we were obtaining it
+            if self.current_generation::<T>() == generation {
+                return Ok((value, generation));
+            }
+        }
+    }
+    /// Get or construct an instance of `T`, along with the generation it belongs to.
+    /// Panics if unable. See `try_obtain_versioned()`.
+    pub fn obtain_versioned<T: ConstructibleResource>(&self) -> (T, u64) {
+        unwrap_constructed::<T, _>(self.try_obtain_versioned::<T>())
+    }
     /// Try to initialize an instance of `T`. Does nothing if `T` is already initialized.
-    pub fn try_init<T: ConstructibleResource>(&self) -> Result<(), T::Error> {
-        match self.wait_for_slot::<T>(true) {
-            Some(_) => Ok(()),
-            None => match T::construct(self.as_ref()) {
-                Ok(x) => {
-                    self.fill_placeholder::<T>(x);
-                    Ok(())
-                }
-                Err(e) => {
-                    self.clear_placeholder::<T>();
-                    Err(e)
-                }
-            },
+    /// Errors are reported as for `try_obtain()`.
+    pub fn try_init<T: ConstructibleResource>(&self) -> Result<(), ObtainError> {
+        match self
+            .wait_for_presence::<T>(true)
+            .map_err(ObtainError::Failed)?
+        {
+            Some(()) => Ok(()),
+            None => self.construct_placeholder::<T>().map(|_| ()),
         }
     }
     /// Initialize an instance of `T`. Does nothing if `T` is already initialized. Panics if unable.
     pub fn init<T: ConstructibleResource>(&self) {
         unwrap_constructed::<T, _>(self.try_init::<T>())
     }
+    /// Get the instance of `T`, calling `f` to create it if it does not exist. `f` is
+    /// called at most once per aerosol instance: concurrent callers wait for the first
+    /// call to finish, and later calls return the existing instance. Useful for one-time
+    /// setup with side effects, such as installing a global logger, where `T` is a
+    /// marker type recording that setup has happened.
+    pub fn initialize_once<T: Resource>(&self, f: impl FnOnce() -> T) -> T {
+        let res = self.wait_for_slot::<T>(true).map_err(ObtainError::Failed);
+        let x = match unwrap_constructed::<T, _>(res) {
+            Some(x) => x,
+            None => {
+                let guard = PlaceholderGuard::new(self);
+                let x = f();
+                guard.fill(x.clone());
+                x
+            }
+        };
+        self.mark_accessed::<T>();
+        x
+    }
 
     /// Builder method equivalent to calling `try_init()` but can be chained.
     pub fn try_with_constructed<T: ConstructibleResource>(
         self,
-    ) -> Result<Aero<HCons<T, R>>, T::Error> {
+    ) -> Result<Aero<HCons<T, R>>, ObtainError> {
         self.try_init::<T>()?;
         Ok(Aero {
             inner: self.inner,
@@ -180,7 +546,32 @@ impl<R: ResourceList> Aero<R> {
         R2: Sculptor<R, I> + ResourceList,
         <R2 as Sculptor<R, I>>::Remainder: ConstructibleResourceList,
     {
-        <<R2 as Sculptor<R, I>>::Remainder>::construct(&self)?;
+        self.try_construct_remaining_with_progress(|_| {})
+    }
+
+    /// Convert into a different variant of the Aero type. Any missing required resources
+    /// will be automatically constructed. `on_each` is called with the name of each
+    /// missing resource before it is initialized.
+    ///
+    /// If another thread is already constructing one of the missing resources, this waits
+    /// for it to finish. Should that construction fail, this retries the construction
+    /// itself, unless failure caching is enabled, in which case the cached error is
+    /// returned.
+    pub fn try_construct_remaining_with_progress<R2, I>(
+        self,
+        mut on_each: impl FnMut(&'static str),
+    ) -> anyhow::Result<Aero<R2>>
+    where
+        R2: Sculptor<R, I> + ResourceList,
+        <R2 as Sculptor<R, I>>::Remainder: ConstructibleResourceList,
+    {
+        let mut initializers = Vec::new();
+        <<R2 as Sculptor<R, I>>::Remainder>::initializers(&mut initializers);
+        self.sort_by_ordering_hints(&mut initializers, |item| item.id);
+        for item in initializers {
+            on_each(item.name);
+            (item.init)(self.as_ref())?;
+        }
         Ok(Aero {
             inner: self.inner,
             phantom: PhantomData,
@@ -198,11 +589,37 @@ impl<R: ResourceList> Aero<R> {
             self.try_construct_remaining(),
         )
     }
+
+    /// Convert into a different variant of the Aero type. Any missing required resources
+    /// will be automatically constructed. `on_each` is called with the name of each
+    /// missing resource before it is initialized. Panics if construction of any missing
+    /// resource fails.
+    pub fn construct_remaining_with_progress<R2, I>(
+        self,
+        on_each: impl FnMut(&'static str),
+    ) -> Aero<R2>
+    where
+        R2: Sculptor<R, I> + ResourceList,
+        <R2 as Sculptor<R, I>>::Remainder: ConstructibleResourceList,
+    {
+        unwrap_constructed_hlist::<<R2 as Sculptor<R, I>>::Remainder, _>(
+            self.try_construct_remaining_with_progress(on_each),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{convert::Infallible, thread::scope, time::Duration};
+    use std::{
+        convert::Infallible,
+        fmt::Debug,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Barrier, OnceLock,
+        },
+        thread::scope,
+        time::Duration,
+    };
 
     use crate::Aero;
 
@@ -226,6 +643,56 @@ mod tests {
         state.obtain::<Dummy>();
     }
 
+    #[test]
+    fn obtain_arc() {
+        let state = Aero::new();
+        let dummy = state.obtain_arc::<Dummy>();
+        assert!(Arc::ptr_eq(&dummy, &state.obtain::<Arc<Dummy>>()));
+    }
+
+    #[test]
+    fn obtain_versioned() {
+        let state = Aero::new();
+        assert_eq!(state.current_generation::<Dummy>(), 0);
+        let (_, generation) = state.obtain_versioned::<Dummy>();
+        assert_eq!(generation, 1);
+        assert_eq!(state.obtain_versioned::<Dummy>().1, generation);
+        state.replace(Dummy);
+        assert_eq!(state.current_generation::<Dummy>(), 2);
+        state.reset::<Dummy>();
+        assert_eq!(state.current_generation::<Dummy>(), 3);
+        assert_eq!(state.obtain_versioned::<Dummy>().1, 4);
+    }
+
+    #[derive(Debug, Clone)]
+    struct DummyNamed;
+
+    impl Constructible for DummyNamed {
+        type Error = Infallible;
+        const TRACING_NAME: Option<&'static str> = Some("app::dummy");
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn tracing_name() {
+        assert_eq!(
+            <Arc<DummyNamed> as IndirectlyConstructible>::TRACING_NAME,
+            Some("app::dummy")
+        );
+        assert_eq!(<Dummy as IndirectlyConstructible>::TRACING_NAME, None);
+    }
+
+    #[test]
+    fn obtain_box() {
+        let state = Aero::new();
+        state.obtain::<Box<Dummy>>();
+        assert!(state.has::<Box<Dummy>>());
+        assert!(!state.has::<Dummy>());
+    }
+
     #[test]
     fn obtain_race() {
         let state = Aero::new();
@@ -234,6 +701,22 @@ mod tests {
                 s.spawn(|| state.obtain::<Dummy>());
             }
         });
+        assert_eq!(state.construction_count::<Dummy>(), 1);
+    }
+
+    #[test]
+    fn obtain_race_clones() {
+        let state = Aero::new();
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let state = state.clone();
+                std::thread::spawn(move || state.obtain::<Dummy>())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(state.construction_count::<Dummy>(), 1);
     }
 
     #[derive(Debug, Clone)]
@@ -254,6 +737,13 @@ mod tests {
         state.obtain::<DummyRecursive>();
     }
 
+    #[test]
+    #[should_panic(expected = "Construction depth exceeded")]
+    fn obtain_max_depth() {
+        let state = Aero::new().with_max_depth(1);
+        state.obtain::<DummyRecursive>();
+    }
+
     #[test]
     fn obtain_recursive_race() {
         let state = Aero::new();
@@ -262,6 +752,8 @@ mod tests {
                 s.spawn(|| state.obtain::<DummyRecursive>());
             }
         });
+        assert_eq!(state.construction_count::<DummyRecursive>(), 1);
+        assert_eq!(state.construction_count::<Dummy>(), 1);
     }
 
     #[derive(Debug, Clone)]
@@ -283,6 +775,54 @@ mod tests {
         state.obtain::<DummyCyclic>();
     }
 
+    #[derive(Debug, Clone)]
+    struct DummyDeadlockA;
+
+    #[derive(Debug, Clone)]
+    struct DummyDeadlockB;
+
+    /// Ensures both threads own their first placeholder before either
+    /// requests the other resource. Retried constructions skip the barrier.
+    fn rendezvous(aero: &Aero) {
+        static ARRIVALS: AtomicUsize = AtomicUsize::new(0);
+        if ARRIVALS.fetch_add(1, Ordering::SeqCst) < 2 {
+            aero.try_get::<Arc<Barrier>>().unwrap().wait();
+        }
+    }
+
+    impl Constructible for DummyDeadlockA {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            rendezvous(aero);
+            aero.obtain::<DummyDeadlockB>();
+            Ok(Self)
+        }
+    }
+
+    impl Constructible for DummyDeadlockB {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            rendezvous(aero);
+            aero.obtain::<DummyDeadlockA>();
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn obtain_deadlock() {
+        let state = Aero::new().with(Arc::new(Barrier::new(2)));
+        let errors = scope(|s| {
+            let a = s.spawn(|| state.obtain::<DummyDeadlockA>());
+            let b = s.spawn(|| state.obtain::<DummyDeadlockB>());
+            [a.join().unwrap_err(), b.join().unwrap_err()]
+        });
+        assert!(errors.iter().any(|e| e
+            .downcast_ref::<String>()
+            .is_some_and(|msg| msg.starts_with("Deadlock detected"))));
+    }
+
     #[derive(Debug)]
     struct DummyNonClone;
 
@@ -301,6 +841,14 @@ mod tests {
         state.obtain::<Arc<DummyNonClone>>();
     }
 
+    #[test]
+    fn obtain_shared() {
+        let state = Aero::new();
+        let dummy = state.obtain_shared::<DummyNonClone>();
+        assert!(Arc::ptr_eq(&dummy, &state.obtain_shared::<DummyNonClone>()));
+        assert!(state.has::<Arc<DummyNonClone>>());
+    }
+
     trait DummyTrait: Send + Sync {}
 
     #[derive(Debug)]
@@ -333,6 +881,39 @@ mod tests {
         state.try_get::<Arc<dyn DummyTrait>>().unwrap();
     }
 
+    #[test]
+    fn obtain_as() {
+        let state = Aero::new();
+        state.register_coercion::<Arc<DummyNonClone>, dyn Debug + Send + Sync>(|x| x);
+        let dummy = state.obtain_as::<DummyNonClone, dyn Debug + Send + Sync>();
+        assert_eq!(format!("{:?}", dummy), "DummyNonClone");
+        assert!(Arc::ptr_eq(
+            &(state.obtain_shared::<DummyNonClone>() as Arc<dyn Debug + Send + Sync>),
+            &dummy
+        ));
+        assert!(!state.has::<Arc<dyn Debug + Send + Sync>>());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "No coercion from `alloc::sync::Arc<aerosol::sync_constructible::tests::DummyNonClone>` to `alloc::sync::Arc<dyn core::fmt::Debug + core::marker::Send + core::marker::Sync>`"
+    )]
+    fn obtain_as_unregistered() {
+        let state = Aero::new();
+        state.obtain_as::<DummyNonClone, dyn Debug + Send + Sync>();
+    }
+
+    #[test]
+    fn try_obtain_as_unregistered() {
+        let state = Aero::new();
+        assert!(matches!(
+            state.try_obtain_as::<DummyNonClone, dyn Debug + Send + Sync>(),
+            Err(ObtainError::Missing(missing))
+                if missing == MissingResource::new::<Arc<dyn Debug + Send + Sync>>()
+        ));
+        assert!(!state.has::<Arc<DummyNonClone>>());
+    }
+
     #[test]
     fn with_constructed() {
         let state = Aero::new().with(42).with_constructed::<Dummy>().with("hi");
@@ -346,4 +927,528 @@ mod tests {
         state.get::<Dummy, _>();
         state.get::<DummyRecursive, _>();
     }
+
+    #[test]
+    fn construct_remaining_with_progress() {
+        let mut names = Vec::new();
+        let _state: Aero![i32, Dummy, DummyRecursive] = Aero::new()
+            .with(42)
+            .construct_remaining_with_progress(|name| names.push(name));
+        assert_eq!(names, [type_name::<Dummy>(), type_name::<DummyRecursive>()]);
+    }
+
+    #[test]
+    fn try_obtain_sealed() {
+        let state = Aero::new();
+        state.seal();
+        assert!(matches!(
+            state.try_obtain::<Dummy>(),
+            Err(ObtainError::Sealed(_))
+        ));
+        assert!(!state.has::<Dummy>());
+    }
+
+    #[test]
+    fn try_init_sealed() {
+        let state = Aero::new();
+        state.seal();
+        assert!(matches!(
+            state.try_init::<Dummy>(),
+            Err(ObtainError::Sealed(_))
+        ));
+        assert!(!state.has::<Dummy>());
+    }
+
+    #[derive(Debug, Clone)]
+    struct DummyFailing;
+
+    impl Constructible for DummyFailing {
+        type Error = anyhow::Error;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.try_get::<Arc<AtomicUsize>>()
+                .unwrap()
+                .fetch_add(1, Ordering::SeqCst);
+            Err(anyhow::anyhow!("Connection refused"))
+        }
+    }
+
+    #[test]
+    fn obtain_cached_failure() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let state = Aero::new().with(attempts.clone());
+        state.cache_failures(true);
+        for _ in 0..3 {
+            assert!(matches!(
+                state.try_obtain::<DummyFailing>(),
+                Err(ObtainError::Failed(_))
+            ));
+        }
+        assert!(matches!(
+            state.try_init::<DummyFailing>(),
+            Err(ObtainError::Failed(_))
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(!state.has::<DummyFailing>());
+
+        state.reset::<DummyFailing>();
+        assert!(state.try_obtain::<DummyFailing>().is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn try_obtain_cached_by_obtain() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let state = Aero::new().with(attempts.clone());
+        state.cache_failures(true);
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            state.obtain::<DummyFailing>();
+        }));
+        assert!(res.is_err());
+        assert!(matches!(
+            state.try_obtain::<DummyFailing>(),
+            Err(ObtainError::Failed(_))
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn try_obtain_constructor_error() {
+        let state = Aero::new().with(Arc::new(AtomicUsize::new(0)));
+        match state.try_obtain::<DummyFailing>() {
+            Err(ObtainError::Failed(e)) => assert_eq!(e.to_string(), "Connection refused"),
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn obtain_uncached_failure() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let state = Aero::new().with(attempts.clone());
+        for _ in 0..3 {
+            assert!(state.try_obtain::<DummyFailing>().is_err());
+        }
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    /// Fails slowly on the first attempt, and succeeds on every later attempt.
+    #[derive(Debug, Clone)]
+    struct DummyFlaky;
+
+    impl Constructible for DummyFlaky {
+        type Error = anyhow::Error;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            let attempts = aero.try_get::<Arc<AtomicUsize>>().unwrap();
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                std::thread::sleep(Duration::from_millis(100));
+                return Err(anyhow::anyhow!("Connection refused"));
+            }
+            Ok(Self)
+        }
+    }
+
+    type FlakyState = Aero![DummyFlaky, Arc<AtomicUsize>];
+
+    #[test]
+    fn construct_remaining_race() {
+        let state = Aero::new();
+        scope(|s| {
+            for _ in 0..100 {
+                s.spawn(|| {
+                    let _: Aero![Dummy, DummyRecursive] = state.clone().construct_remaining();
+                });
+            }
+        });
+        assert_eq!(state.construction_count::<Dummy>(), 1);
+        assert_eq!(state.construction_count::<DummyRecursive>(), 1);
+    }
+
+    #[test]
+    fn construct_remaining_retries_failed_race() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let state = Aero::new().with(attempts.clone());
+        scope(|s| {
+            s.spawn(|| assert!(state.try_obtain::<DummyFlaky>().is_err()));
+            std::thread::sleep(Duration::from_millis(20));
+            let _: FlakyState = state.clone().construct_remaining();
+        });
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn construct_remaining_cached_failed_race() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let state = Aero::new().with(attempts.clone());
+        state.cache_failures(true);
+        scope(|s| {
+            s.spawn(|| assert!(state.try_obtain::<DummyFlaky>().is_err()));
+            std::thread::sleep(Duration::from_millis(20));
+            let res: anyhow::Result<FlakyState> = state.clone().try_construct_remaining();
+            assert!(res.is_err());
+        });
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    static LARGE_CLONES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct DummyLarge(Vec<u8>);
+
+    impl Clone for DummyLarge {
+        fn clone(&self) -> Self {
+            LARGE_CLONES.fetch_add(1, Ordering::SeqCst);
+            Self(self.0.clone())
+        }
+    }
+
+    impl Constructible for DummyLarge {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(vec![0; 1 << 20]))
+        }
+    }
+
+    #[test]
+    fn init_present_does_not_clone() {
+        let state = Aero::new();
+        state.init::<DummyLarge>();
+        let clones = LARGE_CLONES.load(Ordering::SeqCst);
+        for _ in 0..10 {
+            state.init::<DummyLarge>();
+        }
+        assert_eq!(LARGE_CLONES.load(Ordering::SeqCst), clones);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct DummyConfig(&'static str);
+
+    impl Constructible for DummyConfig {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self("production"))
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct DummyClient(DummyConfig, i32);
+
+    impl Constructible for DummyClient {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(aero.obtain(), aero.try_get().unwrap()))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct DummyNeedsFailing;
+
+    impl Constructible for DummyNeedsFailing {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            aero.obtain::<DummyFailing>();
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Whilst constructing:\n    `aerosol::sync_constructible::tests::DummyNeedsFailing`"
+    )]
+    fn obtain_failure_stack() {
+        Aero::new()
+            .with(Arc::new(AtomicUsize::new(0)))
+            .obtain::<DummyNeedsFailing>();
+    }
+
+    #[derive(Clone)]
+    struct DummyOnce {
+        cell: Arc<OnceLock<usize>>,
+    }
+
+    impl Constructible for DummyOnce {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self {
+                cell: Arc::new(OnceLock::new()),
+            })
+        }
+    }
+
+    #[test]
+    fn obtain_once_lock() {
+        let state = Aero::new();
+        let count = AtomicUsize::new(0);
+        let init = || count.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(*state.obtain::<DummyOnce>().cell.get_or_init(init), 0);
+        assert_eq!(*state.obtain::<DummyOnce>().cell.get_or_init(init), 0);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[derive(Clone)]
+    struct DummyRouter {
+        aero: Aero,
+    }
+
+    impl Constructible for DummyRouter {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self {
+                aero: aero.handle(),
+            })
+        }
+    }
+
+    #[test]
+    fn obtain_with_handle() {
+        let state = Aero::new();
+        let router = state.obtain::<DummyRouter>();
+        assert!(!state.has::<Dummy>());
+        router.aero.obtain::<Dummy>();
+        assert!(state.has::<Dummy>());
+    }
+
+    #[test]
+    fn obtain_with_overrides() {
+        let state = Aero::new().with(42);
+        let client = state
+            .try_obtain_with_overrides::<DummyClient>(Aero::new().with(DummyConfig("test")).into())
+            .unwrap();
+        assert_eq!(client, DummyClient(DummyConfig("test"), 42));
+        assert!(!state.has::<DummyConfig>());
+        assert!(!state.has::<DummyClient>());
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate resource")]
+    fn obtain_with_overrides_existing() {
+        let state = Aero::new().with(42);
+        let overrides = Aero::new()
+            .with(DummyConfig("test"))
+            .with(DummyClient(DummyConfig("existing"), 0));
+        let _ = state.try_obtain_with_overrides::<DummyClient>(overrides.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "already layered over a different aerosol instance")]
+    fn obtain_with_overrides_other_parent() {
+        let overrides: Aero = Aero::new().with(DummyConfig("test")).into();
+        let _ = Aero::new()
+            .with(1)
+            .try_obtain_with_overrides::<DummyClient>(overrides.clone());
+        overrides.reset::<DummyClient>();
+        let _ = Aero::new()
+            .with(2)
+            .try_obtain_with_overrides::<DummyClient>(overrides);
+    }
+
+    #[derive(Debug, Clone)]
+    struct LoggerInstalled;
+
+    #[test]
+    fn initialize_once() {
+        let state = Aero::new();
+        let calls = AtomicUsize::new(0);
+        scope(|s| {
+            for _ in 0..10 {
+                s.spawn(|| {
+                    state.initialize_once(|| {
+                        std::thread::sleep(Duration::from_millis(20));
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        LoggerInstalled
+                    })
+                });
+            }
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(state.has::<LoggerInstalled>());
+    }
+
+    #[derive(Debug, Clone)]
+    struct ContextAware(Vec<&'static str>);
+
+    impl Constructible for ContextAware {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            unreachable!()
+        }
+
+        fn construct_in_ctx(_aero: &Aero, ctx: &ConstructCtx) -> Result<Self, Self::Error> {
+            Ok(Self(ctx.chain().to_vec()))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct ContextAwareOuter(ContextAware);
+
+    impl Constructible for ContextAwareOuter {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(aero.obtain()))
+        }
+    }
+
+    #[test]
+    fn obtain_in_ctx() {
+        let state = Aero::new();
+        let chain = state.obtain::<ContextAwareOuter>().0 .0;
+        assert_eq!(
+            chain,
+            [
+                type_name::<ContextAwareOuter>(),
+                type_name::<ContextAware>()
+            ]
+        );
+    }
+
+    #[test]
+    fn obtain_present_only_reads() {
+        let state = Aero::new();
+        state.init::<Dummy>();
+        // Any attempt to take the write lock would block until the guard is dropped.
+        let guard = state.read();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = {
+            let state = state.clone();
+            std::thread::spawn(move || {
+                state.obtain::<Dummy>();
+                state.init::<Dummy>();
+                tx.send(()).unwrap();
+            })
+        };
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("Obtaining a present resource took the write lock");
+        drop(guard);
+        handle.join().unwrap();
+    }
+
+    #[derive(Debug, Clone)]
+    struct SlowToConnect;
+
+    impl Constructible for SlowToConnect {
+        type Error = Infallible;
+        const CONSTRUCT_TIMEOUT: Option<Duration> = Some(Duration::from_millis(20));
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            if aero.has::<i32>() {
+                std::thread::sleep(Duration::from_secs(1));
+            }
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn construct_timeout() {
+        let state = Aero::new().with(1);
+        assert!(matches!(
+            state.try_obtain::<SlowToConnect>(),
+            Err(ObtainError::ConstructTimedOut(_))
+        ));
+        assert!(!state.has::<SlowToConnect>());
+        assert_eq!(state.failure_stats()[0].count(), 1);
+        assert_eq!(
+            state.failure_stats()[0].last_kind(),
+            crate::ObtainErrorKind::ConstructTimedOut
+        );
+
+        // Timeouts are cached like any other failure.
+        state.cache_failures(true);
+        for _ in 0..2 {
+            let err = state.try_obtain::<SlowToConnect>().unwrap_err();
+            assert!(err.to_string().ends_with("timed out"));
+        }
+        assert_eq!(state.construction_count::<SlowToConnect>(), 2);
+
+        // The timeout is inherited by wrappers, and does not apply to fast constructors.
+        let state = Aero::new();
+        assert_eq!(
+            <Arc<SlowToConnect> as IndirectlyConstructible>::CONSTRUCT_TIMEOUT,
+            <SlowToConnect as Constructible>::CONSTRUCT_TIMEOUT
+        );
+        state.obtain::<Arc<SlowToConnect>>();
+
+        // Transient resources are bounded by the timeout too.
+        let state = Aero::new().with(1);
+        state.register::<SlowToConnect>(crate::Lifetime::Transient);
+        assert!(matches!(
+            state.try_obtain::<SlowToConnect>(),
+            Err(ObtainError::ConstructTimedOut(_))
+        ));
+    }
+
+    static STUCK_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone)]
+    struct Stuck;
+
+    impl Constructible for Stuck {
+        type Error = Infallible;
+        const CONSTRUCT_TIMEOUT: Option<Duration> = Some(Duration::from_millis(20));
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            STUCK_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn construct_timeout_reuses_thread() {
+        let state = Aero::new();
+        for _ in 0..3 {
+            assert!(matches!(
+                state.try_obtain::<Stuck>(),
+                Err(ObtainError::ConstructTimedOut(_))
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(300));
+        // The abandoned constructor has finished, so its result is used.
+        state.obtain::<Stuck>();
+        assert_eq!(STUCK_ATTEMPTS.load(Ordering::SeqCst), 1);
+    }
+
+    #[derive(Debug, Clone)]
+    struct TimedChain(Vec<&'static str>);
+
+    impl Constructible for TimedChain {
+        type Error = Infallible;
+        const CONSTRUCT_TIMEOUT: Option<Duration> = Some(Duration::from_secs(10));
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            unreachable!()
+        }
+
+        fn construct_in_ctx(_aero: &Aero, ctx: &ConstructCtx) -> Result<Self, Self::Error> {
+            Ok(Self(ctx.chain().to_vec()))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct TimedChainOuter(TimedChain);
+
+    impl Constructible for TimedChainOuter {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(aero.obtain()))
+        }
+    }
+
+    #[test]
+    fn construct_timeout_keeps_chain() {
+        let state = Aero::new();
+        let outer = state.obtain::<TimedChainOuter>();
+        assert_eq!(
+            outer.0 .0,
+            [type_name::<TimedChainOuter>(), type_name::<TimedChain>()]
+        );
+    }
 }
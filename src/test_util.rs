@@ -0,0 +1,33 @@
+//! Fixtures shared between the unit tests of different modules.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+/// The names of `test_resource!` types, in the order they were constructed. Only
+/// recorded if an instance is present in the container.
+pub(crate) type ConstructionLog = Arc<Mutex<Vec<&'static str>>>;
+
+/// Declare unit structs which are constructible from any container, and record their
+/// construction in its `ConstructionLog`, if it has one.
+macro_rules! test_resource {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[derive(Clone)]
+            struct $name;
+
+            impl $crate::Constructible for $name {
+                type Error = ::std::convert::Infallible;
+
+                fn construct(aero: &$crate::Aero) -> Result<Self, Self::Error> {
+                    if let Some(log) = aero.try_get::<$crate::test_util::ConstructionLog>() {
+                        log.lock().push(stringify!($name));
+                    }
+                    Ok($name)
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use test_resource;
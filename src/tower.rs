@@ -0,0 +1,123 @@
+//! Integration with `tower` middleware.
+//!
+//! Provides the `ServiceBuilderExt` trait, which allows layers to be configured
+//! from resources when building a service stack. This lets configuration such as
+//! timeouts or rate limits be injected, rather than hardcoded.
+//!
+//! Example usage:
+//! ```rust
+//! use std::{convert::Infallible, time::Duration};
+//!
+//! use aerosol::{tower::ServiceBuilderExt, Aero, Constructible};
+//! use tower::{timeout::TimeoutLayer, ServiceBuilder};
+//!
+//! #[derive(Clone)]
+//! struct TimeoutConfig {
+//!     timeout: Duration,
+//! }
+//!
+//! impl Constructible for TimeoutConfig {
+//!     type Error = Infallible;
+//!     fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+//!         Ok(Self { timeout: Duration::from_secs(30) })
+//!     }
+//! }
+//!
+//! let aero = Aero::new();
+//! let service = ServiceBuilder::new()
+//!     .layer_from(&aero, |config: TimeoutConfig| TimeoutLayer::new(config.timeout))
+//!     .service_fn(|req: u32| async move { Ok::<_, Infallible>(req) });
+//! ```
+
+use tower::{layer::util::Stack, ServiceBuilder};
+
+use crate::{resource::unwrap_constructed, Aero, ConstructibleResource, ObtainError, ResourceList};
+
+/// Extension methods for `tower::ServiceBuilder`.
+pub trait ServiceBuilderExt<L>: Sized {
+    /// Add a layer built from a resource, which is obtained from `aero` first.
+    /// Fails if the resource cannot be obtained.
+    fn try_layer_from<T, M, R>(
+        self,
+        aero: &Aero<R>,
+        f: impl FnOnce(T) -> M,
+    ) -> Result<ServiceBuilder<Stack<M, L>>, ObtainError>
+    where
+        T: ConstructibleResource,
+        R: ResourceList;
+
+    /// Add a layer built from a resource, which is obtained from `aero` first.
+    /// Panics if the resource cannot be obtained.
+    fn layer_from<T, M, R>(
+        self,
+        aero: &Aero<R>,
+        f: impl FnOnce(T) -> M,
+    ) -> ServiceBuilder<Stack<M, L>>
+    where
+        T: ConstructibleResource,
+        R: ResourceList,
+    {
+        unwrap_constructed::<T, _>(self.try_layer_from(aero, f))
+    }
+}
+
+impl<L> ServiceBuilderExt<L> for ServiceBuilder<L> {
+    fn try_layer_from<T, M, R>(
+        self,
+        aero: &Aero<R>,
+        f: impl FnOnce(T) -> M,
+    ) -> Result<ServiceBuilder<Stack<M, L>>, ObtainError>
+    where
+        T: ConstructibleResource,
+        R: ResourceList,
+    {
+        Ok(self.layer(f(aero.try_obtain::<T>()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use tower::{layer::util::Identity, ServiceBuilder};
+
+    use crate::{Aero, Constructible, MissingResource, ObtainError};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct RateLimitConfig {
+        per_second: u64,
+    }
+
+    impl Constructible for RateLimitConfig {
+        type Error = MissingResource;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            let per_second = aero
+                .try_get::<u64>()
+                .ok_or_else(MissingResource::new::<u64>)?;
+            Ok(Self { per_second })
+        }
+    }
+
+    #[test]
+    fn layer_from() {
+        let aero = Aero::new().with(10u64);
+        let mut configured = None;
+        ServiceBuilder::new()
+            .layer_from(&aero, |config: RateLimitConfig| {
+                configured = Some(config.per_second);
+                Identity::new()
+            })
+            .service_fn(|()| async { Ok::<_, Infallible>(()) });
+        assert_eq!(configured, Some(10));
+    }
+
+    #[test]
+    fn layer_from_missing() {
+        let res = ServiceBuilder::new()
+            .try_layer_from(&Aero::new(), |_: RateLimitConfig| Identity::new());
+        assert!(matches!(res, Err(ObtainError::Missing(_))));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::slot::ThreadOrWaker;
+
+/// A thread or task blocked on a resource which is being constructed by
+/// another thread or task.
+#[derive(Debug)]
+struct WaitEdge {
+    waiter: ThreadOrWaker,
+    owner: ThreadOrWaker,
+    /// ID of the placeholder being waited on.
+    placeholder: u64,
+    resource: &'static str,
+}
+
+/// Tracks which threads or tasks are waiting on placeholders owned by which
+/// other threads or tasks, so that deadlocks can be detected rather than
+/// hanging forever.
+#[derive(Debug, Default)]
+pub(crate) struct WaitGraph {
+    edges: Vec<WaitEdge>,
+}
+
+impl WaitGraph {
+    /// Record that `waiter` is blocked on `resource`, which is currently being
+    /// constructed by `owner` and has the given placeholder ID. If this would complete a cycle, the edge is not
+    /// added and the names of the resources making up the cycle are returned,
+    /// starting with `resource`.
+    pub fn add(
+        &mut self,
+        waiter: ThreadOrWaker,
+        owner: ThreadOrWaker,
+        placeholder: u64,
+        resource: &'static str,
+    ) -> Result<(), Vec<&'static str>> {
+        self.remove(&waiter);
+
+        let mut cycle = vec![resource];
+        let mut current = &owner;
+        // Each waiter has at most one outgoing edge, so a path can never be
+        // longer than the number of edges.
+        for _ in 0..self.edges.len() {
+            if *current == waiter {
+                break;
+            }
+            match self.edges.iter().find(|edge| edge.waiter == *current) {
+                Some(edge) => {
+                    cycle.push(edge.resource);
+                    current = &edge.owner;
+                }
+                None => break,
+            }
+        }
+        if *current == waiter {
+            cycle.push(resource);
+            return Err(cycle);
+        }
+
+        self.edges.push(WaitEdge {
+            waiter,
+            owner,
+            placeholder,
+            resource,
+        });
+        Ok(())
+    }
+
+    /// Record that `waiter` is no longer blocked.
+    pub fn remove(&mut self, waiter: &ThreadOrWaker) {
+        self.edges.retain(|edge| edge.waiter != *waiter);
+    }
+
+    /// Record that nobody is blocked on the placeholder with this ID any more, as
+    /// it has been filled or removed.
+    pub fn remove_placeholder(&mut self, placeholder: u64) {
+        self.edges.retain(|edge| edge.placeholder != placeholder);
+    }
+}
@@ -0,0 +1,110 @@
+//! A resource which can change over time. Requires feature `watch`.
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// A resource whose value can be updated after construction, for example
+/// configuration which is reloaded whilst the application is running. Requires
+/// feature `watch`.
+///
+/// Every clone shares the same underlying value. Each clone separately tracks
+/// which updates it has seen, so that `changed()` can be used to wait for the
+/// next update.
+///
+/// If `T` is constructible, then so is `Watchable<T>`, with the constructed
+/// value used as the initial value.
+#[derive(Debug)]
+pub struct Watchable<T> {
+    sender: Arc<watch::Sender<T>>,
+    receiver: watch::Receiver<T>,
+}
+
+impl<T> Clone for Watchable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+impl<T> Watchable<T> {
+    /// Construct a new instance with the given initial value.
+    pub fn new(value: T) -> Self {
+        let (sender, receiver) = watch::channel(value);
+        Self {
+            sender: Arc::new(sender),
+            receiver,
+        }
+    }
+
+    /// Get the current value, marking it as seen by this handle.
+    pub fn get(&mut self) -> T
+    where
+        T: Clone,
+    {
+        self.receiver.borrow_and_update().clone()
+    }
+
+    /// Get the current value without marking it as seen.
+    pub fn peek(&self) -> T
+    where
+        T: Clone,
+    {
+        self.receiver.borrow().clone()
+    }
+
+    /// Replace the current value and notify every handle waiting in `changed()`.
+    pub fn set(&self, value: T) {
+        self.sender.send_replace(value);
+    }
+
+    /// Wait until the value is updated. Returns immediately if the value has been
+    /// updated since it was last seen by this handle.
+    pub async fn changed(&mut self) {
+        // Every handle holds the sender, so the channel can never be closed.
+        self.receiver
+            .changed()
+            .await
+            .expect("Watchable sender dropped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{Aero, Constructible};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Config(u32);
+
+    impl Constructible for Config {
+        type Error = Infallible;
+
+        fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Self(1))
+        }
+    }
+
+    #[test]
+    fn obtain() {
+        let state = Aero::new();
+        let mut config: Watchable<Config> = state.obtain();
+        assert_eq!(config.get(), Config(1));
+        state.obtain::<Watchable<Config>>().set(Config(2));
+        assert_eq!(config.get(), Config(2));
+    }
+
+    #[tokio::test]
+    async fn changed() {
+        let state = Aero::new().with(Watchable::new(Config(1)));
+        let mut config: Watchable<Config> = state.try_get().unwrap();
+        let producer: Watchable<Config> = state.try_get().unwrap();
+        let ((), ()) = tokio::join!(config.changed(), async { producer.set(Config(2)) });
+        assert_eq!(config.get(), Config(2));
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Weak},
+};
+
+use parking_lot::RwLock;
+
+use crate::{
+    resource::ResourceList,
+    state::{Aero, InnerAero},
+    sync_constructible::Constructible,
+};
+
+/// A non-owning handle to an aerosol instance, returned from `Aero::downgrade()`.
+///
+/// Resources which need to resolve collaborators lazily can store a `WeakAero` rather
+/// than a handle from `Aero::handle()`. A stored handle is a strong reference, so a
+/// resource holding one keeps the instance (and therefore itself) alive forever. A
+/// `WeakAero` does not, so the instance is dropped as normal once every other handle
+/// has gone away, after which `upgrade()` returns `None`.
+///
+/// `WeakAero` is constructible, so a constructor can obtain one like any other
+/// resource: `aero.obtain::<WeakAero>()`.
+#[derive(Clone)]
+pub struct WeakAero {
+    inner: Weak<RwLock<InnerAero>>,
+}
+
+impl fmt::Debug for WeakAero {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakAero")
+            .field("alive", &(self.inner.strong_count() > 0))
+            .finish()
+    }
+}
+
+impl WeakAero {
+    /// Get an owned handle to the instance, if it has not been dropped.
+    pub fn upgrade(&self) -> Option<Aero> {
+        Some(Aero {
+            inner: self.inner.upgrade()?,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl Constructible for WeakAero {
+    type Error = Infallible;
+
+    fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(aero.downgrade())
+    }
+}
+
+impl<R: ResourceList> Aero<R> {
+    /// Get a non-owning handle to this aerosol instance. See `WeakAero`.
+    pub fn downgrade(&self) -> WeakAero {
+        WeakAero {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{Aero, Constructible};
+
+    use super::WeakAero;
+
+    #[derive(Debug, Clone)]
+    struct Mailer {
+        aero: WeakAero,
+    }
+
+    impl Constructible for Mailer {
+        type Error = Infallible;
+
+        fn construct(aero: &Aero) -> Result<Self, Self::Error> {
+            Ok(Mailer {
+                aero: aero.obtain(),
+            })
+        }
+    }
+
+    #[test]
+    fn weak_aero() {
+        let state = Aero::new().with(25u16);
+        let mailer = state.obtain::<Mailer>();
+        let port = mailer.aero.upgrade().unwrap().try_get::<u16>();
+        assert_eq!(port, Some(25));
+
+        // The stored handle does not keep the instance alive.
+        drop(state);
+        assert!(mailer.aero.upgrade().is_none());
+    }
+}
@@ -0,0 +1,17 @@
+// Erased insertion must not be a way around the `Send + Sync + Clone` bounds
+// which `Resource` places on every stored type. Also covers the derive macros.
+//
+// Compiler diagnostics change between releases, so the expected output is only
+// checked against a single pinned toolchain. Update the version here (and in CI)
+// together with the `.stderr` files.
+#[rustversion::attr(not(stable(1.95)), ignore = "compiler output differs")]
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+    #[cfg(feature = "derive")]
+    {
+        cases.pass("tests/ui/derive/pass.rs");
+        cases.compile_fail("tests/ui/derive/fail_*.rs");
+    }
+}
@@ -0,0 +1,9 @@
+use aerosol::Constructible;
+
+#[derive(Clone, Constructible)]
+enum Mode {
+    Fast,
+    Slow,
+}
+
+fn main() {}
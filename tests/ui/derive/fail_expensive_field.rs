@@ -0,0 +1,10 @@
+use aerosol::Resource;
+
+#[derive(Clone, Resource)]
+struct Config {
+    name: String,
+    hosts: Vec<String>,
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use std::{rc::Rc, sync::Arc};
+
+use aerosol::Resource;
+
+#[derive(Clone, Resource)]
+struct Client {
+    #[aerosol(cheap_clone)]
+    cache: Rc<()>,
+    inner: Arc<()>,
+}
+
+fn main() {}
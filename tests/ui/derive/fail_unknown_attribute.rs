@@ -0,0 +1,9 @@
+use std::sync::Arc;
+
+use aerosol::Resource;
+
+#[derive(Clone, Resource)]
+#[aerosol(cheap)]
+struct Client(Arc<()>);
+
+fn main() {}
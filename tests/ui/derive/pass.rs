@@ -0,0 +1,67 @@
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use aerosol::{Aero, Constructible, Resource, WeakAero};
+
+#[derive(Clone, Resource)]
+struct Settings {
+    verbose: bool,
+    retries: Option<u32>,
+    name: &'static str,
+    shared: Arc<HashMap<String, String>>,
+}
+
+#[derive(Clone, Resource)]
+struct Service {
+    #[aerosol(cheap_clone)]
+    settings: Settings,
+    aero: Aero,
+}
+
+#[derive(Clone, Resource)]
+#[aerosol(expensive_clone)]
+struct Snapshot(Vec<u8>);
+
+#[derive(Clone, Resource)]
+enum Mode {
+    Fast,
+    Slow(Arc<String>, (u8, u8)),
+}
+
+#[derive(Clone, Resource)]
+struct Handle<T>(Arc<T>, PhantomData<T>);
+
+#[derive(Clone, Default)]
+struct Config(Arc<str>);
+
+impl Constructible for Config {
+    type Error = std::convert::Infallible;
+
+    fn construct(_aero: &Aero) -> Result<Self, Self::Error> {
+        Ok(Config("default".into()))
+    }
+}
+
+#[derive(Clone, Resource, Constructible)]
+struct Mailer {
+    #[aerosol(cheap_clone)]
+    config: Config,
+    #[aerosol(container)]
+    aero: Aero,
+}
+
+#[derive(Clone, Resource, Constructible)]
+struct Worker(#[aerosol(cheap_clone)] Mailer, WeakAero);
+
+#[derive(Clone, Constructible)]
+struct Marker;
+
+fn main() {
+    let state = Aero::new().with(Snapshot(Vec::new())).with(Mode::Fast);
+    state.insert(Handle(Arc::new(1), PhantomData));
+
+    let worker = state.obtain::<Worker>();
+    assert_eq!(&*worker.0.config.0, "default");
+    assert!(worker.0.aero.shares_storage_with(&state));
+    assert!(worker.1.upgrade().is_some());
+    state.obtain::<Marker>();
+}
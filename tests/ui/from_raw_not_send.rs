@@ -0,0 +1,14 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    rc::Rc,
+    sync::Arc,
+};
+
+use aerosol::Aero;
+
+fn main() {
+    let mut raw: HashMap<TypeId, Arc<dyn Any>> = HashMap::new();
+    raw.insert(TypeId::of::<Rc<i32>>(), Arc::new(Rc::new(42)));
+    let _state = Aero::from_raw(raw);
+}
@@ -0,0 +1,8 @@
+use aerosol::Aero;
+
+struct NotClone;
+
+fn main() {
+    let state = Aero::new();
+    state.insert_if_absent(NotClone);
+}
@@ -0,0 +1,8 @@
+use std::rc::Rc;
+
+use aerosol::Aero;
+
+fn main() {
+    let state = Aero::new();
+    state.insert(Rc::new(42));
+}